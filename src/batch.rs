@@ -0,0 +1,968 @@
+//! Batch generation of QR codes from CSV or JSON-lines records.
+//!
+//! A [Batch] renders one QR code per record on several threads and writes the results into a
+//! directory or a ZIP archive. Records that cannot be rendered, for example because their content
+//! is too long ([HelperError::DataTooLong]) or a color is malformed, are collected in the returned
+//! [BatchReport] while the rest of the batch carries on.
+//!
+//! # Record fields
+//!
+//! - `content`: the text to encode (required)
+//! - `filename`: the file name without extension, used unless a file name template is set
+//!   (defaults to the row number)
+//! - `fg_color`, `bg_color`: colors overriding those of the style, as `#rrggbb`
+//!
+//! Other fields are ignored by the renderer but can be referenced in file name templates. Empty
+//! fields count as missing.
+//!
+//! CSV input starts with a header row naming the fields and follows RFC 4180: fields containing
+//! commas, quotes or line breaks are quoted, and quotes inside them are doubled. JSON-lines input
+//! holds one object per line whose values are strings, numbers, booleans or `null`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use qirust::batch::{Batch, BatchOutput, InputFormat};
+//! use qirust::render::{QrStyle, RenderFormat};
+//!
+//! let csv = "content,sku,fg_color\n\
+//!            https://example.com/p/1,A-1,#000080\n\
+//!            https://example.com/p/2,A-2,\n";
+//! let batch = Batch::new(QrStyle::new())
+//!     .with_format(RenderFormat::Svg)
+//!     .with_filename_template("product-{sku}");
+//! let dir = std::env::temp_dir().join(format!("qirust-batch-doc-{}", std::process::id()));
+//! let output = BatchOutput::Directory(dir.clone());
+//! let report = batch.run(csv.as_bytes(), InputFormat::Csv, &output).unwrap();
+//! assert_eq!(report.written, 2);
+//! assert!(report.failures.is_empty());
+//! assert!(dir.join("product-A-1.svg").exists());
+//! # std::fs::remove_dir_all(&dir).unwrap();
+//! ```
+use crate::checksum::crc32;
+use crate::encoder::QrEncoder;
+use crate::helper::{hex_to_rgb, HelperError};
+use crate::qrcode::{EncodeTextOptions, Version};
+use crate::render::{QrStyle, RenderFormat, Renderer};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+// Fixed modification time of ZIP entries (1980-01-01 00:00), so archives are reproducible.
+const ZIP_DOS_TIME: u16 = 0;
+const ZIP_DOS_DATE: u16 = (1 << 5) | 1;
+// General purpose flag marking entry names as UTF-8.
+const ZIP_UTF8_FLAG: u16 = 1 << 11;
+
+/// Format of batch input records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON object per line
+    JsonLines,
+}
+
+/// Where a batch writes its files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOutput {
+    /// A directory, created if it does not exist. Existing files with the same names are replaced.
+    Directory(PathBuf),
+    /// A ZIP archive with uncompressed entries, created or replaced at the given path
+    Zip(PathBuf),
+}
+
+/// A record of named fields read from batch input.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BatchRecord {
+    /// Position of the record in the input, starting at 1 and not counting the CSV header
+    pub row: usize,
+    fields: Vec<(String, String)>,
+}
+
+impl BatchRecord {
+    pub fn new(row: usize) -> Self {
+        Self {
+            row,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn with_field(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.fields.push((name.into(), value.into()));
+        self
+    }
+
+    /// Returns the value of a field, or `None` if it is missing or empty
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+/// A record that could not be read, rendered or written.
+#[derive(Debug)]
+pub struct BatchFailure {
+    /// Row of the record, as in [BatchRecord::row]
+    pub row: usize,
+    /// File name of the record, if it could be determined
+    pub name: Option<String>,
+    pub error: HelperError,
+}
+
+impl fmt::Display for BatchFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "row {} ({}): {}", self.row, name, self.error),
+            None => write!(f, "row {}: {}", self.row, self.error),
+        }
+    }
+}
+
+/// Outcome of a batch run.
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// Number of files written
+    pub written: usize,
+    /// Records that failed, ordered by row
+    pub failures: Vec<BatchFailure>,
+}
+
+impl BatchReport {
+    /// Returns true if every record was written
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for BatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} written, {} failed",
+            self.written,
+            self.failures.len()
+        )?;
+        for failure in &self.failures {
+            write!(f, "\n  {}", failure)?;
+        }
+        Ok(())
+    }
+}
+
+/// Settings for rendering a batch of records.
+#[derive(Debug, Clone)]
+pub struct Batch<'a> {
+    /// Style applied to every record before its color overrides (defaults to [QrStyle::default])
+    pub style: QrStyle<'a>,
+    /// Output format, which also sets the file extension (defaults to [RenderFormat::Png])
+    pub format: RenderFormat,
+    /// File name template without extension, in which `{field}` is replaced by the value of a
+    /// record field and `{row}` by the row number (defaults to the `filename` field)
+    pub filename_template: Option<Cow<'a, str>>,
+    /// Number of rendering threads, or 0 for one per available CPU (defaults to 0)
+    pub threads: usize,
+}
+
+impl<'a> Batch<'a> {
+    pub fn new(style: QrStyle<'a>) -> Self {
+        Self {
+            style,
+            format: RenderFormat::Png,
+            filename_template: None,
+            threads: 0,
+        }
+    }
+
+    pub fn with_format(mut self, format: RenderFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_filename_template(mut self, template: impl Into<Cow<'a, str>>) -> Self {
+        self.filename_template = Some(template.into());
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Validates the settings before use
+    pub fn validate(&self) -> Result<(), HelperError> {
+        self.style.validate()?;
+        if let Some(template) = &self.filename_template {
+            template_parts(template)?;
+        }
+        Ok(())
+    }
+
+    /// Reads records from `input` and renders them into `output`.
+    ///
+    /// Rows that cannot be parsed are reported as failures along with those that fail to render.
+    /// Errors that affect the whole batch, such as invalid settings, unreadable input or a ZIP
+    /// archive that cannot be written, are returned as `Err`.
+    pub fn run(
+        &self,
+        input: impl BufRead,
+        format: InputFormat,
+        output: &BatchOutput,
+    ) -> Result<BatchReport, HelperError> {
+        self.validate()?;
+        let mut records = Vec::new();
+        let mut failures = Vec::new();
+        for record in read_records(input, format)? {
+            match record {
+                Ok(record) => records.push(record),
+                Err(failure) => failures.push(failure),
+            }
+        }
+        let mut report = self.run_records(&records, output)?;
+        failures.append(&mut report.failures);
+        failures.sort_by_key(|failure| failure.row);
+        report.failures = failures;
+        Ok(report)
+    }
+
+    /// Renders records into `output`, as [Batch::run] does after reading them.
+    pub fn run_records(
+        &self,
+        records: &[BatchRecord],
+        output: &BatchOutput,
+    ) -> Result<BatchReport, HelperError> {
+        self.validate()?;
+        let mut sink = match output {
+            BatchOutput::Directory(path) => {
+                fs::create_dir_all(path)?;
+                Sink::Directory(path.clone())
+            }
+            BatchOutput::Zip(path) => {
+                Sink::Zip(ZipWriter::new(BufWriter::new(File::create(path)?)))
+            }
+        };
+        let threads = match self.threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        }
+        .min(records.len())
+        .max(1);
+
+        let next_record = AtomicUsize::new(0);
+        let mut report = BatchReport::default();
+        thread::scope(|scope| -> Result<(), HelperError> {
+            let (sender, receiver) = mpsc::channel();
+            for _ in 0..threads {
+                let sender = sender.clone();
+                let next_record = &next_record;
+                scope.spawn(move || {
+                    let mut encoder = QrEncoder::new();
+                    loop {
+                        let index = next_record.fetch_add(1, Ordering::Relaxed);
+                        let Some(record) = records.get(index) else {
+                            break;
+                        };
+                        // The receiver is gone once writing has failed
+                        if sender
+                            .send((index, self.render(record, &mut encoder)))
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(sender);
+
+            // Files are written in input order, whatever order the threads finish in
+            let mut pending = BTreeMap::new();
+            let mut next_write = 0;
+            let mut names = HashSet::new();
+            for (index, result) in receiver {
+                pending.insert(index, result);
+                while let Some(result) = pending.remove(&next_write) {
+                    let row = records[next_write].row;
+                    next_write += 1;
+                    let (name, data) = match result {
+                        Ok(file) => file,
+                        Err(failure) => {
+                            report.failures.push(failure);
+                            continue;
+                        }
+                    };
+                    if !names.insert(name.clone()) {
+                        report.failures.push(BatchFailure {
+                            row,
+                            name: Some(name),
+                            error: HelperError::InvalidInput(
+                                "Duplicate file name in batch".to_string(),
+                            ),
+                        });
+                        continue;
+                    }
+                    match &mut sink {
+                        Sink::Directory(path) => {
+                            if let Err(e) = fs::write(path.join(&name), &data) {
+                                report.failures.push(BatchFailure {
+                                    row,
+                                    name: Some(name),
+                                    error: e.into(),
+                                });
+                                continue;
+                            }
+                        }
+                        Sink::Zip(zip) => zip.add(&name, &data)?,
+                    }
+                    report.written += 1;
+                }
+            }
+            Ok(())
+        })?;
+        if let Sink::Zip(zip) = sink {
+            zip.finish()?.flush()?;
+        }
+        Ok(report)
+    }
+
+    // Renders one record into its file name and contents.
+    fn render(
+        &self,
+        record: &BatchRecord,
+        encoder: &mut QrEncoder,
+    ) -> Result<(String, Vec<u8>), BatchFailure> {
+        let failure = |name: Option<&str>, error: HelperError| BatchFailure {
+            row: record.row,
+            name: name.map(str::to_string),
+            error,
+        };
+        let name = self.file_name(record).map_err(|e| failure(None, e))?;
+        let mut render = || -> Result<Vec<u8>, HelperError> {
+            let content = record
+                .get("content")
+                .ok_or_else(|| HelperError::InvalidInput("Content cannot be empty".to_string()))?;
+            let mut style = self.style.clone();
+            if let Some(color) = record.get("fg_color") {
+                style.fg_color = parse_color("fg_color", color)?;
+            }
+            if let Some(color) = record.get("bg_color") {
+                style.bg_color = parse_color("bg_color", color)?;
+            }
+            let mut code = encoder.encode_text(
+                content,
+                EncodeTextOptions {
+                    ecl: style.ecc,
+                    minversion: Version::MIN,
+                    maxversion: Version::MAX,
+                    mask: None,
+                    boostecl: true,
+                },
+            )?;
+            self.format.render(&code.as_qr_code(), &style)
+        };
+        render()
+            .map(|data| (name.clone(), data))
+            .map_err(|e| failure(Some(&name), e))
+    }
+
+    // Returns the file name of a record, with the extension of the output format.
+    fn file_name(&self, record: &BatchRecord) -> Result<String, HelperError> {
+        let mut name = match &self.filename_template {
+            Some(template) => {
+                let mut name = String::new();
+                for part in template_parts(template)? {
+                    match part {
+                        TemplatePart::Text(text) => name.push_str(text),
+                        TemplatePart::Field("row") => name.push_str(&record.row.to_string()),
+                        TemplatePart::Field(field) => {
+                            name.push_str(record.get(field).ok_or_else(|| {
+                                HelperError::InvalidInput(format!(
+                                    "Missing field {} for the file name",
+                                    field
+                                ))
+                            })?)
+                        }
+                    }
+                }
+                name
+            }
+            None => record
+                .get("filename")
+                .map_or_else(|| record.row.to_string(), str::to_string),
+        };
+        if name.is_empty()
+            || name == "."
+            || name == ".."
+            || name.contains(['/', '\\'])
+            || name.chars().any(char::is_control)
+        {
+            return Err(HelperError::InvalidInput(format!(
+                "Invalid file name: {:?}",
+                name
+            )));
+        }
+        name.push('.');
+        name.push_str(match self.format {
+            RenderFormat::Png => "png",
+            RenderFormat::Svg => "svg",
+            RenderFormat::Terminal => "txt",
+        });
+        Ok(name)
+    }
+}
+
+/// Reads batch records from CSV or JSON-lines input.
+///
+/// Rows that cannot be parsed are returned as failures in place of their record. Input that
+/// cannot be read at all, or a CSV header that cannot be parsed, is returned as `Err`.
+pub fn read_records(
+    mut input: impl BufRead,
+    format: InputFormat,
+) -> Result<Vec<Result<BatchRecord, BatchFailure>>, HelperError> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+    let invalid_row = |row: usize, reason: String| BatchFailure {
+        row,
+        name: None,
+        error: HelperError::InvalidInput(reason),
+    };
+
+    let mut records = Vec::new();
+    match format {
+        InputFormat::Csv => {
+            let mut rows = csv_rows(text).into_iter().filter(
+                |row| !matches!(row, Ok(fields) if fields.len() == 1 && fields[0].is_empty()),
+            );
+            let header = match rows.next() {
+                Some(Ok(header)) => header,
+                Some(Err(reason)) => {
+                    return Err(HelperError::InvalidInput(format!(
+                        "Invalid CSV header: {}",
+                        reason
+                    )))
+                }
+                None => return Ok(records),
+            };
+            for (i, row) in rows.enumerate() {
+                let row_number = i + 1;
+                records.push(match row {
+                    Ok(fields) if fields.len() == header.len() => Ok(BatchRecord {
+                        row: row_number,
+                        fields: header
+                            .iter()
+                            .map(|h| h.trim().to_string())
+                            .zip(fields)
+                            .collect(),
+                    }),
+                    Ok(fields) => Err(invalid_row(
+                        row_number,
+                        format!("Expected {} fields, found {}", header.len(), fields.len()),
+                    )),
+                    Err(reason) => Err(invalid_row(row_number, reason)),
+                });
+            }
+        }
+        InputFormat::JsonLines => {
+            let lines = text.lines().filter(|line| !line.trim().is_empty());
+            for (i, line) in lines.enumerate() {
+                let row = i + 1;
+                records.push(
+                    JsonParser::new(line)
+                        .object()
+                        .map(|fields| BatchRecord { row, fields })
+                        .map_err(|reason| invalid_row(row, reason)),
+                );
+            }
+        }
+    }
+    Ok(records)
+}
+
+fn parse_color(field: &str, value: &str) -> Result<[u8; 3], HelperError> {
+    hex_to_rgb(value).map_err(|e| HelperError::InvalidInput(format!("Invalid {}: {}", field, e)))
+}
+
+enum TemplatePart<'t> {
+    Text(&'t str),
+    Field(&'t str),
+}
+
+// Splits a file name template into literal text and `{field}` placeholders.
+fn template_parts(template: &str) -> Result<Vec<TemplatePart<'_>>, HelperError> {
+    let invalid = || {
+        HelperError::InvalidInput(format!(
+            "Invalid file name template {:?}: braces must enclose a field name",
+            template
+        ))
+    };
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        if rest[start..].starts_with('}') {
+            return Err(invalid());
+        }
+        let end = rest[start..].find('}').ok_or_else(invalid)? + start;
+        let field = &rest[start + 1..end];
+        if field.is_empty() || field.contains('{') {
+            return Err(invalid());
+        }
+        if start > 0 {
+            parts.push(TemplatePart::Text(&rest[..start]));
+        }
+        parts.push(TemplatePart::Field(field));
+        rest = &rest[end + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(TemplatePart::Text(rest));
+    }
+    Ok(parts)
+}
+
+// Splits CSV text into rows of fields as described in RFC 4180, accepting LF or CRLF line endings.
+// A row with a stray quote is returned as an error, and reading resumes after the end of its line.
+fn csv_rows(text: &str) -> Vec<Result<Vec<String>, String>> {
+    let mut chars = text.chars().peekable();
+    let mut rows = Vec::new();
+    while chars.peek().is_some() {
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut error = None;
+        'fields: loop {
+            let quoted = chars.peek() == Some(&'"');
+            if quoted {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => field.push(c),
+                        None => {
+                            rows.push(Err("Unterminated quoted field".to_string()));
+                            return rows;
+                        }
+                    }
+                }
+            }
+            loop {
+                match chars.next() {
+                    Some(',') => {
+                        fields.push(std::mem::take(&mut field));
+                        continue 'fields;
+                    }
+                    Some('\r') if chars.peek() == Some(&'\n') => {
+                        chars.next();
+                        break 'fields;
+                    }
+                    Some('\n') | None => break 'fields,
+                    Some(c) => {
+                        if quoted {
+                            error.get_or_insert("Unexpected character after a closing quote");
+                        } else if c == '"' {
+                            error.get_or_insert("Quote inside an unquoted field");
+                        }
+                        field.push(c);
+                    }
+                }
+            }
+        }
+        fields.push(field);
+        rows.push(match error {
+            Some(reason) => Err(reason.to_string()),
+            None => Ok(fields),
+        });
+    }
+    rows
+}
+
+// Parses single-line JSON objects with scalar values into fields. Strings keep their unescaped
+// value, numbers and booleans their literal text, and `null` fields are left out.
+struct JsonParser<'s> {
+    rest: &'s str,
+}
+
+impl<'s> JsonParser<'s> {
+    fn new(text: &'s str) -> Self {
+        Self { rest: text }
+    }
+
+    fn object(mut self) -> Result<Vec<(String, String)>, String> {
+        let mut fields = Vec::new();
+        self.expect('{')?;
+        if !self.eat('}') {
+            loop {
+                self.skip_whitespace();
+                let name = self.string()?;
+                self.expect(':')?;
+                self.skip_whitespace();
+                if let Some(value) = self.value()? {
+                    fields.push((name, value));
+                }
+                if !self.eat(',') {
+                    self.expect('}')?;
+                    break;
+                }
+            }
+        }
+        self.skip_whitespace();
+        if !self.rest.is_empty() {
+            return Err("Unexpected text after the JSON object".to_string());
+        }
+        Ok(fields)
+    }
+
+    fn value(&mut self) -> Result<Option<String>, String> {
+        for literal in ["true", "false"] {
+            if let Some(rest) = self.rest.strip_prefix(literal) {
+                self.rest = rest;
+                return Ok(Some(literal.to_string()));
+            }
+        }
+        if let Some(rest) = self.rest.strip_prefix("null") {
+            self.rest = rest;
+            return Ok(None);
+        }
+        match self.rest.chars().next() {
+            Some('"') => self.string().map(Some),
+            Some('-' | '0'..='9') => {
+                let len = self
+                    .rest
+                    .find(|c: char| !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'))
+                    .unwrap_or(self.rest.len());
+                let number = &self.rest[..len];
+                if number.parse::<f64>().is_err() {
+                    return Err(format!("Invalid number {}", number));
+                }
+                self.rest = &self.rest[len..];
+                Ok(Some(number.to_string()))
+            }
+            Some('{' | '[') => Err("Nested objects and arrays are not supported".to_string()),
+            _ => Err("Expected a JSON value".to_string()),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        if !self.rest.starts_with('"') {
+            return Err("Expected a JSON string".to_string());
+        }
+        let mut chars = self.rest[1..].char_indices();
+        let mut value = String::new();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.rest = &self.rest[i + 2..];
+                    return Ok(value);
+                }
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('"') => value.push('"'),
+                    Some('\\') => value.push('\\'),
+                    Some('/') => value.push('/'),
+                    Some('b') => value.push('\u{8}'),
+                    Some('f') => value.push('\u{c}'),
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some('u') => {
+                        let high = code_unit(&mut chars)?;
+                        let units = if (0xd800..0xdc00).contains(&high) {
+                            if chars.next().map(|(_, c)| c) != Some('\\')
+                                || chars.next().map(|(_, c)| c) != Some('u')
+                            {
+                                return Err("Unpaired surrogate in \\u escape".to_string());
+                            }
+                            vec![high, code_unit(&mut chars)?]
+                        } else {
+                            vec![high]
+                        };
+                        value.push_str(
+                            &String::from_utf16(&units)
+                                .map_err(|_| "Unpaired surrogate in \\u escape".to_string())?,
+                        );
+                    }
+                    _ => return Err("Invalid escape in JSON string".to_string()),
+                },
+                c if c < ' ' => return Err("Control character in JSON string".to_string()),
+                c => value.push(c),
+            }
+        }
+        Err("Unterminated JSON string".to_string())
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.rest = self.rest.trim_start_matches([' ', '\t', '\r', '\n']);
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        match self.rest.strip_prefix(c) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(format!("Expected '{}'", c))
+        }
+    }
+}
+
+// Reads the four hex digits of a `\u` escape.
+fn code_unit(chars: &mut impl Iterator<Item = (usize, char)>) -> Result<u16, String> {
+    let hex: String = chars.take(4).map(|(_, c)| c).collect();
+    u16::from_str_radix(&hex, 16)
+        .ok()
+        .filter(|_| hex.len() == 4)
+        .ok_or_else(|| format!("Invalid escape \\u{}", hex))
+}
+
+enum Sink {
+    Directory(PathBuf),
+    Zip(ZipWriter<BufWriter<File>>),
+}
+
+// Writes a ZIP archive of uncompressed entries. Rendered PNGs are already compressed, so storing
+// them costs little, and no compression library is needed.
+struct ZipWriter<W: Write> {
+    inner: W,
+    offset: u64,
+    // Central directory records, written when the archive is finished
+    directory: Vec<u8>,
+    entries: u16,
+}
+
+impl<W: Write> ZipWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            offset: 0,
+            directory: Vec::new(),
+            entries: 0,
+        }
+    }
+
+    fn add(&mut self, name: &str, data: &[u8]) -> io::Result<()> {
+        let too_large = || io::Error::other("ZIP archive exceeds 4 GiB or 65535 entries");
+        let size = u32::try_from(data.len()).map_err(|_| too_large())?;
+        let offset = u32::try_from(self.offset).map_err(|_| too_large())?;
+        let name_len = u16::try_from(name.len()).map_err(|_| too_large())?;
+        self.entries = self.entries.checked_add(1).ok_or_else(too_large)?;
+
+        // Fields shared by the local header and the central directory record, from the version
+        // needed to extract through the extra field length
+        let mut common = Vec::with_capacity(26);
+        for value in [10, ZIP_UTF8_FLAG, 0, ZIP_DOS_TIME, ZIP_DOS_DATE] {
+            common.extend_from_slice(&u16::to_le_bytes(value));
+        }
+        for value in [crc32(data), size, size] {
+            common.extend_from_slice(&value.to_le_bytes());
+        }
+        common.extend_from_slice(&name_len.to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+
+        let mut header = Vec::with_capacity(30 + name.len());
+        header.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        header.extend_from_slice(&common);
+        header.extend_from_slice(name.as_bytes());
+        self.inner.write_all(&header)?;
+        self.inner.write_all(data)?;
+        self.offset += header.len() as u64 + u64::from(size);
+
+        self.directory
+            .extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        self.directory.extend_from_slice(&20u16.to_le_bytes()); // Version made by
+        self.directory.extend_from_slice(&common);
+        // Comment length, disk number, internal and external attributes
+        self.directory.extend_from_slice(&[0; 10]);
+        self.directory.extend_from_slice(&offset.to_le_bytes());
+        self.directory.extend_from_slice(name.as_bytes());
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<W> {
+        let too_large = || io::Error::other("ZIP archive exceeds 4 GiB");
+        let directory_offset = u32::try_from(self.offset).map_err(|_| too_large())?;
+        let directory_len = u32::try_from(self.directory.len()).map_err(|_| too_large())?;
+        self.inner.write_all(&self.directory)?;
+
+        let mut end = Vec::with_capacity(22);
+        end.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        end.extend_from_slice(&[0; 4]); // Disk numbers
+        end.extend_from_slice(&self.entries.to_le_bytes());
+        end.extend_from_slice(&self.entries.to_le_bytes());
+        end.extend_from_slice(&directory_len.to_le_bytes());
+        end.extend_from_slice(&directory_offset.to_le_bytes());
+        end.extend_from_slice(&0u16.to_le_bytes()); // Comment length
+        self.inner.write_all(&end)?;
+        Ok(self.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("qirust-batch-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_csv_rows() {
+        let rows = csv_rows("a,b\r\n\"x, \"\"y\"\"\",\"line\nbreak\"\nbad\"quote,1\nlast,\n");
+        assert_eq!(rows[0], Ok(vec!["a".to_string(), "b".to_string()]));
+        assert_eq!(
+            rows[1],
+            Ok(vec!["x, \"y\"".to_string(), "line\nbreak".to_string()])
+        );
+        assert!(rows[2].is_err());
+        assert_eq!(rows[3], Ok(vec!["last".to_string(), String::new()]));
+        assert_eq!(rows.len(), 4);
+        assert!(csv_rows("a,\"open\n").last().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_json_objects() {
+        let fields =
+            JsonParser::new(r#" {"content": "café 😀\n", "n": -1.5e3, "ok": true, "x": null} "#)
+                .object()
+                .unwrap();
+        assert_eq!(
+            fields,
+            [
+                ("content".to_string(), "café 😀\n".to_string()),
+                ("n".to_string(), "-1.5e3".to_string()),
+                ("ok".to_string(), "true".to_string()),
+            ]
+        );
+        assert_eq!(JsonParser::new("{}").object(), Ok(vec![]));
+        for invalid in [
+            r#"{"a": [1]}"#,
+            r#"{"a": "b""#,
+            r#"{"a": "\ud83d"}"#,
+            r#"{"a": 1} x"#,
+            r#"{a: 1}"#,
+        ] {
+            assert!(JsonParser::new(invalid).object().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_file_names() {
+        let record = BatchRecord::new(7)
+            .with_field("sku", "A-1")
+            .with_field("filename", "");
+        let batch = Batch::new(QrStyle::new());
+        assert_eq!(batch.file_name(&record).unwrap(), "7.png");
+
+        let batch = batch
+            .with_format(RenderFormat::Svg)
+            .with_filename_template("{sku}_{row}");
+        assert_eq!(batch.file_name(&record).unwrap(), "A-1_7.svg");
+
+        let batch = batch.with_filename_template("{missing}");
+        assert!(batch.file_name(&record).is_err());
+        let record = record.with_field("path", "../x");
+        assert!(batch
+            .with_filename_template("{path}")
+            .file_name(&record)
+            .is_err());
+
+        for template in ["{", "}", "{}", "a{b{c}}"] {
+            assert!(Batch::new(QrStyle::new())
+                .with_filename_template(template)
+                .validate()
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_run_into_zip_with_failures() {
+        let long = "x".repeat(3000);
+        let jsonl = format!(
+            "{{\"content\": \"https://example.com/1\", \"filename\": \"one\"}}\n\
+             \n\
+             {{\"content\": \"{}\", \"filename\": \"long\"}}\n\
+             not json\n\
+             {{\"content\": \"https://example.com/2\", \"filename\": \"one\"}}\n\
+             {{\"content\": \"https://example.com/3\", \"fg_color\": \"#12345\"}}\n\
+             {{\"content\": \"https://example.com/4\", \"filename\": \"four\", \"bg_color\": \"#eeeeee\"}}\n",
+            long
+        );
+        let path = temp_path("codes.zip");
+        let report = Batch::new(QrStyle::new())
+            .with_format(RenderFormat::Svg)
+            .with_threads(3)
+            .run(
+                jsonl.as_bytes(),
+                InputFormat::JsonLines,
+                &BatchOutput::Zip(path.clone()),
+            )
+            .unwrap();
+        assert_eq!(report.written, 2);
+        let rows: Vec<usize> = report.failures.iter().map(|f| f.row).collect();
+        assert_eq!(rows, [2, 3, 4, 5]);
+        assert!(matches!(
+            report.failures[0].error,
+            HelperError::DataTooLong(_)
+        ));
+        assert!(report
+            .to_string()
+            .starts_with("2 written, 4 failed\n  row 2 (long.svg): "));
+
+        // Both stored entries are listed in the central directory in input order
+        let zip = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let end = &zip[zip.len() - 22..];
+        assert_eq!(end[..4], 0x0605_4b50u32.to_le_bytes());
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 2);
+        let directory = u32::from_le_bytes(end[16..20].try_into().unwrap()) as usize;
+        assert_eq!(&zip[directory + 46..directory + 53], b"one.svg");
+        assert_eq!(zip[..4], 0x0403_4b50u32.to_le_bytes());
+        let size = u32::from_le_bytes(zip[18..22].try_into().unwrap()) as usize;
+        let data = &zip[30 + 7..30 + 7 + size];
+        assert!(data.starts_with(b"<?xml") || data.starts_with(b"<svg"));
+        assert_eq!(
+            u32::from_le_bytes(zip[14..18].try_into().unwrap()),
+            crc32(data)
+        );
+    }
+
+    #[test]
+    fn test_run_into_directory() {
+        let csv = "content,id\n0123,a\nHELLO,b\n\"bad\"x,c\nhttps://example.com,d\n";
+        let path = temp_path("dir");
+        let report = Batch::new(QrStyle::new())
+            .with_filename_template("code-{id}")
+            .run(
+                csv.as_bytes(),
+                InputFormat::Csv,
+                &BatchOutput::Directory(path.clone()),
+            )
+            .unwrap();
+        let written: Vec<bool> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|id| path.join(format!("code-{}.png", id)).exists())
+            .collect();
+        fs::remove_dir_all(&path).unwrap();
+        assert_eq!(written, [true, true, false, true]);
+        assert_eq!(report.written, 3);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].row, 3);
+    }
+}
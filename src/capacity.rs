@@ -0,0 +1,342 @@
+//! Capacity planning for QR codes.
+//!
+//! [QrCode::encode_text] picks the smallest version that holds the data and fails with
+//! [DataTooLong](crate::qrcode::DataTooLong) when none does. A [CapacityPlan] answers the same
+//! question up front for every error correction level without encoding anything, so a user
+//! interface can show which version some text needs, how much room is left and how large the
+//! printed symbol will be.
+//!
+//! # Example
+//!
+//! ```rust
+//! use qirust::capacity::CapacityPlan;
+//! use qirust::qrcode::{QrCodeEcc, QrSegmentMode, Version};
+//!
+//! let plan = CapacityPlan::for_text("https://example.com/orders/2024");
+//! let fit = plan.fit(QrCodeEcc::Quartile).unwrap();
+//! assert_eq!(fit.version(), Version::new(3));
+//! assert_eq!(fit.size(), 29);
+//!
+//! // The text is encoded in byte mode, which holds 32 bytes at version 3-Q
+//! assert_eq!(fit.capacity(QrSegmentMode::Byte), 32);
+//! println!(
+//!     "Needs version {} at ECC Q; {} bytes left",
+//!     fit.version().value(),
+//!     fit.capacity(QrSegmentMode::Byte) - 31
+//! );
+//!
+//! // A 25 mm wide print with a 4-module quiet zone needs modules of about 0.68 mm
+//! assert!((fit.physical_size(0.68, 4) - 25.16).abs() < 1e-9);
+//! ```
+use crate::payload::with_segments;
+use crate::qrcode::{QrCode, QrCodeEcc, QrSegment, QrSegmentMode, Version};
+
+/// The smallest version that holds some data at each error correction level.
+///
+/// Versions are found as in [QrCode::encode_segments_to_codewords] with `boostecl` disabled, so
+/// encoding the same segments at a given level and version range produces [CapacityPlan::fit]'s
+/// version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityPlan {
+    fits: [Option<VersionFit>; 4],
+}
+
+impl CapacityPlan {
+    /// A plan in which nothing fits.
+    pub const EMPTY: CapacityPlan = CapacityPlan { fits: [None; 4] };
+
+    /// Plans the given segments within a version range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `minversion` is greater than `maxversion`.
+    pub fn for_segments(segs: &[QrSegment], minversion: Version, maxversion: Version) -> Self {
+        assert!(minversion <= maxversion, "Invalid value");
+        let mut fits = [None; 4];
+        for (fit, ecc) in fits.iter_mut().zip(QrCodeEcc::ALL) {
+            *fit = (minversion.value()..=maxversion.value()).find_map(|ver| {
+                let version = Version::new(ver);
+                let used_bits = QrSegment::get_total_bits(segs, version)?;
+                let capacity_bits = QrCode::get_num_data_codewords(version, ecc) * 8;
+                (used_bits <= capacity_bits).then_some(VersionFit {
+                    version,
+                    ecc,
+                    used_bits,
+                    capacity_bits,
+                })
+            });
+        }
+        Self { fits }
+    }
+
+    /// Plans text encoded as by [QrCode::encode_text], as a single numeric, alphanumeric or byte
+    /// segment, over the full version range.
+    pub fn for_text(text: &str) -> Self {
+        use QrSegmentMode::*;
+        if text.is_empty() {
+            return Self::for_segments(&[], Version::MIN, Version::MAX);
+        }
+        let mode = if QrSegment::is_numeric(text) {
+            Numeric
+        } else if QrSegment::is_alphanumeric(text) {
+            Alphanumeric
+        } else {
+            Byte
+        };
+        let len = match mode {
+            Byte => 0,
+            _ => match QrSegment::calc_buffer_size(mode, text.len()) {
+                Some(len) => len,
+                None => return Self::EMPTY,
+            },
+        };
+        let mut buffer = vec![0u8; len];
+        let seg = match mode {
+            Numeric => QrSegment::make_numeric(text, &mut buffer),
+            Alphanumeric => QrSegment::make_alphanumeric(text, &mut buffer),
+            _ => QrSegment::make_bytes(text.as_bytes()),
+        };
+        Self::for_segments(&[seg], Version::MIN, Version::MAX)
+    }
+
+    /// Plans text encoded as by [encode_segmented](crate::payload::encode_segmented), with long
+    /// numeric and alphanumeric runs in their own segments, over the full version range.
+    pub fn for_segmented_text(text: &str) -> Self {
+        with_segments(text, |segs| {
+            Self::for_segments(segs, Version::MIN, Version::MAX)
+        })
+        .unwrap_or(Self::EMPTY)
+    }
+
+    /// Returns the smallest version that holds the data at the given error correction level, or
+    /// `None` if no version in the range does.
+    pub fn fit(&self, ecc: QrCodeEcc) -> Option<VersionFit> {
+        self.fits[ecc as usize]
+    }
+
+    /// Returns the strongest error correction level whose version is at most `max_version`.
+    pub fn strongest_ecc(&self, max_version: Version) -> Option<VersionFit> {
+        self.fits
+            .iter()
+            .rev()
+            .flatten()
+            .find(|fit| fit.version <= max_version)
+            .copied()
+    }
+
+    /// Iterates over the levels that fit, from the lowest to the highest error tolerance.
+    pub fn fits(&self) -> impl Iterator<Item = VersionFit> + '_ {
+        self.fits.iter().flatten().copied()
+    }
+}
+
+/// The version chosen for some data at one error correction level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionFit {
+    version: Version,
+    ecc: QrCodeEcc,
+    used_bits: usize,
+    capacity_bits: usize,
+}
+
+impl VersionFit {
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    pub fn ecc(&self) -> QrCodeEcc {
+        self.ecc
+    }
+
+    /// Returns the number of data bits taken by the segments, including their headers
+    pub fn used_bits(&self) -> usize {
+        self.used_bits
+    }
+
+    /// Returns the number of data bits the version holds at this error correction level
+    pub fn capacity_bits(&self) -> usize {
+        self.capacity_bits
+    }
+
+    /// Returns the number of unused data bits
+    pub fn remaining_bits(&self) -> usize {
+        self.capacity_bits - self.used_bits
+    }
+
+    /// Returns how many characters of the given mode still fit in an additional segment, after
+    /// its mode indicator and character count header.
+    ///
+    /// Characters are digits in numeric mode and bytes in byte mode. ECI segments carry no
+    /// characters, so the result is 0 for [QrSegmentMode::Eci].
+    pub fn remaining(&self, mode: QrSegmentMode) -> usize {
+        max_chars(mode, self.version, self.remaining_bits())
+    }
+
+    /// Returns how many characters of the given mode a single segment can hold at this version
+    /// and error correction level.
+    ///
+    /// For data encoded as one segment of that mode, such as plain text passed to
+    /// [QrCode::encode_text], the difference between this and its length is the room left.
+    pub fn capacity(&self, mode: QrSegmentMode) -> usize {
+        capacity(self.version, self.ecc, mode)
+    }
+
+    /// Returns the width and height of the symbol in modules, without the quiet zone
+    pub fn size(&self) -> u32 {
+        u32::from(self.version.value()) * 4 + 17
+    }
+
+    /// Returns the width and height of the symbol including a quiet zone of `quiet_zone` modules
+    /// on each side, in the unit of `module_size`, such as pixels or millimetres.
+    pub fn physical_size(&self, module_size: f64, quiet_zone: u32) -> f64 {
+        f64::from(self.size() + 2 * quiet_zone) * module_size
+    }
+}
+
+/// Returns how many characters of the given mode a single segment can hold in a QR code of the
+/// given version and error correction level.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::capacity::capacity;
+/// use qirust::qrcode::{QrCodeEcc, QrSegmentMode, Version};
+///
+/// assert_eq!(capacity(Version::MIN, QrCodeEcc::Low, QrSegmentMode::Numeric), 41);
+/// assert_eq!(capacity(Version::MAX, QrCodeEcc::Low, QrSegmentMode::Byte), 2953);
+/// ```
+pub fn capacity(version: Version, ecc: QrCodeEcc, mode: QrSegmentMode) -> usize {
+    max_chars(
+        mode,
+        version,
+        QrCode::get_num_data_codewords(version, ecc) * 8,
+    )
+}
+
+// Returns the largest number of characters for which a segment of the given mode, including its
+// header, fits in `bits` data bits at the given version.
+fn max_chars(mode: QrSegmentMode, version: Version, bits: usize) -> usize {
+    use QrSegmentMode::*;
+    let ccbits = mode.num_char_count_bits(version);
+    let Some(bits) = bits.checked_sub(4 + usize::from(ccbits)) else {
+        return 0;
+    };
+    let chars = match mode {
+        // Three digits take 10 bits, and a trailing one or two digits 4 or 7 bits
+        Numeric => bits / 10 * 3 + [0, 0, 0, 0, 1, 1, 1, 2, 2, 2][bits % 10],
+        // Two characters take 11 bits, and a trailing one 6 bits
+        Alphanumeric => bits / 11 * 2 + usize::from(bits % 11 >= 6),
+        Byte => bits / 8,
+        Kanji => bits / 13,
+        Eci => 0,
+    };
+    // The character count must also fit its header field
+    chars.min((1 << ccbits) - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qrcode::{EncodeTextOptions, Mask};
+
+    #[test]
+    fn test_capacity_matches_standard_table() {
+        use QrSegmentMode::*;
+        let table = [
+            (1, QrCodeEcc::Low, [41, 25, 17, 10]),
+            (1, QrCodeEcc::High, [17, 10, 7, 4]),
+            (10, QrCodeEcc::Medium, [513, 311, 213, 131]),
+            (40, QrCodeEcc::Low, [7089, 4296, 2953, 1817]),
+            (40, QrCodeEcc::High, [3057, 1852, 1273, 784]),
+        ];
+        for (version, ecc, expected) in table {
+            let actual = [Numeric, Alphanumeric, Byte, Kanji]
+                .map(|mode| capacity(Version::new(version), ecc, mode));
+            assert_eq!(actual, expected, "version {} {:?}", version, ecc);
+        }
+    }
+
+    #[test]
+    fn test_plan_matches_encoder() {
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        for text in [
+            "",
+            "0123456789",
+            "HELLO WORLD",
+            &"https://example.com/".repeat(20),
+        ] {
+            let plan = CapacityPlan::for_text(text);
+            for ecl in QrCodeEcc::ALL {
+                let qr = QrCode::encode_text(
+                    text,
+                    &mut tempbuffer,
+                    &mut outbuffer,
+                    EncodeTextOptions {
+                        ecl,
+                        minversion: Version::MIN,
+                        maxversion: Version::MAX,
+                        mask: Some(Mask::new(0)),
+                        boostecl: false,
+                    },
+                )
+                .unwrap();
+                let fit = plan.fit(ecl).unwrap();
+                assert_eq!(fit.version(), qr.version());
+                assert_eq!(fit.size(), qr.size() as u32);
+            }
+        }
+    }
+
+    #[test]
+    fn test_remaining_capacity() {
+        // 17 bytes fill version 1-L exactly
+        let fit = CapacityPlan::for_text(&"a".repeat(16))
+            .fit(QrCodeEcc::Low)
+            .unwrap();
+        assert_eq!(fit.version(), Version::MIN);
+        assert_eq!(fit.capacity(QrSegmentMode::Byte), 17);
+        assert_eq!(fit.remaining_bits(), 12);
+        // An extra segment would need 12 header bits
+        assert_eq!(fit.remaining(QrSegmentMode::Byte), 0);
+
+        let fit = CapacityPlan::for_text("12345").fit(QrCodeEcc::Low).unwrap();
+        // 152 bits, less 4 + 10 + 17 for the segment and 14 for another header
+        assert_eq!(fit.remaining_bits(), 121);
+        assert_eq!(fit.remaining(QrSegmentMode::Numeric), 32);
+        assert_eq!(fit.remaining(QrSegmentMode::Alphanumeric), 19);
+        assert_eq!(fit.remaining(QrSegmentMode::Eci), 0);
+    }
+
+    #[test]
+    fn test_levels_that_do_not_fit() {
+        let plan = CapacityPlan::for_text(&"a".repeat(2000));
+        assert_eq!(
+            plan.fit(QrCodeEcc::Low).unwrap().version(),
+            Version::new(33)
+        );
+        assert!(plan.fit(QrCodeEcc::High).is_none());
+        assert_eq!(plan.fits().count(), 2);
+        assert_eq!(
+            plan.strongest_ecc(Version::MAX).unwrap().ecc(),
+            QrCodeEcc::Medium
+        );
+        assert!(plan.strongest_ecc(Version::new(30)).is_none());
+
+        let plan = CapacityPlan::for_text(&"a".repeat(3000));
+        assert_eq!(plan, CapacityPlan::EMPTY);
+    }
+
+    #[test]
+    fn test_segmented_text_needs_less_room() {
+        let text = format!("HTTPS://EXAMPLE.COM/{}?q=x", "ABCDEFGHIJ".repeat(20));
+        let single = CapacityPlan::for_text(&text)
+            .fit(QrCodeEcc::Medium)
+            .unwrap();
+        let segmented = CapacityPlan::for_segmented_text(&text)
+            .fit(QrCodeEcc::Medium)
+            .unwrap();
+        assert!(segmented.used_bits() < single.used_bits());
+        assert!(segmented.version() <= single.version());
+    }
+}
@@ -0,0 +1,341 @@
+//! Data URIs for embedding rendered codes inline.
+//!
+//! A `data:` URI carries the rendered code itself, so it can be used directly as the `src` of an
+//! HTML `<img>`, in CSS or in JSON responses without serving a separate file. [DataUri] wraps any
+//! renderer and returns its output as such a URI; the free functions convert output that was
+//! already rendered.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #[cfg(feature = "image")] {
+//! use qirust::data_uri::{DataUri, SvgDataEncoding};
+//! use qirust::qrcode::{EncodeTextOptions, QrCode, QrCodeEcc, Version};
+//! use qirust::render::{PngRenderer, QrStyle, Renderer, SvgRenderer};
+//!
+//! let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+//! let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+//! let qr = QrCode::encode_text(
+//!     "https://example.com",
+//!     &mut tempbuffer,
+//!     &mut outbuffer,
+//!     EncodeTextOptions {
+//!         ecl: QrCodeEcc::Medium,
+//!         minversion: Version::MIN,
+//!         maxversion: Version::MAX,
+//!         mask: None,
+//!         boostecl: true,
+//!     },
+//! ).unwrap();
+//!
+//! let style = QrStyle::new();
+//! let png = DataUri::new(PngRenderer).render(&qr, &style).unwrap();
+//! let svg = DataUri::new(SvgRenderer)
+//!     .with_svg_encoding(SvgDataEncoding::Base64)
+//!     .render(&qr, &style)
+//!     .unwrap();
+//! let html = format!("<img src=\"{}\" alt=\"QR code\">", png);
+//! # assert!(png.starts_with("data:image/png;base64,iVBORw0KGgo"));
+//! # assert!(svg.starts_with("data:image/svg+xml;base64,"));
+//! # }
+//! ```
+#[cfg(feature = "image")]
+use crate::helper::Base64Writer;
+use crate::helper::{encode_base64, HelperError};
+use crate::payload::percent_encode;
+#[cfg(feature = "image")]
+use crate::print::{PrintImage, PrintRenderer};
+use crate::qrcode::QrCode;
+#[cfg(feature = "image")]
+use crate::render::{ImageRenderer, PngRenderer, RasterRenderer, SwissQrRenderer};
+use crate::render::{QrStyle, RenderFormat, Renderer, SvgRenderer, TerminalRenderer};
+#[cfg(feature = "image")]
+use image::RgbaImage;
+#[cfg(feature = "image")]
+use std::io::Write;
+
+// Characters left unencoded in percent-encoded SVG besides the unreserved ones. Quotes, angle
+// brackets, `#`, `%` and whitespace are encoded, which keeps the URI valid in HTML attributes and
+// CSS `url()` values.
+const SVG_KEEP: &str = "/:=;,'()!*@$&+?";
+
+/// How an SVG document is encoded in a data URI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SvgDataEncoding {
+    /// Percent-encodes the characters that are not allowed in URIs, keeping the markup readable and
+    /// usually shorter than base64 (`data:image/svg+xml,...`)
+    #[default]
+    Percent,
+    /// Encodes the whole document in base64 (`data:image/svg+xml;base64,...`)
+    Base64,
+}
+
+/// Returns a `data:image/png;base64,` URI for PNG-encoded bytes.
+pub fn png_data_uri(png: &[u8]) -> String {
+    format!("data:image/png;base64,{}", encode_base64(png))
+}
+
+/// Returns a `data:image/svg+xml` URI for an SVG document.
+pub fn svg_data_uri(svg: &str, encoding: SvgDataEncoding) -> String {
+    match encoding {
+        SvgDataEncoding::Percent => format!("data:image/svg+xml,{}", percent_encode(svg, SVG_KEEP)),
+        SvgDataEncoding::Base64 => {
+            format!(
+                "data:image/svg+xml;base64,{}",
+                encode_base64(svg.as_bytes())
+            )
+        }
+    }
+}
+
+/// Returns a `data:text/plain` URI for text such as the output of [TerminalRenderer].
+pub fn text_data_uri(text: &str) -> String {
+    format!("data:text/plain;charset=utf-8,{}", percent_encode(text, ""))
+}
+
+/// Writes `img` as a `data:image/png;base64,` URI to `writer` and returns the writer.
+///
+/// The PNG encoder writes straight into a [Base64Writer], so the PNG bytes are never held in memory
+/// in full next to their encoding.
+#[cfg(feature = "image")]
+pub fn write_png_data_uri<W: Write>(img: &RgbaImage, mut writer: W) -> Result<W, HelperError> {
+    writer.write_all(b"data:image/png;base64,")?;
+    let mut encoder = Base64Writer::new(writer);
+    img.write_with_encoder(image::codecs::png::PngEncoder::new(&mut encoder))?;
+    Ok(encoder.finish()?)
+}
+
+// Returns `img` as a `data:image/png;base64,` URI.
+#[cfg(feature = "image")]
+fn image_data_uri(img: &RgbaImage) -> Result<String, HelperError> {
+    let uri = write_png_data_uri(img, Vec::new())?;
+    Ok(String::from_utf8(uri).expect("base64 output is ASCII"))
+}
+
+/// A renderer whose output can be written as a data URI by [DataUri].
+pub trait DataUriSource: Renderer {
+    /// Converts the rendered `output` into a data URI, encoding SVG documents with `svg_encoding`.
+    fn to_data_uri(
+        &self,
+        output: Self::Output,
+        svg_encoding: SvgDataEncoding,
+    ) -> Result<String, HelperError>;
+}
+
+#[cfg(feature = "image")]
+impl DataUriSource for RasterRenderer {
+    fn to_data_uri(&self, img: RgbaImage, _: SvgDataEncoding) -> Result<String, HelperError> {
+        image_data_uri(&img)
+    }
+}
+
+#[cfg(feature = "image")]
+impl DataUriSource for SwissQrRenderer {
+    fn to_data_uri(&self, img: RgbaImage, _: SvgDataEncoding) -> Result<String, HelperError> {
+        image_data_uri(&img)
+    }
+}
+
+#[cfg(feature = "image")]
+impl DataUriSource for PrintRenderer {
+    fn to_data_uri(&self, print: PrintImage, _: SvgDataEncoding) -> Result<String, HelperError> {
+        let mut png = Vec::new();
+        print.write_png(&mut png)?;
+        Ok(png_data_uri(&png))
+    }
+}
+
+#[cfg(feature = "image")]
+impl DataUriSource for PngRenderer {
+    fn to_data_uri(&self, png: Vec<u8>, _: SvgDataEncoding) -> Result<String, HelperError> {
+        Ok(png_data_uri(&png))
+    }
+}
+
+#[cfg(feature = "image")]
+impl DataUriSource for ImageRenderer {
+    fn to_data_uri(&self, bytes: Vec<u8>, _: SvgDataEncoding) -> Result<String, HelperError> {
+        Ok(format!(
+            "data:{};base64,{}",
+            self.format.media_type(),
+            encode_base64(&bytes)
+        ))
+    }
+}
+
+impl DataUriSource for SvgRenderer {
+    fn to_data_uri(&self, svg: String, encoding: SvgDataEncoding) -> Result<String, HelperError> {
+        Ok(svg_data_uri(&svg, encoding))
+    }
+}
+
+impl DataUriSource for TerminalRenderer {
+    fn to_data_uri(&self, text: String, _: SvgDataEncoding) -> Result<String, HelperError> {
+        Ok(text_data_uri(&text))
+    }
+}
+
+impl DataUriSource for RenderFormat {
+    fn to_data_uri(
+        &self,
+        bytes: Vec<u8>,
+        svg_encoding: SvgDataEncoding,
+    ) -> Result<String, HelperError> {
+        // SVG and terminal output are produced from strings, so they are valid UTF-8
+        let text = |bytes| String::from_utf8(bytes).expect("text output is UTF-8");
+        Ok(match self {
+            #[cfg(feature = "image")]
+            RenderFormat::Png => png_data_uri(&bytes),
+            RenderFormat::Svg => svg_data_uri(&text(bytes), svg_encoding),
+            RenderFormat::Terminal => text_data_uri(&text(bytes)),
+        })
+    }
+}
+
+/// Renders the output of another renderer as a data URI.
+///
+/// Raster images are encoded as PNG, SVG documents are percent-encoded or base64-encoded as set by
+/// [DataUri::with_svg_encoding], and terminal text is percent-encoded as `text/plain`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DataUri<R> {
+    /// The renderer whose output is encoded
+    pub renderer: R,
+    /// Encoding of SVG documents (defaults to [SvgDataEncoding::Percent])
+    pub svg_encoding: SvgDataEncoding,
+}
+
+impl<R: DataUriSource> DataUri<R> {
+    pub fn new(renderer: R) -> Self {
+        Self {
+            renderer,
+            svg_encoding: SvgDataEncoding::default(),
+        }
+    }
+
+    pub fn with_svg_encoding(mut self, encoding: SvgDataEncoding) -> Self {
+        self.svg_encoding = encoding;
+        self
+    }
+}
+
+impl<R: DataUriSource> Renderer for DataUri<R> {
+    type Output = String;
+
+    fn render(&self, qr: &QrCode, style: &QrStyle) -> Result<String, HelperError> {
+        let output = self.renderer.render(qr, style)?;
+        self.renderer.to_data_uri(output, self.svg_encoding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "image")]
+    use crate::helper::ImageOutputFormat;
+    use crate::helper::{encode_base64_url, Base64Writer};
+    #[cfg(feature = "image")]
+    use crate::qrcode::{EncodeTextOptions, QrCodeEcc, Version};
+    use std::io::Write;
+
+    #[test]
+    fn test_base64_variants_and_writer() {
+        for len in 0..20 {
+            let data: Vec<u8> = (0..len).map(|i| (i * 37 + 250) as u8).collect();
+            let standard = encode_base64(&data);
+            assert_eq!(
+                encode_base64_url(&data),
+                standard
+                    .trim_end_matches('=')
+                    .replace('+', "-")
+                    .replace('/', "_")
+            );
+
+            // Every way of splitting the input gives the same output as encoding it at once
+            for split in 0..=data.len() {
+                let mut writer = Base64Writer::new(Vec::new());
+                for part in [&data[..split], &data[split..]] {
+                    for byte in part.chunks(2) {
+                        writer.write_all(byte).unwrap();
+                    }
+                }
+                assert_eq!(writer.finish().unwrap(), standard.as_bytes());
+            }
+            let mut writer = Base64Writer::url_safe(Vec::new());
+            writer.write_all(&data).unwrap();
+            assert_eq!(
+                writer.finish().unwrap(),
+                encode_base64_url(&data).as_bytes()
+            );
+        }
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_data_uris_for_every_renderer() {
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let qr = QrCode::encode_text(
+            "Hello, World!",
+            &mut tempbuffer,
+            &mut outbuffer,
+            EncodeTextOptions {
+                ecl: QrCodeEcc::Low,
+                minversion: Version::MIN,
+                maxversion: Version::MAX,
+                mask: None,
+                boostecl: false,
+            },
+        )
+        .unwrap();
+        let style = QrStyle::new();
+
+        let png = PngRenderer.render(&qr, &style).unwrap();
+        let expected = png_data_uri(&png);
+        assert_eq!(
+            DataUri::new(PngRenderer).render(&qr, &style).unwrap(),
+            expected
+        );
+        assert_eq!(
+            DataUri::new(RenderFormat::Png).render(&qr, &style).unwrap(),
+            expected
+        );
+        // The streamed PNG matches the PNG renderer byte for byte
+        assert_eq!(
+            DataUri::new(RasterRenderer).render(&qr, &style).unwrap(),
+            expected
+        );
+        assert_eq!(
+            DataUri::new(ImageRenderer::default())
+                .render(&qr, &style)
+                .unwrap(),
+            expected
+        );
+        let jpeg = DataUri::new(ImageRenderer::new(ImageOutputFormat::Jpeg { quality: 80 }))
+            .render(&qr, &style)
+            .unwrap();
+        assert!(jpeg.starts_with("data:image/jpeg;base64,/9j/"));
+        let swiss = DataUri::new(SwissQrRenderer::new(72))
+            .render(&qr, &style)
+            .unwrap();
+        assert!(swiss.starts_with("data:image/png;base64,iVBORw0KGgo"));
+
+        let svg = SvgRenderer.render(&qr, &style).unwrap();
+        let percent = DataUri::new(SvgRenderer).render(&qr, &style).unwrap();
+        assert!(percent.starts_with("data:image/svg+xml,%3C?xml%20version=%221.0%22"));
+        assert!(!percent.contains(['<', '>', '"', '#', ' ', '\n']));
+        assert_eq!(
+            DataUri::new(RenderFormat::Svg)
+                .with_svg_encoding(SvgDataEncoding::Base64)
+                .render(&qr, &style)
+                .unwrap(),
+            format!(
+                "data:image/svg+xml;base64,{}",
+                encode_base64(svg.as_bytes())
+            )
+        );
+
+        let text = DataUri::new(TerminalRenderer).render(&qr, &style).unwrap();
+        assert!(text.starts_with("data:text/plain;charset=utf-8,%20%20"));
+        assert!(text.contains("%E2%96%88%E2%96%88"));
+    }
+}
@@ -0,0 +1,261 @@
+//! Reusable encoding with pooled buffers.
+//!
+//! The functions on [QrCode] write into caller-provided buffers and return a code that borrows
+//! them, and the `generate_*` helpers in [crate::helper] allocate a fresh pair of buffers on every
+//! call. A [QrEncoder] owns its working buffers and reuses them for every code it encodes, and
+//! returns each result as an [OwnedQrCode] that can outlive the encoder or be sent to another
+//! thread.
+//!
+//! # Example
+//!
+//! ```rust
+//! # #[cfg(feature = "image")] {
+//! use qirust::encoder::QrEncoder;
+//! use qirust::qrcode::{EncodeTextOptions, QrCodeEcc, Version};
+//! use qirust::render::{QrStyle, RasterRenderer, Renderer};
+//!
+//! let options = EncodeTextOptions {
+//!     ecl: QrCodeEcc::Medium,
+//!     minversion: Version::MIN,
+//!     maxversion: Version::MAX,
+//!     mask: None,
+//!     boostecl: true,
+//! };
+//! let mut encoder = QrEncoder::new();
+//! let mut codes = Vec::new();
+//! for label in ["LABEL-0001", "LABEL-0002", "LABEL-0003"] {
+//!     codes.push(encoder.encode_text(label, options).unwrap());
+//! }
+//!
+//! let img = RasterRenderer.render(&codes[0].as_qr_code(), &QrStyle::new()).unwrap();
+//! # assert!(img.width() > 0);
+//! # }
+//! ```
+use crate::qrcode::{DataTooLong, EncodeTextOptions, Mask, QrCode, QrCodeEcc, QrSegment, Version};
+
+/// An encoder that keeps its working buffers between calls.
+///
+/// Each encoder holds two buffers of [Version::buffer_len] bytes for [Version::MAX], allocated
+/// once in [QrEncoder::new]. The only allocation per call is the returned [OwnedQrCode], which is
+/// sized to the version of the code. Encoders are `Send`, so each worker thread can keep its own.
+#[derive(Debug, Clone)]
+pub struct QrEncoder {
+    outbuffer: Vec<u8>,
+    tempbuffer: Vec<u8>,
+}
+
+impl Default for QrEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QrEncoder {
+    pub fn new() -> Self {
+        Self {
+            outbuffer: vec![0u8; Version::MAX.buffer_len()],
+            tempbuffer: vec![0u8; Version::MAX.buffer_len()],
+        }
+    }
+
+    /// Encodes a text string as [QrCode::encode_text] does.
+    pub fn encode_text(
+        &mut self,
+        text: &str,
+        options: EncodeTextOptions,
+    ) -> Result<OwnedQrCode, DataTooLong> {
+        let qr = QrCode::encode_text(text, &mut self.tempbuffer, &mut self.outbuffer, options)?;
+        Ok(OwnedQrCode::from_qr_code(&qr))
+    }
+
+    /// Encodes binary data in byte mode as [QrCode::encode_binary] does.
+    pub fn encode_binary(
+        &mut self,
+        data: &[u8],
+        options: EncodeTextOptions,
+    ) -> Result<OwnedQrCode, DataTooLong> {
+        // The data is encoded from the front of the temporary buffer
+        if data.len() > self.tempbuffer.len() {
+            return Err(DataTooLong::SegmentTooLong);
+        }
+        self.tempbuffer[..data.len()].copy_from_slice(data);
+        let qr = QrCode::encode_binary(
+            &mut self.tempbuffer,
+            data.len(),
+            &mut self.outbuffer,
+            options,
+        )?;
+        Ok(OwnedQrCode::from_qr_code(&qr))
+    }
+
+    /// Encodes segments as [QrCode::encode_segments_to_codewords] and [QrCode::encode_codewords]
+    /// do, picking the smallest version within the range of `options`.
+    pub fn encode_segments(
+        &mut self,
+        segs: &[QrSegment],
+        options: EncodeTextOptions,
+    ) -> Result<OwnedQrCode, DataTooLong> {
+        let (datacodewordslen, ecl, version) = QrCode::encode_segments_to_codewords(
+            segs,
+            &mut self.outbuffer,
+            options.ecl,
+            options.minversion,
+            options.maxversion,
+            options.boostecl,
+        )?;
+        let qr = QrCode::encode_codewords(
+            &mut self.outbuffer,
+            datacodewordslen,
+            &mut self.tempbuffer,
+            ecl,
+            version,
+            options.mask,
+        );
+        Ok(OwnedQrCode::from_qr_code(&qr))
+    }
+}
+
+/// A QR code that owns its modules, as returned by [QrEncoder].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedQrCode {
+    // The size in modules followed by the modules packed bitwise, as in a QrCode's buffer
+    buffer: Vec<u8>,
+    ecc: QrCodeEcc,
+    mask: Mask,
+}
+
+impl OwnedQrCode {
+    /// Copies the modules of a borrowed QR code.
+    pub fn from_qr_code(qr: &QrCode) -> Self {
+        let mut buffer = Vec::with_capacity(qr.version().buffer_len());
+        buffer.push(qr.size() as u8);
+        buffer.extend_from_slice(qr.packed_modules());
+        Self {
+            buffer,
+            ecc: qr.error_correction_level(),
+            mask: qr.mask(),
+        }
+    }
+
+    /// Returns a [QrCode] view of the modules, for use with the renderers and helpers.
+    pub fn as_qr_code(&mut self) -> QrCode<'_> {
+        QrCode::from_buffer(&mut self.buffer)
+    }
+
+    pub fn version(&self) -> Version {
+        Version::new((self.buffer[0] - 17) / 4)
+    }
+
+    /// Returns the width and height in modules, in the range [21, 177]
+    pub fn size(&self) -> i32 {
+        i32::from(self.buffer[0])
+    }
+
+    pub fn error_correction_level(&self) -> QrCodeEcc {
+        self.ecc
+    }
+
+    pub fn mask(&self) -> Mask {
+        self.mask
+    }
+
+    /// Returns `true` for a dark module. Coordinates outside the code return `false`.
+    pub fn get_module(&self, x: i32, y: i32) -> bool {
+        let range = 0..self.size();
+        if !range.contains(&x) || !range.contains(&y) {
+            return false;
+        }
+        let index = (y * self.size() + x) as usize;
+        (self.buffer[1 + (index >> 3)] >> (index & 7)) & 1 != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(ecl: QrCodeEcc) -> EncodeTextOptions {
+        EncodeTextOptions {
+            ecl,
+            minversion: Version::MIN,
+            maxversion: Version::MAX,
+            mask: None,
+            boostecl: true,
+        }
+    }
+
+    #[test]
+    fn test_reused_buffers_match_fresh_encoding() {
+        let mut encoder = QrEncoder::new();
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        // Large codes first, so stale data would be left in the pooled buffers
+        let long = "https://example.com/".repeat(40);
+        for text in [long.as_str(), "0123456789", "HELLO WORLD", "hello", ""] {
+            let mut owned = encoder.encode_text(text, options(QrCodeEcc::Low)).unwrap();
+            let qr = QrCode::encode_text(
+                text,
+                &mut tempbuffer,
+                &mut outbuffer,
+                options(QrCodeEcc::Low),
+            )
+            .unwrap();
+            assert_eq!(owned.version(), qr.version());
+            assert_eq!(owned.error_correction_level(), qr.error_correction_level());
+            assert_eq!(owned.mask(), qr.mask());
+            assert!(owned.as_qr_code() == qr);
+            assert!((0..qr.size())
+                .all(|y| (0..qr.size()).all(|x| owned.get_module(x, y) == qr.get_module(x, y))));
+        }
+    }
+
+    #[test]
+    fn test_binary_and_segments() {
+        let mut encoder = QrEncoder::new();
+        let data: Vec<u8> = (0..=255).collect();
+        let binary = encoder
+            .encode_binary(&data, options(QrCodeEcc::Medium))
+            .unwrap();
+        let segments = encoder
+            .encode_segments(&[QrSegment::make_bytes(&data)], options(QrCodeEcc::Medium))
+            .unwrap();
+        assert_eq!(binary, segments);
+
+        let too_long = vec![0u8; 3000];
+        assert!(matches!(
+            encoder.encode_binary(&too_long, options(QrCodeEcc::Low)),
+            Err(DataTooLong::DataOverCapacity(..))
+        ));
+        assert!(matches!(
+            encoder.encode_binary(&vec![0u8; 5000], options(QrCodeEcc::Low)),
+            Err(DataTooLong::SegmentTooLong)
+        ));
+    }
+
+    #[test]
+    fn test_encoders_on_worker_threads() {
+        fn assert_send<T: Send>() {}
+        assert_send::<QrEncoder>();
+        assert_send::<OwnedQrCode>();
+
+        let handles: Vec<_> = (0..4)
+            .map(|worker| {
+                std::thread::spawn(move || {
+                    let mut encoder = QrEncoder::new();
+                    (0..25)
+                        .map(|i| {
+                            let text = format!("LABEL-{}-{:04}", worker, i);
+                            encoder.encode_text(&text, options(QrCodeEcc::Quartile))
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                        .unwrap()
+                })
+            })
+            .collect();
+        for handle in handles {
+            let codes = handle.join().unwrap();
+            assert_eq!(codes.len(), 25);
+            assert!(codes.iter().all(|code| code.version() == Version::MIN));
+        }
+    }
+}
@@ -6,9 +6,14 @@
 /// features like horizontal module grouping and caching, and it is written in safe, pure Rust
 /// without external dependencies.
 ///
+/// Styling is described by a single [QrStyle] and the destination of saved files by an
+/// [OutputConfig]. The older `QrConfig`, `FrameQrConfig`, `FrameQrSvgConfig` and
+/// `FrameQrSaveConfig` types are deprecated and convert into a [QrStyle] with [From].
+///
 /// # Features
 ///
 /// - Render QR codes in multiple formats: ASCII art, PNG, SVG, and in-memory buffers.
+/// - Write PBM, PGM, XBM and BMP bitmaps with only the standard library ([write_bitmap]).
 /// - Support styling with logos, custom colors, and square or rounded frames.
 /// - Optimized for performance with horizontal module grouping and caching for logo processing.
 /// - Safe and pure Rust implementation with no unsafe code.
@@ -18,84 +23,150 @@
 /// Generate a basic QR code as an in-memory image buffer:
 ///
 /// ```rust
-/// use qirust::helper::{generate_image_buffer, QrConfig};
-///
-/// let config = QrConfig::new()
-///     .with_border(4).unwrap()
-///     .with_scale(4).unwrap();
-/// let img = generate_image_buffer("Hello, World!", config)
+/// # #[cfg(feature = "image")] {
+/// use qirust::helper::generate_image_buffer;
+/// use qirust::render::QrStyle;
+///
+/// let style = QrStyle::new()
+///     .with_quiet_zone(4).unwrap()
+///     .with_module_size(4).unwrap();
+/// let img = generate_image_buffer("Hello, World!", style)
 ///     .expect("Failed to generate image buffer");
 /// # img.save("target/qr_example.png").ok();
+/// # }
 /// ```
 ///
 /// Generate a styled QR code with a logo and rounded frame:
 ///
 /// ```rust,no_run
-/// use qirust::helper::{generate_frameqr, FrameQrConfig, FrameStyle};
+/// # #[cfg(feature = "image")] {
+/// use qirust::helper::{generate_styled, FrameStyle, OutputConfig};
 /// use qirust::qrcode::QrCodeEcc;
+/// use qirust::render::QrStyle;
 ///
-/// let config = FrameQrConfig::new("src/logo.png").unwrap()
+/// let style = QrStyle::new()
 ///     .with_ecc(QrCodeEcc::High)
-///     .with_upscale(6).unwrap()
-///     .with_directory("output")
-///     .with_filename("styled_qr")
-///     .with_color([255, 165, 0])
-///     .with_outer_frame(40)
+///     .with_module_size(6).unwrap()
+///     .with_quiet_zone(7).unwrap()
+///     .with_fg_color([255, 165, 0])
+///     .with_logo("src/logo.png").unwrap()
 ///     .with_inner_frame(10)
 ///     .with_frame_style(FrameStyle::Rounded);
+/// let output = OutputConfig::new().with_directory("output").with_filename("styled_qr");
 ///
-/// generate_frameqr("https://example.com", config)
+/// generate_styled("https://example.com", &style, &output)
 ///     .expect("Failed to generate QR code");
+/// # }
 /// ```
 use crate::qrcode::{DataTooLong, EncodeTextOptions, QrCode, QrCodeEcc, Version};
+#[cfg(feature = "image")]
+use crate::render::{render_raster, render_svg, QrStyle, RasterRenderer, Renderer};
+#[cfg(feature = "image")]
+use ab_glyph::{point, Font, FontRef, Glyph, OutlineCurve, PxScale, ScaleFont};
+#[cfg(feature = "image")]
 use image::{
-    imageops::{overlay, replace, resize, FilterType},
-    DynamicImage, ImageBuffer, ImageFormat, Luma, Rgb, Rgba, RgbaImage,
+    imageops::overlay, DynamicImage, ImageBuffer, ImageFormat, Luma, Rgb, Rgba, RgbaImage,
 };
+#[cfg(feature = "image")]
 use std::{
-    env,
-    error::Error,
-    fmt,
-    fmt::Write,
+    borrow::Cow,
     fs,
-    io::Write as IoWrite,
-    path::{Path, PathBuf},
-    sync::Mutex,
+    path::Path,
+    sync::Arc,
     time::{SystemTime, UNIX_EPOCH},
 };
+use std::{error::Error, fmt, fmt::Write, path::PathBuf};
 
 // Constants for avoiding magic numbers
-const MAX_UPSCALE_FACTOR: u32 = 100;
-const MAX_BORDER_SIZE: u32 = 200;
-const MAX_IMAGE_DIMENSION: u32 = 50000;
+pub(crate) const MAX_UPSCALE_FACTOR: u32 = 100;
+pub(crate) const MAX_BORDER_SIZE: u32 = 200;
+pub(crate) const MAX_IMAGE_DIMENSION: u32 = 50000;
+pub(crate) const DEFAULT_BORDER_SIZE: u32 = 4;
+pub(crate) const DEFAULT_SCALE: u32 = 4;
+#[cfg(feature = "image")]
 const DEFAULT_UPSCALE_FACTOR: u32 = 8;
-const DEFAULT_BORDER_SIZE: u32 = 4;
-const DEFAULT_SCALE: u32 = 4;
-const LOGO_SIZE_DIVISOR: u32 = 3;
-const DEFAULT_INNER_FRAME: u32 = 3;
+#[cfg(feature = "image")]
+pub(crate) const LOGO_SIZE_DIVISOR: u32 = 3;
+#[cfg(feature = "image")]
+pub(crate) const DEFAULT_INNER_FRAME: u32 = 3;
+#[cfg(feature = "image")]
+const DEFAULT_CAPTION_FONT_SIZE: f32 = 24.0;
+#[cfg(feature = "image")]
+const DEFAULT_CAPTION_PADDING: u32 = 8;
+#[cfg(feature = "image")]
+const MAX_CAPTION_FONT_SIZE: f32 = 1000.0;
+#[cfg(feature = "image")]
+const MAX_CAPTION_PADDING: u32 = 1000;
 
 /// Custom error type untuk operasi helper QR code
 #[derive(Debug)]
 pub enum HelperError {
+    #[cfg(feature = "image")]
     ImageError(image::ImageError),
     DataTooLong(DataTooLong),
     IoError(std::io::Error),
     InvalidInput(String),
+    /// The logo file does not exist at the given path
+    LogoNotFound(PathBuf),
+    /// The logo file exists but could not be read or decoded as an image
+    #[cfg(feature = "image")]
+    LogoDecode {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+    /// The rendered image would exceed the maximum supported dimension
+    DimensionOverflow {
+        width: u64,
+        height: u64,
+        max: u32,
+    },
+    /// A global cache was poisoned by a panic in another thread
+    PoisonedCache(&'static str),
+    /// Writing formatted output such as SVG markup failed
+    FmtError(fmt::Error),
 }
 
 impl fmt::Display for HelperError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            #[cfg(feature = "image")]
             HelperError::ImageError(e) => write!(f, "Image error: {}", e),
             HelperError::DataTooLong(e) => write!(f, "Data too long: {:?}", e),
             HelperError::IoError(e) => write!(f, "IO error: {}", e),
             HelperError::InvalidInput(s) => write!(f, "Invalid input: {}", s),
+            HelperError::LogoNotFound(path) => {
+                write!(f, "Logo file not found: {}", path.display())
+            }
+            #[cfg(feature = "image")]
+            HelperError::LogoDecode { path, source } => {
+                write!(f, "Failed to decode logo {}: {}", path.display(), source)
+            }
+            HelperError::DimensionOverflow { width, height, max } => write!(
+                f,
+                "Generated image would be too large ({}x{}, max {}x{})",
+                width, height, max, max
+            ),
+            HelperError::PoisonedCache(name) => write!(f, "The {} cache is poisoned", name),
+            HelperError::FmtError(e) => write!(f, "Format error: {}", e),
         }
     }
 }
 
-impl Error for HelperError {}
+impl Error for HelperError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            #[cfg(feature = "image")]
+            HelperError::ImageError(e) => Some(e),
+            HelperError::IoError(e) => Some(e),
+            #[cfg(feature = "image")]
+            HelperError::LogoDecode { source, .. } => Some(source),
+            HelperError::FmtError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
 
+#[cfg(feature = "image")]
 impl From<image::ImageError> for HelperError {
     fn from(err: image::ImageError) -> Self {
         HelperError::ImageError(err)
@@ -114,19 +185,300 @@ impl From<std::io::Error> for HelperError {
     }
 }
 
+impl From<fmt::Error> for HelperError {
+    fn from(err: fmt::Error) -> Self {
+        HelperError::FmtError(err)
+    }
+}
+
+/// Destination of QR code images written to disk.
+///
+/// Kept separate from [QrStyle] so that the same style can be rendered in memory or saved to
+/// different locations.
+#[cfg(feature = "image")]
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(remote = "Self", default)
+)]
+pub struct OutputConfig<'a> {
+    /// Directory the file is written to, created if it does not exist (defaults to "generated")
+    pub directory_path: Cow<'a, str>,
+    /// File name without extension (defaults to a timestamp since the Unix epoch)
+    pub file_name: Option<Cow<'a, str>>,
+    /// Image format of the file, which also selects its extension (defaults to PNG)
+    pub format: ImageOutputFormat,
+}
+
+#[cfg(feature = "image")]
+impl Default for OutputConfig<'_> {
+    fn default() -> Self {
+        Self {
+            directory_path: Cow::Borrowed("generated"),
+            file_name: None,
+            format: ImageOutputFormat::default(),
+        }
+    }
+}
+
+#[cfg(feature = "image")]
+impl<'a> OutputConfig<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_directory(mut self, path: impl Into<Cow<'a, str>>) -> Self {
+        self.directory_path = path.into();
+        self
+    }
+
+    pub fn with_filename(mut self, name: impl Into<Cow<'a, str>>) -> Self {
+        self.file_name = Some(name.into());
+        self
+    }
+
+    pub fn with_format(mut self, format: ImageOutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Validates the configuration before use
+    pub fn validate(&self) -> Result<(), HelperError> {
+        if self.file_name.as_deref().is_some_and(str::is_empty) {
+            return Err(HelperError::InvalidInput(
+                "File name cannot be empty".to_string(),
+            ));
+        }
+        self.format.validate()
+    }
+
+    // Creates the output directory if needed and returns the full path of the file to write.
+    pub(crate) fn prepare_path(&self) -> Result<PathBuf, std::io::Error> {
+        let filename = self
+            .file_name
+            .as_deref()
+            .map(|s| s.to_string())
+            .unwrap_or_else(timestamp_file_name);
+
+        let directory_path = Path::new(self.directory_path.as_ref());
+        if !directory_path.exists() {
+            fs::create_dir_all(directory_path)?;
+        }
+        Ok(directory_path.join(format!("{}.{}", filename, self.format.extension())))
+    }
+}
+
+/// Image format of encoded raster output.
+///
+/// Selects the encoder used by [write_image] and the extension of files saved with an
+/// [OutputConfig]. Every format is lossless except JPEG, which also has no alpha channel: images
+/// are flattened to RGB before encoding, so only use it with opaque colors.
+#[cfg(feature = "image")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum ImageOutputFormat {
+    /// Portable Network Graphics
+    #[default]
+    Png,
+    /// JPEG with a quality between 1 and 100
+    Jpeg { quality: u8 },
+    /// Lossless WebP
+    WebP,
+    /// Windows bitmap
+    Bmp,
+    /// Single-frame GIF, quantized to a 256-color palette
+    Gif,
+    /// Uncompressed TIFF
+    Tiff,
+}
+
+#[cfg(feature = "image")]
+impl ImageOutputFormat {
+    /// Returns [ImageOutputFormat::Jpeg] with the given quality, checking that it is between 1 and
+    /// 100.
+    pub fn jpeg(quality: u8) -> Result<Self, HelperError> {
+        let format = ImageOutputFormat::Jpeg { quality };
+        format.validate()?;
+        Ok(format)
+    }
+
+    /// Selects a format from a file extension such as `"jpg"` or `"webp"`, ignoring case.
+    ///
+    /// JPEG is selected with a quality of [DEFAULT_JPEG_QUALITY]. Returns `None` for extensions of
+    /// formats that cannot be written.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Some(match ImageFormat::from_extension(extension)? {
+            ImageFormat::Png => ImageOutputFormat::Png,
+            ImageFormat::Jpeg => ImageOutputFormat::Jpeg {
+                quality: DEFAULT_JPEG_QUALITY,
+            },
+            ImageFormat::WebP => ImageOutputFormat::WebP,
+            ImageFormat::Bmp => ImageOutputFormat::Bmp,
+            ImageFormat::Gif => ImageOutputFormat::Gif,
+            ImageFormat::Tiff => ImageOutputFormat::Tiff,
+            _ => return None,
+        })
+    }
+
+    /// The corresponding [ImageFormat] of the `image` crate.
+    pub fn image_format(&self) -> ImageFormat {
+        match self {
+            ImageOutputFormat::Png => ImageFormat::Png,
+            ImageOutputFormat::Jpeg { .. } => ImageFormat::Jpeg,
+            ImageOutputFormat::WebP => ImageFormat::WebP,
+            ImageOutputFormat::Bmp => ImageFormat::Bmp,
+            ImageOutputFormat::Gif => ImageFormat::Gif,
+            ImageOutputFormat::Tiff => ImageFormat::Tiff,
+        }
+    }
+
+    /// The preferred file extension, without a leading dot.
+    pub fn extension(&self) -> &'static str {
+        self.image_format().extensions_str()[0]
+    }
+
+    /// The media type, e.g. `"image/webp"`, for `Content-Type` headers and data URIs.
+    pub fn media_type(&self) -> &'static str {
+        self.image_format().to_mime_type()
+    }
+
+    /// Validates the format before use
+    pub fn validate(&self) -> Result<(), HelperError> {
+        match self {
+            ImageOutputFormat::Jpeg { quality } if !(1..=100).contains(quality) => Err(
+                HelperError::InvalidInput("JPEG quality must be between 1 and 100".to_string()),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// JPEG quality used when the format is chosen by [ImageOutputFormat::from_extension].
+#[cfg(feature = "image")]
+pub const DEFAULT_JPEG_QUALITY: u8 = 90;
+
+/// Encodes `img` in `format` and writes it to `writer`.
+///
+/// The writer does not need to implement [std::io::Seek], so HTTP response bodies, sockets and
+/// `Vec<u8>` all work. TIFF needs to seek while encoding and is encoded into memory first; the
+/// other formats are streamed. Wrap unbuffered writers such as [std::fs::File] in a
+/// [std::io::BufWriter].
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::helper::{write_image, ImageOutputFormat};
+/// use image::{DynamicImage, RgbaImage};
+///
+/// let img = DynamicImage::ImageRgba8(RgbaImage::new(8, 8));
+/// let mut bytes = Vec::new();
+/// write_image(&img, ImageOutputFormat::WebP, &mut bytes).unwrap();
+/// assert!(bytes.starts_with(b"RIFF"));
+/// ```
+#[cfg(feature = "image")]
+pub fn write_image<W: std::io::Write>(
+    img: &DynamicImage,
+    format: ImageOutputFormat,
+    mut writer: W,
+) -> Result<(), HelperError> {
+    use image::codecs::{
+        bmp::BmpEncoder, gif::GifEncoder, jpeg::JpegEncoder, png::PngEncoder, tiff::TiffEncoder,
+        webp::WebPEncoder,
+    };
+
+    format.validate()?;
+    match format {
+        ImageOutputFormat::Png => img.write_with_encoder(PngEncoder::new(&mut writer))?,
+        ImageOutputFormat::Jpeg { quality } => {
+            // JPEG has no alpha channel
+            let encoder = JpegEncoder::new_with_quality(&mut writer, quality);
+            match img {
+                DynamicImage::ImageLuma8(_) | DynamicImage::ImageRgb8(_) => {
+                    img.write_with_encoder(encoder)?
+                }
+                _ => DynamicImage::ImageRgb8(img.to_rgb8()).write_with_encoder(encoder)?,
+            }
+        }
+        ImageOutputFormat::WebP => {
+            img.write_with_encoder(WebPEncoder::new_lossless(&mut writer))?
+        }
+        ImageOutputFormat::Bmp => img.write_with_encoder(BmpEncoder::new(&mut writer))?,
+        ImageOutputFormat::Gif => {
+            // The GIF encoder only accepts RGB(A) input
+            DynamicImage::ImageRgba8(img.to_rgba8())
+                .write_with_encoder(GifEncoder::new(&mut writer))?
+        }
+        ImageOutputFormat::Tiff => {
+            let mut buffer = std::io::Cursor::new(Vec::new());
+            img.write_with_encoder(TiffEncoder::new(&mut buffer))?;
+            writer.write_all(buffer.get_ref())?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// Writes `img` to a new file at `path` in `format`.
+#[cfg(feature = "image")]
+fn save_image(
+    img: &DynamicImage,
+    format: ImageOutputFormat,
+    path: &Path,
+) -> Result<(), HelperError> {
+    let file = fs::File::create(path)?;
+    write_image(img, format, std::io::BufWriter::new(file))
+}
+
+// Default file name: the time since the Unix epoch, or zero if the system clock is set before it.
+#[cfg(feature = "image")]
+fn timestamp_file_name() -> String {
+    let since_the_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format!("{:?}", since_the_epoch)
+}
+
+// Converts a pixel frame into whole modules of quiet zone, rounding up so the converted style never
+// has less margin than the original configuration.
+#[cfg(feature = "image")]
+fn frame_to_quiet_zone(outer_frame_px: u32, upscale_factor: u32) -> u32 {
+    outer_frame_px
+        .div_ceil(upscale_factor.max(1))
+        .min(MAX_BORDER_SIZE)
+}
+
 /// Configuration for basic QR code rendering.
+#[cfg(feature = "image")]
+#[deprecated(
+    since = "0.1.32",
+    note = "use `QrStyle` from the `render` module instead"
+)]
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(remote = "Self", default)
+)]
 pub struct QrConfig {
     /// Border size in modules (defaults to 4)
     pub border: u32,
     /// Foreground color as RGB (defaults to black [0, 0, 0])
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::color"))]
     pub fg_color: [u8; 3],
     /// Background color as RGB (defaults to white [255, 255, 255])
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::color"))]
     pub bg_color: [u8; 3],
     /// Scale factor for output size (defaults to 4)
     pub scale: u32,
 }
 
+#[cfg(feature = "image")]
+#[allow(deprecated)]
 impl Default for QrConfig {
     fn default() -> Self {
         Self {
@@ -138,6 +490,8 @@ impl Default for QrConfig {
     }
 }
 
+#[cfg(feature = "image")]
+#[allow(deprecated)]
 impl QrConfig {
     pub fn new() -> Self {
         Self::default()
@@ -192,20 +546,52 @@ impl QrConfig {
     }
 }
 
+/// Converts the border and scale one-to-one into the quiet zone and module size.
+#[cfg(feature = "image")]
+#[allow(deprecated)]
+impl From<QrConfig> for QrStyle<'_> {
+    fn from(config: QrConfig) -> Self {
+        QrStyle {
+            fg_color: config.fg_color,
+            bg_color: config.bg_color,
+            quiet_zone: config.border,
+            module_size: config.scale,
+            ..Default::default()
+        }
+    }
+}
+
 /// Configuration for styled QR codes with frames and logos.
+#[cfg(feature = "image")]
+#[deprecated(
+    since = "0.1.32",
+    note = "use `QrStyle` with `OutputConfig` and `generate_styled` instead"
+)]
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(remote = "Self", default)
+)]
 pub struct FrameQrConfig<'a> {
     pub logo_path: &'a str,
     pub ecc: QrCodeEcc,
     pub upscale_factor: u32,
     pub directory_path: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub file_name: Option<&'a str>,
+    pub format: ImageOutputFormat,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::color"))]
     pub qr_color: [u8; 3],
     pub outer_frame_px: u32,
     pub inner_frame_px: u32,
     pub frame_style: FrameStyle,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub caption: Option<Caption<'a>>,
 }
 
+#[cfg(feature = "image")]
+#[allow(deprecated)]
 impl<'a> Default for FrameQrConfig<'a> {
     fn default() -> Self {
         Self {
@@ -214,14 +600,18 @@ impl<'a> Default for FrameQrConfig<'a> {
             upscale_factor: 8,
             directory_path: "generated",
             file_name: None,
+            format: ImageOutputFormat::Png,
             qr_color: [0, 0, 0],
             outer_frame_px: 0,
             inner_frame_px: 0,
             frame_style: FrameStyle::None,
+            caption: None,
         }
     }
 }
 
+#[cfg(feature = "image")]
+#[allow(deprecated)]
 impl<'a> FrameQrConfig<'a> {
     pub fn new(logo_path: &'a str) -> Result<Self, HelperError> {
         if logo_path.is_empty() {
@@ -261,6 +651,11 @@ impl<'a> FrameQrConfig<'a> {
         self
     }
 
+    pub fn with_format(mut self, format: ImageOutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
     pub fn with_color(mut self, color: [u8; 3]) -> Self {
         self.qr_color = color;
         self
@@ -281,6 +676,11 @@ impl<'a> FrameQrConfig<'a> {
         self
     }
 
+    pub fn with_caption(mut self, caption: Caption<'a>) -> Self {
+        self.caption = Some(caption);
+        self
+    }
+
     /// Validates the configuration before use
     pub fn validate(&self) -> Result<(), HelperError> {
         if self.logo_path.is_empty() {
@@ -294,7 +694,45 @@ impl<'a> FrameQrConfig<'a> {
                 MAX_UPSCALE_FACTOR
             )));
         }
-        Ok(())
+        if let Some(caption) = &self.caption {
+            caption.validate()?;
+        }
+        self.format.validate()
+    }
+}
+
+/// Converts the pixel-based outer frame into a quiet zone of `outer_frame_px / upscale_factor`
+/// modules, rounded up and capped at the maximum quiet zone.
+#[cfg(feature = "image")]
+#[allow(deprecated)]
+impl<'a> From<FrameQrConfig<'a>> for QrStyle<'a> {
+    fn from(config: FrameQrConfig<'a>) -> Self {
+        QrStyle {
+            fg_color: config.qr_color,
+            quiet_zone: frame_to_quiet_zone(config.outer_frame_px, config.upscale_factor),
+            module_size: config.upscale_factor,
+            ecc: config.ecc,
+            logo_path: Some(config.logo_path)
+                .filter(|path| !path.is_empty())
+                .map(Cow::Borrowed),
+            frame_style: config.frame_style,
+            inner_frame_px: config.inner_frame_px,
+            caption: config.caption,
+            logo_upscale: false,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "image")]
+#[allow(deprecated)]
+impl<'a> From<&FrameQrConfig<'a>> for OutputConfig<'a> {
+    fn from(config: &FrameQrConfig<'a>) -> Self {
+        OutputConfig {
+            directory_path: Cow::Borrowed(config.directory_path),
+            file_name: config.file_name.map(Cow::Borrowed),
+            format: config.format,
+        }
     }
 }
 
@@ -327,8 +765,32 @@ impl<'a> FrameQrConfig<'a> {
 /// Uses `String::with_capacity` to avoid reallocations and bitwise operations for encoding, making it
 /// suitable for performance-critical applications.
 pub fn encode_base64(data: &[u8]) -> String {
-    const BASE64_ALPHABET: &[u8; 64] =
-        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    encode_base64_with(data, BASE64_ALPHABET, true)
+}
+
+/// Encodes a byte slice into URL- and filename-safe base64 (RFC 4648, section 5).
+///
+/// Uses `-` and `_` in place of `+` and `/` and omits the `=` padding, so the result can be used in
+/// URLs, query parameters and file names without escaping.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::helper::encode_base64_url;
+///
+/// assert_eq!(encode_base64_url(&[0xfb, 0xff]), "-_8");
+/// ```
+pub fn encode_base64_url(data: &[u8]) -> String {
+    encode_base64_with(data, BASE64_URL_ALPHABET, false)
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// Encodes `data` with `alphabet`, padding the last group with `=` if `padding` is set.
+fn encode_base64_with(data: &[u8], alphabet: &[u8; 64], padding: bool) -> String {
     let encoded_len = data.len().div_ceil(3) * 4;
     let mut result = String::with_capacity(encoded_len);
 
@@ -338,32 +800,134 @@ pub fn encode_base64(data: &[u8]) -> String {
         let b2 = data[i + 1];
         let b3 = data[i + 2];
 
-        result.push(BASE64_ALPHABET[(b1 >> 2) as usize] as char);
-        result.push(BASE64_ALPHABET[(((b1 & 0b00000011) << 4) | (b2 >> 4)) as usize] as char);
-        result.push(BASE64_ALPHABET[(((b2 & 0b00001111) << 2) | (b3 >> 6)) as usize] as char);
-        result.push(BASE64_ALPHABET[(b3 & 0b00111111) as usize] as char);
+        result.push(alphabet[(b1 >> 2) as usize] as char);
+        result.push(alphabet[(((b1 & 0b00000011) << 4) | (b2 >> 4)) as usize] as char);
+        result.push(alphabet[(((b2 & 0b00001111) << 2) | (b3 >> 6)) as usize] as char);
+        result.push(alphabet[(b3 & 0b00111111) as usize] as char);
 
         i += 3;
     }
 
     if data.len() - i == 1 {
         let b1 = data[i];
-        result.push(BASE64_ALPHABET[(b1 >> 2) as usize] as char);
-        result.push(BASE64_ALPHABET[((b1 & 0b00000011) << 4) as usize] as char);
-        result.push('=');
-        result.push('=');
+        result.push(alphabet[(b1 >> 2) as usize] as char);
+        result.push(alphabet[((b1 & 0b00000011) << 4) as usize] as char);
+        if padding {
+            result.push_str("==");
+        }
     } else if data.len() - i == 2 {
         let b1 = data[i];
         let b2 = data[i + 1];
-        result.push(BASE64_ALPHABET[(b1 >> 2) as usize] as char);
-        result.push(BASE64_ALPHABET[(((b1 & 0b00000011) << 4) | (b2 >> 4)) as usize] as char);
-        result.push(BASE64_ALPHABET[((b2 & 0b00001111) << 2) as usize] as char);
-        result.push('=');
+        result.push(alphabet[(b1 >> 2) as usize] as char);
+        result.push(alphabet[(((b1 & 0b00000011) << 4) | (b2 >> 4)) as usize] as char);
+        result.push(alphabet[((b2 & 0b00001111) << 2) as usize] as char);
+        if padding {
+            result.push('=');
+        }
     }
 
     result
 }
 
+/// A writer that base64-encodes everything written to it into another writer.
+///
+/// Encodes large outputs such as PNG images without holding the raw bytes and their encoding in
+/// memory at the same time. Bytes are encoded in groups of three as they arrive; call
+/// [Base64Writer::finish] after the last write to encode the remaining bytes and padding, since
+/// dropping the writer discards them.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::helper::Base64Writer;
+/// use std::io::Write;
+///
+/// let mut writer = Base64Writer::new(Vec::new());
+/// writer.write_all(b"Hel").unwrap();
+/// writer.write_all(b"lo").unwrap();
+/// let encoded = writer.finish().unwrap();
+/// assert_eq!(encoded, b"SGVsbG8=");
+/// ```
+#[derive(Debug)]
+pub struct Base64Writer<W: std::io::Write> {
+    inner: W,
+    // Bytes that do not complete a group of three yet
+    pending: [u8; 3],
+    pending_len: usize,
+    url_safe: bool,
+}
+
+impl<W: std::io::Write> Base64Writer<W> {
+    /// Creates a writer using the standard alphabet with padding, as [encode_base64] does.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            pending: [0; 3],
+            pending_len: 0,
+            url_safe: false,
+        }
+    }
+
+    /// Creates a writer using the URL-safe alphabet without padding, as [encode_base64_url] does.
+    pub fn url_safe(inner: W) -> Self {
+        Self {
+            url_safe: true,
+            ..Self::new(inner)
+        }
+    }
+
+    fn encode(&self, data: &[u8]) -> String {
+        if self.url_safe {
+            encode_base64_url(data)
+        } else {
+            encode_base64(data)
+        }
+    }
+
+    /// Encodes the remaining bytes with padding and returns the inner writer.
+    pub fn finish(mut self) -> std::io::Result<W> {
+        let tail = self.encode(&self.pending[..self.pending_len]);
+        self.inner.write_all(tail.as_bytes())?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: std::io::Write> std::io::Write for Base64Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // Encodes at most this many bytes at a time, a multiple of three
+        const CHUNK: usize = 3 * 1024;
+        let mut rest = buf;
+        if self.pending_len > 0 {
+            let taken = rest.len().min(3 - self.pending_len);
+            self.pending[self.pending_len..self.pending_len + taken]
+                .copy_from_slice(&rest[..taken]);
+            self.pending_len += taken;
+            rest = &rest[taken..];
+            if self.pending_len < 3 {
+                return Ok(buf.len());
+            }
+            let group = self.encode(&self.pending);
+            self.inner.write_all(group.as_bytes())?;
+            self.pending_len = 0;
+        }
+        let complete = rest.len() / 3 * 3;
+        for chunk in rest[..complete].chunks(CHUNK) {
+            self.inner.write_all(self.encode(chunk).as_bytes())?;
+        }
+        let tail = &rest[complete..];
+        self.pending[..tail.len()].copy_from_slice(tail);
+        self.pending_len = tail.len();
+        Ok(buf.len())
+    }
+
+    /// Flushes the inner writer. Bytes that do not complete a group of three stay pending until
+    /// more bytes arrive or [Base64Writer::finish] is called.
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
 /// Generates an SVG string for a QR code.
 ///
 /// Produces an SVG with a white background and black modules, using Unix newlines (`\n`). Modules are
@@ -418,7 +982,7 @@ pub fn to_svg_string(qr: &QrCode, border: i32) -> String {
         result,
         "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
          <!DOCTYPE svg PUBLIC \"-//W3C//DTD SVG 1.1//EN\" \"http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd\">\n\
-         <svg xmlns=\"http://www.w3.org/200intro/svg\" version=\"1.1\" viewBox=\"0 0 {} {}\" stroke=\"none\">\n\
+         <svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" viewBox=\"0 0 {} {}\" stroke=\"none\">\n\
          \t<rect width=\"100%\" height=\"100%\" fill=\"#FFFFFF\"/>\n",
         dimension,
         dimension
@@ -454,119 +1018,77 @@ pub fn to_svg_string(qr: &QrCode, border: i32) -> String {
     result
 }
 
-/// Defines the style of the frame behind the logo in styled QR codes.
-///
-/// Used in functions like [frameqr_to_svg_string], [frameqr_to_image_and_save], and
-/// [generate_frameqr_buffer] to specify whether the logo has a square frame, a rounded (circular)
-/// frame, or no frame at all.
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum FrameStyle {
-    /// A square frame, rendered as a white rectangle around the logo.
-    Square,
-    /// A rounded (circular) frame, rendered as a white circle around the logo.
-    Rounded,
-    /// No frame, with the logo directly overlaid on the QR code.
-    None,
-}
-
-/// Configuration for SVG styled QR codes
-#[derive(Debug, Clone)]
-pub struct FrameQrSvgConfig<'a> {
-    pub logo_path: &'a str,
-    pub upscale_factor: u32,
-    pub qr_color: [u8; 3],
-    pub outer_frame_px: u32,
-    pub inner_frame_px: u32,
-    pub frame_style: FrameStyle,
-}
-
-impl<'a> Default for FrameQrSvgConfig<'a> {
-    fn default() -> Self {
-        Self {
-            logo_path: "",
-            upscale_factor: DEFAULT_UPSCALE_FACTOR,
-            qr_color: [0, 0, 0],
-            outer_frame_px: 0,
-            inner_frame_px: DEFAULT_INNER_FRAME,
-            frame_style: FrameStyle::None,
-        }
-    }
+/// Bitmap formats written by [write_bitmap] without any image library.
+///
+/// All formats are black and white: dark modules are black and everything else, including the
+/// border, is white.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitmapFormat {
+    /// Plain PBM (`P1`): one ASCII `0` or `1` per pixel
+    PbmAscii,
+    /// Binary PBM (`P4`): one bit per pixel
+    Pbm,
+    /// Binary PGM (`P5`): one byte per pixel, 0 for black and 255 for white
+    Pgm,
+    /// X BitMap: C source declaring `qr_width`, `qr_height` and the `qr_bits` array
+    Xbm,
+    /// Uncompressed Windows bitmap with a two-color palette
+    Bmp,
 }
 
-impl<'a> FrameQrSvgConfig<'a> {
-    pub fn new(logo_path: &'a str) -> Result<Self, HelperError> {
-        if logo_path.is_empty() {
-            return Err(HelperError::InvalidInput(
-                "Logo path cannot be empty".to_string(),
-            ));
+impl BitmapFormat {
+    /// The usual file extension, without a leading dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            BitmapFormat::PbmAscii | BitmapFormat::Pbm => "pbm",
+            BitmapFormat::Pgm => "pgm",
+            BitmapFormat::Xbm => "xbm",
+            BitmapFormat::Bmp => "bmp",
         }
-        Ok(Self {
-            logo_path,
-            ..Default::default()
-        })
     }
 
-    pub fn with_upscale(mut self, factor: u32) -> Result<Self, HelperError> {
-        if factor == 0 || factor > MAX_UPSCALE_FACTOR {
-            return Err(HelperError::InvalidInput(format!(
-                "Upscale factor must be between 1 and {}",
-                MAX_UPSCALE_FACTOR
-            )));
+    /// The media type, for `Content-Type` headers and data URIs.
+    pub fn media_type(&self) -> &'static str {
+        match self {
+            BitmapFormat::PbmAscii | BitmapFormat::Pbm => "image/x-portable-bitmap",
+            BitmapFormat::Pgm => "image/x-portable-graymap",
+            BitmapFormat::Xbm => "image/x-xbitmap",
+            BitmapFormat::Bmp => "image/bmp",
         }
-        self.upscale_factor = factor;
-        Ok(self)
-    }
-
-    pub fn with_color(mut self, color: [u8; 3]) -> Self {
-        self.qr_color = color;
-        self
-    }
-
-    pub fn with_outer_frame(mut self, size: u32) -> Self {
-        self.outer_frame_px = size;
-        self
-    }
-
-    pub fn with_inner_frame(mut self, size: u32) -> Self {
-        self.inner_frame_px = size;
-        self
-    }
-
-    pub fn with_frame_style(mut self, style: FrameStyle) -> Self {
-        self.frame_style = style;
-        self
     }
 }
 
-/// Generates an SVG string for a styled QR code with an embedded logo.
+/// Writes a QR code as a bitmap in `format` using only the standard library.
 ///
-/// Renders a QR code with a logo embedded as a base64-encoded PNG, supporting custom colors, outer
-/// frames, and square or rounded frames behind the logo. Uses horizontal module grouping for
-/// efficiency and a global cache for logo base64 encoding to reduce redundant processing.
+/// Pixels are read straight from [QrCode::get_module]: every module becomes `scale` × `scale`
+/// pixels and the code is surrounded by `border` light modules. Unlike [ImageOutputFormat], no
+/// image is built in memory; only one row of pixels is held at a time (BMP stores rows bottom-up,
+/// so they are written in reverse). Wrap unbuffered writers such as [std::fs::File] in a
+/// [std::io::BufWriter].
 ///
 /// # Arguments
 ///
 /// * `qr` - The [QrCode] to render.
-/// * `config` - Configuration for styling ([FrameQrSvgConfig]).
-///
-/// # Returns
-///
-/// A `Result` containing the SVG string or an [image::ImageError] on failure.
+/// * `border` - Number of border modules on each side (between 0 and 200), as for
+///   [to_svg_string] and [to_terminal_string].
+/// * `scale` - Pixels per module (between 1 and 100).
+/// * `format` - The [BitmapFormat] to write.
+/// * `writer` - Destination of the encoded bitmap.
 ///
 /// # Example
 ///
 /// ```rust
 /// use qirust::qrcode::{QrCode, QrCodeEcc, Version, EncodeTextOptions};
-/// use qirust::helper::{frameqr_to_svg_string, FrameQrSvgConfig, FrameStyle};
+/// use qirust::helper::{write_bitmap, BitmapFormat};
 ///
 /// let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
 /// let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
 /// let qr = QrCode::encode_text(
-///     "https://example.com",
+///     "Hello, World!",
 ///     &mut tempbuffer,
 ///     &mut outbuffer,
 ///     EncodeTextOptions {
-///         ecl: QrCodeEcc::High,
+///         ecl: QrCodeEcc::Low,
 ///         minversion: Version::MIN,
 ///         maxversion: Version::MAX,
 ///         mask: None,
@@ -574,177 +1096,577 @@ impl<'a> FrameQrSvgConfig<'a> {
 ///     },
 /// ).unwrap();
 ///
-/// let config = FrameQrSvgConfig::new("logo.png").unwrap()
-///     .with_upscale(6).unwrap()
-///     .with_color([255, 165, 0])
-///     .with_outer_frame(40)
-///     .with_inner_frame(10)
-///     .with_frame_style(FrameStyle::Rounded);
-///
-/// let svg = frameqr_to_svg_string(qr, config).expect("Failed to generate SVG");
-/// println!("{}", svg);
+/// let mut pbm = Vec::new();
+/// write_bitmap(&qr, 4, 2, BitmapFormat::Pbm, &mut pbm).unwrap();
+/// assert!(pbm.starts_with(b"P4\n58 58\n"));
 /// ```
-pub fn frameqr_to_svg_string(
-    qr: QrCode,
-    config: FrameQrSvgConfig,
-) -> Result<String, image::ImageError> {
-    static LOGO_BASE64_CACHE: Mutex<Option<(String, String)>> = Mutex::new(None);
+pub fn write_bitmap<W: std::io::Write>(
+    qr: &QrCode,
+    border: i32,
+    scale: u32,
+    format: BitmapFormat,
+    mut writer: W,
+) -> Result<(), HelperError> {
+    if !(0..=MAX_BORDER_SIZE as i32).contains(&border) {
+        return Err(HelperError::InvalidInput(format!(
+            "Border must be between 0 and {}",
+            MAX_BORDER_SIZE
+        )));
+    }
+    if scale == 0 || scale > MAX_UPSCALE_FACTOR {
+        return Err(HelperError::InvalidInput(format!(
+            "Scale must be between 1 and {}",
+            MAX_UPSCALE_FACTOR
+        )));
+    }
+    let side = (qr.size() + 2 * border) as u32 * scale;
+    if side > MAX_IMAGE_DIMENSION {
+        return Err(HelperError::DimensionOverflow {
+            width: u64::from(side),
+            height: u64::from(side),
+            max: MAX_IMAGE_DIMENSION,
+        });
+    }
+
+    let side_px = side as usize;
+    let packed_len = side_px.div_ceil(8);
+    let mut modules = Vec::with_capacity(side_px);
+    // Plain PBM is the longest row: a digit per pixel and a line break every 70 pixels
+    let mut row = Vec::with_capacity(side_px + side_px / 70 + 1);
+    let mut module_rows: Vec<i32> = (-border..qr.size() + border).collect();
+    if format == BitmapFormat::Bmp {
+        module_rows.reverse();
+    }
+
+    match format {
+        BitmapFormat::PbmAscii => write!(writer, "P1\n{} {}\n", side, side)?,
+        BitmapFormat::Pbm => write!(writer, "P4\n{} {}\n", side, side)?,
+        BitmapFormat::Pgm => write!(writer, "P5\n{} {}\n255\n", side, side)?,
+        BitmapFormat::Xbm => write!(
+            writer,
+            "#define qr_width {}\n#define qr_height {}\nstatic unsigned char qr_bits[] = {{",
+            side, side
+        )?,
+        BitmapFormat::Bmp => write_bmp_header(&mut writer, side)?,
+    }
+
+    let mut xbm_values = 0usize;
+    let mut xbm_line = String::new();
+    for y in module_rows {
+        modules.clear();
+        for x in -border..qr.size() + border {
+            let dark = qr.get_module(x, y);
+            modules.resize(modules.len() + scale as usize, dark);
+        }
 
-    let qr_size = qr.size() as u32;
-    let upscale = config.upscale_factor;
-    let outer_frame = config.outer_frame_px;
-    let inner_frame = config.inner_frame_px;
+        row.clear();
+        match format {
+            BitmapFormat::PbmAscii => {
+                // Plain PBM lines should not exceed 70 characters
+                for chunk in modules.chunks(70) {
+                    row.extend(chunk.iter().map(|&dark| if dark { b'1' } else { b'0' }));
+                    row.push(b'\n');
+                }
+            }
+            BitmapFormat::Pbm => pack_bits(&modules, true, &mut row),
+            BitmapFormat::Pgm => row.extend(modules.iter().map(|&dark| if dark { 0 } else { 255 })),
+            BitmapFormat::Xbm => pack_bits(&modules, false, &mut row),
+            BitmapFormat::Bmp => {
+                pack_bits(&modules, true, &mut row);
+                // Rows are padded to a multiple of four bytes
+                row.resize(packed_len.next_multiple_of(4), 0);
+            }
+        }
 
-    let estimated_size = 200 + qr_size * qr_size * 16 + 500 + qr_size * upscale * 4;
-    let mut result = String::with_capacity(estimated_size as usize);
-    let dimension = qr_size * upscale + 2 * outer_frame;
+        for _ in 0..scale {
+            if format != BitmapFormat::Xbm {
+                writer.write_all(&row)?;
+                continue;
+            }
+            // Twelve values per line, as written by the X11 `bitmap` tool
+            xbm_line.clear();
+            for byte in &row {
+                let separator = match xbm_values {
+                    0 => "\n   ",
+                    n if n % 12 == 0 => ",\n   ",
+                    _ => ", ",
+                };
+                xbm_values += 1;
+                write!(xbm_line, "{}0x{:02x}", separator, byte)?;
+            }
+            writer.write_all(xbm_line.as_bytes())?;
+        }
+    }
 
-    writeln!(
-        result,
-        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE svg PUBLIC \"-//W3C//DTD SVG 1.1//EN\" \"http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd\">\n<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" viewBox=\"0 0 {0} {0}\" stroke=\"none\">\n<rect width=\"100%\" height=\"100%\" fill=\"#FFFFFF\"/>\n",
-        dimension
-    ).unwrap();
+    if format == BitmapFormat::Xbm {
+        writer.write_all(b"\n};\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
 
-    // Render QR modules with horizontal grouping
-    let mut path_buffer = Vec::with_capacity((qr_size as usize) * (qr_size as usize) * 20);
-    for y in 0..qr_size {
-        let mut x = 0;
-        while x < qr_size {
-            if qr.get_module(x as i32, y as i32) {
-                let start_x = x;
-                let mut width = 1;
-                while x + 1 < qr_size && qr.get_module((x + 1) as i32, y as i32) {
-                    x += 1;
-                    width += 1;
-                }
-                let px = start_x * upscale + outer_frame;
-                let py = y * upscale + outer_frame;
-                write!(
-                    &mut path_buffer,
-                    "M{} {}h{}v{}h-{}z ",
-                    px,
-                    py,
-                    width * upscale,
-                    upscale,
-                    width * upscale
-                )
-                .unwrap();
+/// Returns a QR code encoded as a bitmap in `format`, see [write_bitmap].
+pub fn to_bitmap(
+    qr: &QrCode,
+    border: i32,
+    scale: u32,
+    format: BitmapFormat,
+) -> Result<Vec<u8>, HelperError> {
+    let mut bytes = Vec::new();
+    write_bitmap(qr, border, scale, format, &mut bytes)?;
+    Ok(bytes)
+}
+
+// Packs dark pixels into bits set to one, eight to a byte and the last byte padded with zeros. PBM
+// and BMP put the leftmost pixel in the most significant bit, XBM in the least significant.
+fn pack_bits(pixels: &[bool], msb_first: bool, out: &mut Vec<u8>) {
+    for chunk in pixels.chunks(8) {
+        let mut byte = 0u8;
+        for (i, &dark) in chunk.iter().enumerate() {
+            if dark {
+                byte |= if msb_first { 0x80 >> i } else { 1 << i };
             }
-            x += 1;
         }
+        out.push(byte);
     }
+}
 
-    writeln!(
-        result,
-        "<path d=\"{}\" fill=\"#{:02x}{:02x}{:02x}\"/>",
-        std::str::from_utf8(&path_buffer).unwrap().trim(),
-        config.qr_color[0],
-        config.qr_color[1],
-        config.qr_color[2]
-    )
-    .unwrap();
-
-    // Load and encode logo
-    let logo = image::open(config.logo_path)?.to_rgba8();
-    let max_logo_w = (qr_size * upscale) / LOGO_SIZE_DIVISOR;
-    let max_logo_h = (qr_size * upscale) / LOGO_SIZE_DIVISOR;
-
-    let logo_resized = if logo.width() > max_logo_w || logo.height() > max_logo_h {
-        image::imageops::resize(
-            &logo,
-            max_logo_w,
-            max_logo_h,
-            image::imageops::FilterType::Triangle,
-        )
-    } else {
-        logo
-    };
+// Writes the file header, info header and palette of a square 1-bit BMP. Palette entry 0 is white
+// and entry 1 black, so dark pixels are the bits set by `pack_bits`.
+fn write_bmp_header<W: std::io::Write>(writer: &mut W, side: u32) -> std::io::Result<()> {
+    const HEADERS_LEN: u32 = 14 + 40 + 2 * 4;
+    let row_len = side.div_ceil(8).next_multiple_of(4);
+    let image_len = row_len * side;
+
+    let mut header = Vec::with_capacity(HEADERS_LEN as usize);
+    header.extend_from_slice(b"BM");
+    header.extend_from_slice(&(HEADERS_LEN + image_len).to_le_bytes());
+    header.extend_from_slice(&[0; 4]); // reserved
+    header.extend_from_slice(&HEADERS_LEN.to_le_bytes()); // offset of the pixel data
+    header.extend_from_slice(&40u32.to_le_bytes()); // BITMAPINFOHEADER size
+    header.extend_from_slice(&(side as i32).to_le_bytes());
+    header.extend_from_slice(&(side as i32).to_le_bytes()); // positive height: bottom-up rows
+    header.extend_from_slice(&1u16.to_le_bytes()); // planes
+    header.extend_from_slice(&1u16.to_le_bytes()); // bits per pixel
+    header.extend_from_slice(&0u32.to_le_bytes()); // BI_RGB, uncompressed
+    header.extend_from_slice(&image_len.to_le_bytes());
+    header.extend_from_slice(&2835i32.to_le_bytes()); // 72 dpi in pixels per metre
+    header.extend_from_slice(&2835i32.to_le_bytes());
+    header.extend_from_slice(&2u32.to_le_bytes()); // colors in the palette
+    header.extend_from_slice(&0u32.to_le_bytes()); // all colors are important
+    header.extend_from_slice(&[255, 255, 255, 0, 0, 0, 0, 0]); // white, black (BGRX)
+    writer.write_all(&header)
+}
 
-    let logo_base64 = {
-        let cache = LOGO_BASE64_CACHE.lock().unwrap();
-        if let Some((cached_path, cached_base64)) = cache.as_ref() {
-            if cached_path == config.logo_path {
-                cached_base64.clone()
-            } else {
-                let logo_buffer = {
-                    let mut logo_buffer = Vec::new();
-                    DynamicImage::ImageRgba8(logo_resized.clone()).write_to(
-                        &mut std::io::Cursor::new(&mut logo_buffer),
-                        ImageFormat::Png,
-                    )?;
-                    logo_buffer
-                };
-                encode_base64(&logo_buffer)
-            }
-        } else {
-            let logo_buffer = {
-                let mut logo_buffer = Vec::new();
-                DynamicImage::ImageRgba8(logo_resized.clone()).write_to(
-                    &mut std::io::Cursor::new(&mut logo_buffer),
-                    ImageFormat::Png,
-                )?;
-                logo_buffer
-            };
-            encode_base64(&logo_buffer)
+/// Defines the style of the frame behind the logo in styled QR codes.
+///
+/// Used in functions like [frameqr_to_svg_string], [frameqr_to_image_and_save], and
+/// [generate_frameqr_buffer] to specify whether the logo has a square frame, a rounded (circular)
+/// frame, or no frame at all.
+#[cfg(feature = "image")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum FrameStyle {
+    /// A square frame, rendered as a white rectangle around the logo.
+    Square,
+    /// A rounded (circular) frame, rendered as a white circle around the logo.
+    Rounded,
+    /// No frame, with the logo directly overlaid on the QR code.
+    None,
+}
+
+/// Defines where a [Caption] is placed relative to the QR code.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum CaptionPosition {
+    /// Above the QR code.
+    Top,
+    /// Below the QR code.
+    Bottom,
+}
+
+/// Caption text rendered in a banner above or below a styled QR code.
+///
+/// Used by [frameqr_to_image_and_save], [frameqr_to_svg_string] and [generate_frameqr] to print a
+/// call-to-action such as "Scan me" or a short URL inside the outer frame. The canvas grows
+/// automatically to fit the banner. The banner background reuses [FrameStyle]: `Square` draws a
+/// rectangle, `Rounded` draws a pill shape and `None` leaves the text on the white background.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use qirust::helper::{Caption, CaptionPosition, FrameStyle};
+///
+/// let font = std::fs::read("fonts/DejaVuSans.ttf").unwrap();
+/// let caption = Caption::new("Scan me", &font).unwrap()
+///     .with_font_size(32.0).unwrap()
+///     .with_text_color([255, 255, 255])
+///     .with_position(CaptionPosition::Bottom)
+///     .with_banner_style(FrameStyle::Rounded)
+///     .with_banner_color([255, 165, 0]);
+/// ```
+#[cfg(feature = "image")]
+#[derive(Debug, Clone)]
+pub struct Caption<'a> {
+    /// Text to render on a single line
+    pub text: &'a str,
+    /// TrueType or OpenType font data
+    pub font_data: &'a [u8],
+    /// Font size in pixels (defaults to 24)
+    pub font_size: f32,
+    /// Text color as RGB (defaults to black [0, 0, 0])
+    pub text_color: [u8; 3],
+    /// Placement relative to the QR code (defaults to [CaptionPosition::Bottom])
+    pub position: CaptionPosition,
+    /// Shape of the banner behind the text (defaults to [FrameStyle::None])
+    pub banner_style: FrameStyle,
+    /// Banner color as RGB, used unless the banner style is `None` (defaults to white)
+    pub banner_color: [u8; 3],
+    /// Padding in pixels between the text and the banner edge (defaults to 8, at most 1000)
+    pub padding_px: u32,
+}
+
+#[cfg(feature = "image")]
+impl<'a> Caption<'a> {
+    pub fn new(text: &'a str, font_data: &'a [u8]) -> Result<Self, HelperError> {
+        let caption = Self {
+            text,
+            font_data,
+            font_size: DEFAULT_CAPTION_FONT_SIZE,
+            text_color: [0, 0, 0],
+            position: CaptionPosition::Bottom,
+            banner_style: FrameStyle::None,
+            banner_color: [255, 255, 255],
+            padding_px: DEFAULT_CAPTION_PADDING,
+        };
+        caption.validate()?;
+        Ok(caption)
+    }
+
+    pub fn with_font_size(mut self, size: f32) -> Result<Self, HelperError> {
+        if !(size > 0.0 && size <= MAX_CAPTION_FONT_SIZE) {
+            return Err(HelperError::InvalidInput(format!(
+                "Caption font size must be greater than 0 and at most {}",
+                MAX_CAPTION_FONT_SIZE
+            )));
         }
-    };
+        self.font_size = size;
+        Ok(self)
+    }
+
+    pub fn with_text_color(mut self, color: [u8; 3]) -> Self {
+        self.text_color = color;
+        self
+    }
 
-    let logo_center_x = (qr_size * upscale) / 2 + outer_frame;
-    let logo_center_y = (qr_size * upscale) / 2 + outer_frame;
-    let base_logo_radius = max_logo_w.min(max_logo_h) / 2;
-    let logo_radius = base_logo_radius + inner_frame;
+    pub fn with_position(mut self, position: CaptionPosition) -> Self {
+        self.position = position;
+        self
+    }
 
-    // Apply frame style
-    match config.frame_style {
-        FrameStyle::Rounded => {
-            writeln!(
-                result,
-                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"#FFFFFF\"/>",
-                logo_center_x, logo_center_y, logo_radius
-            )
-            .unwrap();
+    pub fn with_banner_style(mut self, style: FrameStyle) -> Self {
+        self.banner_style = style;
+        self
+    }
+
+    pub fn with_banner_color(mut self, color: [u8; 3]) -> Self {
+        self.banner_color = color;
+        self
+    }
+
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        self.padding_px = padding;
+        self
+    }
+
+    /// Validates the caption before use
+    pub fn validate(&self) -> Result<(), HelperError> {
+        if self.text.is_empty() {
+            return Err(HelperError::InvalidInput(
+                "Caption text cannot be empty".to_string(),
+            ));
         }
-        FrameStyle::Square => {
-            writeln!(
-                result,
-                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#FFFFFF\" stroke=\"#FFFFFF\" stroke-width=\"{}\"/>",
-                logo_center_x - max_logo_w / 2,
-                logo_center_y - max_logo_h / 2,
-                max_logo_w,
-                max_logo_h,
-                inner_frame * 2
-            ).unwrap();
+        if self.text.contains(['\n', '\r']) {
+            return Err(HelperError::InvalidInput(
+                "Caption text must be a single line".to_string(),
+            ));
         }
-        FrameStyle::None => {}
+        if !(self.font_size > 0.0 && self.font_size <= MAX_CAPTION_FONT_SIZE) {
+            return Err(HelperError::InvalidInput(format!(
+                "Caption font size must be greater than 0 and at most {}",
+                MAX_CAPTION_FONT_SIZE
+            )));
+        }
+        if self.padding_px > MAX_CAPTION_PADDING {
+            return Err(HelperError::InvalidInput(format!(
+                "Caption padding must be at most {}",
+                MAX_CAPTION_PADDING
+            )));
+        }
+        FontRef::try_from_slice(self.font_data).map_err(|_| {
+            HelperError::InvalidInput("Caption font could not be parsed".to_string())
+        })?;
+        Ok(())
     }
+}
 
-    write!(
-        result,
-        "<image x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" href=\"data:image/png;base64,{}\" preserveAspectRatio=\"xMidYMid meet\"/>\n</svg>\n",
-        logo_center_x - max_logo_w / 2,
-        logo_center_y - max_logo_h / 2,
-        max_logo_w,
-        max_logo_h,
-        logo_base64
-    ).unwrap();
+/// Configuration for SVG styled QR codes
+#[cfg(feature = "image")]
+#[deprecated(since = "0.1.32", note = "use `QrStyle` with `SvgRenderer` instead")]
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(remote = "Self", default)
+)]
+pub struct FrameQrSvgConfig<'a> {
+    pub logo_path: &'a str,
+    pub upscale_factor: u32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::color"))]
+    pub qr_color: [u8; 3],
+    pub outer_frame_px: u32,
+    pub inner_frame_px: u32,
+    pub frame_style: FrameStyle,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub caption: Option<Caption<'a>>,
+}
+
+#[cfg(feature = "image")]
+#[allow(deprecated)]
+impl<'a> Default for FrameQrSvgConfig<'a> {
+    fn default() -> Self {
+        Self {
+            logo_path: "",
+            upscale_factor: DEFAULT_UPSCALE_FACTOR,
+            qr_color: [0, 0, 0],
+            outer_frame_px: 0,
+            inner_frame_px: DEFAULT_INNER_FRAME,
+            frame_style: FrameStyle::None,
+            caption: None,
+        }
+    }
+}
 
-    Ok(result)
+#[cfg(feature = "image")]
+#[allow(deprecated)]
+impl<'a> FrameQrSvgConfig<'a> {
+    pub fn new(logo_path: &'a str) -> Result<Self, HelperError> {
+        if logo_path.is_empty() {
+            return Err(HelperError::InvalidInput(
+                "Logo path cannot be empty".to_string(),
+            ));
+        }
+        Ok(Self {
+            logo_path,
+            ..Default::default()
+        })
+    }
+
+    pub fn with_upscale(mut self, factor: u32) -> Result<Self, HelperError> {
+        if factor == 0 || factor > MAX_UPSCALE_FACTOR {
+            return Err(HelperError::InvalidInput(format!(
+                "Upscale factor must be between 1 and {}",
+                MAX_UPSCALE_FACTOR
+            )));
+        }
+        self.upscale_factor = factor;
+        Ok(self)
+    }
+
+    pub fn with_color(mut self, color: [u8; 3]) -> Self {
+        self.qr_color = color;
+        self
+    }
+
+    pub fn with_outer_frame(mut self, size: u32) -> Self {
+        self.outer_frame_px = size;
+        self
+    }
+
+    pub fn with_inner_frame(mut self, size: u32) -> Self {
+        self.inner_frame_px = size;
+        self
+    }
+
+    pub fn with_frame_style(mut self, style: FrameStyle) -> Self {
+        self.frame_style = style;
+        self
+    }
+
+    pub fn with_caption(mut self, caption: Caption<'a>) -> Self {
+        self.caption = Some(caption);
+        self
+    }
+
+    /// Validates the configuration before use
+    pub fn validate(&self) -> Result<(), HelperError> {
+        if self.logo_path.is_empty() {
+            return Err(HelperError::InvalidInput(
+                "Logo path is required".to_string(),
+            ));
+        }
+        if self.upscale_factor == 0 || self.upscale_factor > MAX_UPSCALE_FACTOR {
+            return Err(HelperError::InvalidInput(format!(
+                "Upscale factor must be between 1 and {}",
+                MAX_UPSCALE_FACTOR
+            )));
+        }
+        if let Some(caption) = &self.caption {
+            caption.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// Converts the pixel-based outer frame into a quiet zone of `outer_frame_px / upscale_factor`
+/// modules, rounded up and capped at the maximum quiet zone.
+#[cfg(feature = "image")]
+#[allow(deprecated)]
+impl<'a> From<FrameQrSvgConfig<'a>> for QrStyle<'a> {
+    fn from(config: FrameQrSvgConfig<'a>) -> Self {
+        QrStyle {
+            fg_color: config.qr_color,
+            quiet_zone: frame_to_quiet_zone(config.outer_frame_px, config.upscale_factor),
+            module_size: config.upscale_factor,
+            logo_path: Some(config.logo_path)
+                .filter(|path| !path.is_empty())
+                .map(Cow::Borrowed),
+            frame_style: config.frame_style,
+            inner_frame_px: config.inner_frame_px,
+            caption: config.caption,
+            logo_upscale: false,
+            ..Default::default()
+        }
+    }
+}
+
+/// Generates an SVG string for a styled QR code with an embedded logo.
+///
+/// Renders a QR code with a logo, supporting custom colors, outer frames, and square or rounded
+/// frames behind the logo. SVG logos are inlined as vector markup with prefixed ids, and other logos
+/// are embedded as base64-encoded PNGs. Uses horizontal module grouping for efficiency.
+/// An optional [Caption] is rendered as vector outlines in a banner above or below the code.
+///
+/// # Arguments
+///
+/// * `qr` - The [QrCode] to render.
+/// * `config` - Configuration for styling ([FrameQrSvgConfig]).
+///
+/// # Returns
+///
+/// A `Result` containing the SVG string, or a [HelperError] such as [HelperError::LogoNotFound] or
+/// [HelperError::LogoDecode] naming the logo path on failure.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use qirust::qrcode::{QrCode, QrCodeEcc, Version, EncodeTextOptions};
+/// use qirust::helper::{frameqr_to_svg_string, FrameQrSvgConfig, FrameStyle};
+///
+/// let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let qr = QrCode::encode_text(
+///     "https://example.com",
+///     &mut tempbuffer,
+///     &mut outbuffer,
+///     EncodeTextOptions {
+///         ecl: QrCodeEcc::High,
+///         minversion: Version::MIN,
+///         maxversion: Version::MAX,
+///         mask: None,
+///         boostecl: true,
+///     },
+/// ).unwrap();
+///
+/// let config = FrameQrSvgConfig::new("logo.png").unwrap()
+///     .with_upscale(6).unwrap()
+///     .with_color([255, 165, 0])
+///     .with_outer_frame(40)
+///     .with_inner_frame(10)
+///     .with_frame_style(FrameStyle::Rounded);
+///
+/// let svg = frameqr_to_svg_string(qr, config).expect("Failed to generate SVG");
+/// println!("{}", svg);
+/// ```
+#[cfg(feature = "image")]
+#[deprecated(since = "0.1.32", note = "use `SvgRenderer` with a `QrStyle` instead")]
+#[allow(deprecated)]
+pub fn frameqr_to_svg_string(qr: QrCode, config: FrameQrSvgConfig) -> Result<String, HelperError> {
+    // The outer frame is kept in exact pixels rather than rounded to whole modules
+    let outer_frame = config.outer_frame_px;
+    let style = QrStyle::from(config);
+    render_svg(&qr, &style, outer_frame)
+}
+
+/// Prints a QR code to the console using ASCII characters.
+///
+/// Uses `█` for dark modules and spaces for light modules, with a fixed 4-module border for clarity.
+/// Each module is represented by two characters for better visibility.
+///
+/// # Arguments
+///
+/// * `qr` - The [QrCode] to print.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::qrcode::{QrCode, QrCodeEcc, Version, EncodeTextOptions};
+/// use qirust::helper::print_qr;
+///
+/// let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let qr = QrCode::encode_text(
+///     "Hello, World!",
+///     &mut tempbuffer,
+///     &mut outbuffer,
+///     EncodeTextOptions {
+///         ecl: QrCodeEcc::Low,
+///         minversion: Version::MIN,
+///         maxversion: Version::MAX,
+///         mask: None,
+///         boostecl: true,
+///     },
+/// ).unwrap();
+///
+/// print_qr(&qr);
+/// ```
+///
+/// # Performance
+///
+/// Minimal overhead due to simple iteration over QR modules and direct console output. Suitable for
+/// quick debugging or terminal-based applications.
+pub fn print_qr(qr: &QrCode) {
+    println!("{}", to_terminal_string(qr, 4));
 }
 
-/// Prints a QR code to the console using ASCII characters.
+/// Generates a terminal-friendly string for a QR code.
 ///
-/// Uses `█` for dark modules and spaces for light modules, with a fixed 4-module border for clarity.
-/// Each module is represented by two characters for better visibility.
+/// Uses `█` for dark modules and spaces for light modules, with each module represented by two
+/// characters so the code keeps its square aspect ratio in most terminal fonts. Every row, including
+/// the last, ends with a Unix newline (`\n`).
 ///
 /// # Arguments
 ///
-/// * `qr` - The [QrCode] to print.
+/// * `qr` - The [QrCode] to render.
+/// * `border` - Number of border modules (must be non-negative).
+///
+/// # Returns
+///
+/// A `String` containing the rendered rows.
 ///
 /// # Example
 ///
 /// ```rust
 /// use qirust::qrcode::{QrCode, QrCodeEcc, Version, EncodeTextOptions};
-/// use qirust::helper::print_qr;
+/// use qirust::helper::to_terminal_string;
 ///
 /// let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
 /// let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
@@ -761,29 +1683,29 @@ pub fn frameqr_to_svg_string(
 ///     },
 /// ).unwrap();
 ///
-/// print_qr(&qr);
+/// let text = to_terminal_string(&qr, 2);
+/// assert_eq!(text.lines().count(), (qr.size() + 4) as usize);
 /// ```
-///
-/// # Performance
-///
-/// Minimal overhead due to simple iteration over QR modules and direct console output. Suitable for
-/// quick debugging or terminal-based applications.
-pub fn print_qr(qr: &QrCode) {
-    let border: i32 = 4;
+pub fn to_terminal_string(qr: &QrCode, border: i32) -> String {
+    let dimension = (qr.size() + border * 2) as usize;
+    let mut result = String::with_capacity(dimension * (dimension * 6 + 1));
     for y in -border..qr.size() + border {
         for x in -border..qr.size() + border {
             let c: char = if qr.get_module(x, y) { '█' } else { ' ' };
-            print!("{0}{0}", c);
+            result.push(c);
+            result.push(c);
         }
-        println!();
+        result.push('\n');
     }
-    println!();
+    result
 }
 
 /// Saves a QR code as a PNG image.
 ///
 /// Renders a basic QR code with a black-and-white color scheme and a 4-module border, saving it to
-/// a PNG file. The output directory is created if it does not exist.
+/// a PNG file. The output directory is created if it does not exist. Use
+/// [qr_to_image_and_save_with] to select another format, or [qr_to_writer] to write the image
+/// somewhere other than a file.
 ///
 /// # Arguments
 ///
@@ -793,7 +1715,7 @@ pub fn print_qr(qr: &QrCode) {
 ///
 /// # Returns
 ///
-/// A `Result` indicating success or an [image::ImageError] on failure (e.g., invalid directory path).
+/// A `Result` indicating success or a [HelperError] on failure (e.g., invalid directory path).
 ///
 /// # Example
 ///
@@ -825,11 +1747,109 @@ pub fn print_qr(qr: &QrCode) {
 /// Efficient for small to medium QR codes due to single-pass rendering. For large QR codes (e.g.,
 /// Version 40), consider using [generate_image_buffer] for in-memory processing to avoid immediate
 /// disk I/O.
+#[cfg(feature = "image")]
 pub fn qr_to_image_and_save(
     qr: &QrCode,
     directory_path: Option<&str>,
     filename: Option<&str>,
-) -> Result<(), image::ImageError> {
+) -> Result<(), HelperError> {
+    let mut output = OutputConfig::new();
+    if let Some(directory_path) = directory_path {
+        output = output.with_directory(directory_path);
+    }
+    if let Some(filename) = filename {
+        output = output.with_filename(filename);
+    }
+    qr_to_image_and_save_with(qr, &output)?;
+    Ok(())
+}
+
+/// Saves a basic QR code as an image file in the directory, file name and format of `output`.
+///
+/// Renders the same black-and-white image as [qr_to_image_and_save], one pixel per module with a
+/// 4-module border, and returns the path of the written file.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::qrcode::{QrCode, QrCodeEcc, Version, EncodeTextOptions};
+/// use qirust::helper::{qr_to_image_and_save_with, ImageOutputFormat, OutputConfig};
+///
+/// let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let qr = QrCode::encode_text(
+///     "Hello, World!",
+///     &mut tempbuffer,
+///     &mut outbuffer,
+///     EncodeTextOptions {
+///         ecl: QrCodeEcc::Low,
+///         minversion: Version::MIN,
+///         maxversion: Version::MAX,
+///         mask: None,
+///         boostecl: true,
+///     },
+/// ).unwrap();
+///
+/// let output = OutputConfig::new()
+///     .with_directory("target/doctest")
+///     .with_filename("plain_qr")
+///     .with_format(ImageOutputFormat::Bmp);
+/// let path = qr_to_image_and_save_with(&qr, &output).expect("Failed to save QR code");
+/// assert!(path.ends_with("plain_qr.bmp"));
+/// ```
+#[cfg(feature = "image")]
+pub fn qr_to_image_and_save_with(
+    qr: &QrCode,
+    output: &OutputConfig,
+) -> Result<PathBuf, HelperError> {
+    output.validate()?;
+    let file_path = output.prepare_path()?;
+    save_image(&plain_image(qr), output.format, &file_path)?;
+    Ok(file_path)
+}
+
+/// Writes a basic QR code to any [std::io::Write] in the given image format.
+///
+/// The in-memory counterpart of [qr_to_image_and_save_with]. See [write_image] for how each format
+/// is encoded.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::qrcode::{QrCode, QrCodeEcc, Version, EncodeTextOptions};
+/// use qirust::helper::{qr_to_writer, ImageOutputFormat};
+///
+/// let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let qr = QrCode::encode_text(
+///     "Hello, World!",
+///     &mut tempbuffer,
+///     &mut outbuffer,
+///     EncodeTextOptions {
+///         ecl: QrCodeEcc::Low,
+///         minversion: Version::MIN,
+///         maxversion: Version::MAX,
+///         mask: None,
+///         boostecl: true,
+///     },
+/// ).unwrap();
+///
+/// let mut body = Vec::new();
+/// qr_to_writer(&qr, ImageOutputFormat::Png, &mut body).unwrap();
+/// assert!(body.starts_with(b"\x89PNG"));
+/// ```
+#[cfg(feature = "image")]
+pub fn qr_to_writer<W: std::io::Write>(
+    qr: &QrCode,
+    format: ImageOutputFormat,
+    writer: W,
+) -> Result<(), HelperError> {
+    write_image(&plain_image(qr), format, writer)
+}
+
+// Renders a QR code in black and white, one pixel per module with a 4-module border.
+#[cfg(feature = "image")]
+fn plain_image(qr: &QrCode) -> DynamicImage {
     let border: i32 = 4;
     let size = (qr.size() as u32) + 2 * (border as u32);
     let mut img = ImageBuffer::new(size, size);
@@ -843,41 +1863,39 @@ pub fn qr_to_image_and_save(
             Luma([255u8]) // White
         };
     }
-
-    let directory_path = PathBuf::from(directory_path.unwrap_or("generated"));
-    let filename = match filename {
-        Some(name) => name.to_string(),
-        None => {
-            let start = SystemTime::now();
-            let since_the_epoch = start
-                .duration_since(UNIX_EPOCH)
-                .expect("Time went backwards");
-            format!("{:?}", since_the_epoch)
-        }
-    };
-
-    let file_path = directory_path.join(format!("{}.png", filename));
-
-    if !Path::new(&directory_path).exists() {
-        fs::create_dir_all(directory_path)?;
-    }
-
-    img.save(&Path::new(&file_path))
+    DynamicImage::ImageLuma8(img)
 }
 
 /// Configuration for saving styled QR codes with frames and logos
+#[cfg(feature = "image")]
+#[deprecated(
+    since = "0.1.32",
+    note = "use `QrStyle` with `OutputConfig` and `styled_to_image_and_save` instead"
+)]
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(remote = "Self", default)
+)]
 pub struct FrameQrSaveConfig<'a> {
     pub logo_path: &'a str,
     pub upscale_factor: u32,
     pub directory_path: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub file_name: Option<&'a str>,
+    pub format: ImageOutputFormat,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::color"))]
     pub qr_color: [u8; 3],
     pub outer_frame_px: u32,
     pub inner_frame_px: u32,
     pub frame_style: FrameStyle,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub caption: Option<Caption<'a>>,
 }
 
+#[cfg(feature = "image")]
+#[allow(deprecated)]
 impl<'a> Default for FrameQrSaveConfig<'a> {
     fn default() -> Self {
         Self {
@@ -885,14 +1903,18 @@ impl<'a> Default for FrameQrSaveConfig<'a> {
             upscale_factor: DEFAULT_UPSCALE_FACTOR,
             directory_path: "generated",
             file_name: None,
+            format: ImageOutputFormat::Png,
             qr_color: [0, 0, 0],
             outer_frame_px: 0,
             inner_frame_px: DEFAULT_INNER_FRAME,
             frame_style: FrameStyle::None,
+            caption: None,
         }
     }
 }
 
+#[cfg(feature = "image")]
+#[allow(deprecated)]
 impl<'a> FrameQrSaveConfig<'a> {
     pub fn new(logo_path: &'a str) -> Result<Self, HelperError> {
         if logo_path.is_empty() {
@@ -927,6 +1949,11 @@ impl<'a> FrameQrSaveConfig<'a> {
         self
     }
 
+    pub fn with_format(mut self, format: ImageOutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
     pub fn with_color(mut self, color: [u8; 3]) -> Self {
         self.qr_color = color;
         self
@@ -947,6 +1974,11 @@ impl<'a> FrameQrSaveConfig<'a> {
         self
     }
 
+    pub fn with_caption(mut self, caption: Caption<'a>) -> Self {
+        self.caption = Some(caption);
+        self
+    }
+
     pub fn validate(&self) -> Result<(), HelperError> {
         if self.logo_path.is_empty() {
             return Err(HelperError::InvalidInput(
@@ -959,15 +1991,53 @@ impl<'a> FrameQrSaveConfig<'a> {
                 MAX_UPSCALE_FACTOR
             )));
         }
-        Ok(())
+        if let Some(caption) = &self.caption {
+            caption.validate()?;
+        }
+        self.format.validate()
+    }
+}
+
+/// Converts the pixel-based outer frame into a quiet zone of `outer_frame_px / upscale_factor`
+/// modules, rounded up and capped at the maximum quiet zone.
+#[cfg(feature = "image")]
+#[allow(deprecated)]
+impl<'a> From<FrameQrSaveConfig<'a>> for QrStyle<'a> {
+    fn from(config: FrameQrSaveConfig<'a>) -> Self {
+        QrStyle {
+            fg_color: config.qr_color,
+            quiet_zone: frame_to_quiet_zone(config.outer_frame_px, config.upscale_factor),
+            module_size: config.upscale_factor,
+            logo_path: Some(config.logo_path)
+                .filter(|path| !path.is_empty())
+                .map(Cow::Borrowed),
+            frame_style: config.frame_style,
+            inner_frame_px: config.inner_frame_px,
+            caption: config.caption,
+            logo_upscale: false,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "image")]
+#[allow(deprecated)]
+impl<'a> From<&FrameQrSaveConfig<'a>> for OutputConfig<'a> {
+    fn from(config: &FrameQrSaveConfig<'a>) -> Self {
+        OutputConfig {
+            directory_path: Cow::Borrowed(config.directory_path),
+            file_name: config.file_name.map(Cow::Borrowed),
+            format: config.format,
+        }
     }
 }
 
-/// Saves a styled QR code with an embedded logo as a PNG image.
+/// Saves a styled QR code with an embedded logo as an image file.
 ///
 /// Renders a QR code with a logo, custom colors, and optional square or rounded frames. The logo is
-/// resized to one-third of the QR code dimensions for scannability, and a global cache is used to
-/// avoid redundant resizing. The output directory is created if it does not exist.
+/// fitted into one-third of the QR code dimensions for scannability, keeping its aspect ratio. The
+/// output directory is created if it does not exist.
+/// An optional [Caption] is drawn in a banner above or below the code, growing the canvas to fit.
 ///
 /// # Arguments
 ///
@@ -976,11 +2046,12 @@ impl<'a> FrameQrSaveConfig<'a> {
 ///
 /// # Returns
 ///
-/// A `Result` indicating success or an [image::ImageError] on failure.
+/// A `Result` indicating success, or a [HelperError] on failure. A missing or undecodable logo is
+/// reported as [HelperError::LogoNotFound] or [HelperError::LogoDecode] with the failing path.
 ///
 /// # Example
 ///
-/// ```rust
+/// ```rust,no_run
 /// use qirust::qrcode::{QrCode, QrCodeEcc, Version, EncodeTextOptions};
 /// use qirust::helper::{frameqr_to_image_and_save, FrameQrSaveConfig, FrameStyle};
 ///
@@ -1013,173 +2084,34 @@ impl<'a> FrameQrSaveConfig<'a> {
 ///
 /// # Performance
 ///
-/// Optimized with horizontal module grouping and a global `Mutex`-based cache for logo resizing,
-/// reducing redundant processing. Uses `FilterType::Nearest` for fast logo resizing, suitable for
-/// most applications.
+/// Optimized with horizontal module grouping. The logo is loaded and resized on every call; render
+/// through [crate::render::QrStyle::with_logo_cache] to reuse it. The logo is resampled with
+/// [crate::logo::LogoFilter::Lanczos3] unless the style selects another filter.
 ///
 /// # Notes
 ///
-/// - The logo is resized to one-third of the QR code dimensions to ensure scannability.
+/// - The logo is fitted into one-third of the QR code dimensions, keeping its aspect ratio, to
+///   ensure scannability.
 /// - Ensure the logo file exists and is accessible before calling.
 /// - For in-memory processing, consider using [generate_frameqr_buffer] to avoid immediate disk I/O.
-pub fn frameqr_to_image_and_save(
-    qr: QrCode,
-    config: FrameQrSaveConfig,
-) -> Result<(), image::ImageError> {
+/// - The file is written in [FrameQrSaveConfig::format], a PNG unless
+///   [FrameQrSaveConfig::with_format] selects another [ImageOutputFormat].
+#[cfg(feature = "image")]
+#[deprecated(since = "0.1.32", note = "use `styled_to_image_and_save` instead")]
+#[allow(deprecated)]
+pub fn frameqr_to_image_and_save(qr: QrCode, config: FrameQrSaveConfig) -> Result<(), HelperError> {
     // Validate config
-    config.validate().map_err(|e| {
-        image::ImageError::IoError(std::io::Error::new(
-            std::io::ErrorKind::InvalidInput,
-            e.to_string(),
-        ))
-    })?;
-
-    let qr_size = qr.size() as u32;
-    let mut qr_img = ImageBuffer::new(qr_size, qr_size);
-
-    // Render QR code modules
-    for y in 0..qr_size {
-        for x in 0..qr_size {
-            let color = if qr.get_module(x as i32, y as i32) {
-                Rgb(config.qr_color)
-            } else {
-                Rgb([255, 255, 255])
-            };
-            qr_img.put_pixel(x, y, color);
-        }
-    }
-
-    // Upscale QR code
-    let mut upscaled_qr = resize(
-        &DynamicImage::ImageRgb8(qr_img),
-        qr_size * config.upscale_factor,
-        qr_size * config.upscale_factor,
-        FilterType::Nearest,
-    );
-
-    // Load and validate logo
-    let full_path = env::current_dir()?.join(config.logo_path);
-    if !full_path.exists() {
-        return Err(image::ImageError::IoError(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            format!("Logo file not found: {:?}", full_path),
-        )));
-    }
-    let logo = image::open(&full_path)?.to_rgba8();
-
-    // Calculate logo dimensions
-    let max_logo_w = upscaled_qr.width() / LOGO_SIZE_DIVISOR;
-    let max_logo_h = upscaled_qr.height() / LOGO_SIZE_DIVISOR;
-
-    // Cache for resized logos
-    static LOGO_RESIZE_CACHE: Mutex<Option<(String, u32, u32, RgbaImage)>> = Mutex::new(None);
-
-    let logo_resized = {
-        let mut cache = LOGO_RESIZE_CACHE.lock().unwrap();
-        if let Some((cached_path, cached_w, cached_h, cached_logo)) = cache.as_ref() {
-            if cached_path == config.logo_path && *cached_w == max_logo_w && *cached_h == max_logo_h
-            {
-                cached_logo.clone()
-            } else {
-                let resized = if logo.width() > max_logo_w || logo.height() > max_logo_h {
-                    resize(&logo, max_logo_w, max_logo_h, FilterType::Nearest)
-                } else {
-                    logo
-                };
-                *cache = Some((
-                    config.logo_path.to_string(),
-                    max_logo_w,
-                    max_logo_h,
-                    resized.clone(),
-                ));
-                resized
-            }
-        } else {
-            let resized = if logo.width() > max_logo_w || logo.height() > max_logo_h {
-                resize(&logo, max_logo_w, max_logo_h, FilterType::Nearest)
-            } else {
-                logo
-            };
-            *cache = Some((
-                config.logo_path.to_string(),
-                max_logo_w,
-                max_logo_h,
-                resized.clone(),
-            ));
-            resized
-        }
-    };
-
-    // Calculate logo position
-    let x_offset = (upscaled_qr.width() - logo_resized.width()) / 2;
-    let y_offset = (upscaled_qr.height() - logo_resized.height()) / 2;
-
-    // Apply frame style
-    match config.frame_style {
-        FrameStyle::Rounded => {
-            let margin = config.inner_frame_px;
-            let radius = (logo_resized.width().min(logo_resized.height()) + 2 * margin) / 2;
-            let mask = create_circle_mask(radius * 2, radius as i32);
-            let mask_x = x_offset + logo_resized.width() / 2 - radius;
-            let mask_y = y_offset + logo_resized.height() / 2 - radius;
-            overlay(&mut upscaled_qr, &mask, mask_x as i64, mask_y as i64);
-        }
-        FrameStyle::Square => {
-            let frame_margin = config.inner_frame_px;
-            for y in y_offset.saturating_sub(frame_margin)
-                ..(y_offset + logo_resized.height() + frame_margin).min(upscaled_qr.height())
-            {
-                for x in x_offset.saturating_sub(frame_margin)
-                    ..(x_offset + logo_resized.width() + frame_margin).min(upscaled_qr.width())
-                {
-                    upscaled_qr.put_pixel(x, y, image::Rgba([255, 255, 255, 255]));
-                }
-            }
-        }
-        FrameStyle::None => {}
-    }
+    config.validate()?;
 
-    // Overlay logo
-    overlay(
-        &mut upscaled_qr,
-        &logo_resized,
-        x_offset as i64,
-        y_offset as i64,
-    );
+    // The outer frame is kept in exact pixels rather than rounded to whole modules
+    let output = OutputConfig::from(&config);
+    let outer_frame = config.outer_frame_px;
+    let style = QrStyle::from(config);
+    let img = render_raster(&qr, &style, outer_frame)?;
 
-    // Prepare output path
-    let filename = config.file_name.map(|s| s.to_string()).unwrap_or_else(|| {
-        let start = SystemTime::now();
-        let since_the_epoch = start
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards");
-        format!("{:?}", since_the_epoch)
-    });
-
-    let file_path = format!("{}/{}.png", config.directory_path, filename);
-
-    // Create directory if it doesn't exist
-    if !Path::new(config.directory_path).exists() {
-        fs::create_dir_all(config.directory_path)?;
-    }
-
-    // Save with or without outer frame
-    if config.outer_frame_px > 0 {
-        let frame_px = config.outer_frame_px;
-        let final_w = upscaled_qr.width() + frame_px * 2;
-        let final_h = upscaled_qr.height() + frame_px * 2;
-        let mut final_image =
-            RgbaImage::from_pixel(final_w, final_h, image::Rgba([255, 255, 255, 255]));
-        overlay(
-            &mut final_image,
-            &upscaled_qr,
-            frame_px as i64,
-            frame_px as i64,
-        );
-        final_image.save(&Path::new(&file_path))
-    } else {
-        upscaled_qr.save(&Path::new(&file_path))
-    }
+    let file_path = output.prepare_path()?;
+    save_image(&DynamicImage::ImageRgba8(img), output.format, &file_path)?;
+    Ok(())
 }
 
 /// Generates and saves a styled QR code from text content.
@@ -1232,9 +2164,13 @@ pub fn frameqr_to_image_and_save(
 ///
 /// # Notes
 ///
-/// - The logo is resized to one-third of the QR code dimensions to ensure scannability.
+/// - The logo is fitted into one-third of the QR code dimensions, keeping its aspect ratio, to
+///   ensure scannability.
 /// - Ensure the logo file exists and is accessible before calling.
 /// - For invalid input data, the underlying [QrCode::encode_text] may return a [DataTooLong] error.
+#[cfg(feature = "image")]
+#[deprecated(since = "0.1.32", note = "use `generate_styled` instead")]
+#[allow(deprecated)]
 pub fn generate_frameqr(content: &str, config: FrameQrConfig) -> Result<(), HelperError> {
     // Validate input
     if content.is_empty() {
@@ -1269,13 +2205,189 @@ pub fn generate_frameqr(content: &str, config: FrameQrConfig) -> Result<(), Help
         upscale_factor: config.upscale_factor,
         directory_path: config.directory_path,
         file_name: config.file_name,
+        format: config.format,
         qr_color: config.qr_color,
         outer_frame_px: config.outer_frame_px,
         inner_frame_px: config.inner_frame_px,
         frame_style: config.frame_style,
+        caption: config.caption,
     };
 
-    frameqr_to_image_and_save(qr, save_config).map_err(HelperError::from)
+    frameqr_to_image_and_save(qr, save_config)
+}
+
+/// Saves a QR code rendered with a [QrStyle] as an image file.
+///
+/// Renders the code with [RasterRenderer], so colors, quiet zone, module size, logo and caption all
+/// come from `style`, while the destination and image format come from `output`. The output
+/// directory is created if it does not exist. Use [styled_to_writer] to write the image somewhere
+/// other than a file.
+///
+/// # Arguments
+///
+/// * `qr` - The [QrCode] to render.
+/// * `style` - Styling options ([QrStyle]).
+/// * `output` - Destination directory, file name and format ([OutputConfig]).
+///
+/// # Returns
+///
+/// The path of the written file, or a [HelperError] if the style or output configuration is
+/// invalid, the logo cannot be loaded, or the file cannot be written.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::qrcode::{QrCode, QrCodeEcc, Version, EncodeTextOptions};
+/// use qirust::helper::{styled_to_image_and_save, ImageOutputFormat, OutputConfig};
+/// use qirust::render::QrStyle;
+///
+/// let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let qr = QrCode::encode_text(
+///     "https://example.com",
+///     &mut tempbuffer,
+///     &mut outbuffer,
+///     EncodeTextOptions {
+///         ecl: QrCodeEcc::High,
+///         minversion: Version::MIN,
+///         maxversion: Version::MAX,
+///         mask: None,
+///         boostecl: true,
+///     },
+/// ).unwrap();
+///
+/// let style = QrStyle::new().with_fg_color([255, 165, 0]).with_module_size(6).unwrap();
+/// let output = OutputConfig::new().with_directory("target/doctest").with_filename("styled_qr");
+///
+/// let path = styled_to_image_and_save(&qr, &style, &output).expect("Failed to save QR code");
+/// assert!(path.ends_with("styled_qr.png"));
+///
+/// let output = output.with_format(ImageOutputFormat::jpeg(85).unwrap());
+/// let path = styled_to_image_and_save(&qr, &style, &output).expect("Failed to save QR code");
+/// assert!(path.ends_with("styled_qr.jpg"));
+/// ```
+#[cfg(feature = "image")]
+pub fn styled_to_image_and_save(
+    qr: &QrCode,
+    style: &QrStyle,
+    output: &OutputConfig,
+) -> Result<PathBuf, HelperError> {
+    output.validate()?;
+    let img = RasterRenderer.render(qr, style)?;
+    let file_path = output.prepare_path()?;
+    save_image(&DynamicImage::ImageRgba8(img), output.format, &file_path)?;
+    Ok(file_path)
+}
+
+/// Writes a QR code rendered with a [QrStyle] to any [std::io::Write] in the given image format.
+///
+/// The in-memory counterpart of [styled_to_image_and_save] for HTTP response bodies, sockets or
+/// buffers. See [write_image] for how each format is encoded.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::qrcode::{QrCode, QrCodeEcc, Version, EncodeTextOptions};
+/// use qirust::helper::{styled_to_writer, ImageOutputFormat};
+/// use qirust::render::QrStyle;
+///
+/// let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let qr = QrCode::encode_text(
+///     "https://example.com",
+///     &mut tempbuffer,
+///     &mut outbuffer,
+///     EncodeTextOptions {
+///         ecl: QrCodeEcc::Medium,
+///         minversion: Version::MIN,
+///         maxversion: Version::MAX,
+///         mask: None,
+///         boostecl: true,
+///     },
+/// ).unwrap();
+///
+/// let mut body = Vec::new();
+/// styled_to_writer(&qr, &QrStyle::new(), ImageOutputFormat::Gif, &mut body).unwrap();
+/// assert!(body.starts_with(b"GIF89a"));
+/// ```
+#[cfg(feature = "image")]
+pub fn styled_to_writer<W: std::io::Write>(
+    qr: &QrCode,
+    style: &QrStyle,
+    format: ImageOutputFormat,
+    writer: W,
+) -> Result<(), HelperError> {
+    format.validate()?;
+    let img = RasterRenderer.render(qr, style)?;
+    write_image(&DynamicImage::ImageRgba8(img), format, writer)
+}
+
+/// Generates and saves a styled QR code from text content.
+///
+/// Encodes `content` with the error correction level of [QrStyle::ecc] and saves it with
+/// [styled_to_image_and_save]. This replaces [generate_frameqr] and, unlike it, does not require a
+/// logo.
+///
+/// # Arguments
+///
+/// * `content` - The text to encode (must not be empty).
+/// * `style` - Styling options ([QrStyle]).
+/// * `output` - Destination directory, file name and format ([OutputConfig]).
+///
+/// # Returns
+///
+/// The path of the written file, or a [HelperError] if the content is empty or too long, or
+/// rendering or saving fails.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use qirust::helper::{generate_styled, FrameStyle, OutputConfig};
+/// use qirust::qrcode::QrCodeEcc;
+/// use qirust::render::QrStyle;
+///
+/// let style = QrStyle::new()
+///     .with_ecc(QrCodeEcc::High)
+///     .with_module_size(6).unwrap()
+///     .with_fg_color([255, 165, 0])
+///     .with_logo("logo.png").unwrap()
+///     .with_inner_frame(10)
+///     .with_frame_style(FrameStyle::Rounded);
+/// let output = OutputConfig::new().with_directory("output").with_filename("styled_qr");
+///
+/// generate_styled("https://example.com", &style, &output)
+///     .expect("Failed to generate QR code");
+/// ```
+#[cfg(feature = "image")]
+pub fn generate_styled(
+    content: &str,
+    style: &QrStyle,
+    output: &OutputConfig,
+) -> Result<PathBuf, HelperError> {
+    // Validate input
+    if content.is_empty() {
+        return Err(HelperError::InvalidInput(
+            "Content cannot be empty".to_string(),
+        ));
+    }
+
+    let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+    let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+    let qr = QrCode::encode_text(
+        content,
+        &mut tempbuffer,
+        &mut outbuffer,
+        EncodeTextOptions {
+            ecl: style.ecc,
+            minversion: Version::MIN,
+            maxversion: Version::MAX,
+            mask: None,
+            boostecl: true,
+        },
+    )?;
+    std::mem::drop(tempbuffer);
+
+    styled_to_image_and_save(&qr, style, output)
 }
 
 /// Generates and saves a basic QR code image from text content.
@@ -1291,7 +2403,7 @@ pub fn generate_frameqr(content: &str, config: FrameQrConfig) -> Result<(), Help
 ///
 /// # Returns
 ///
-/// A `Result` indicating success or an [image::ImageError] on failure (e.g., invalid directory path).
+/// A `Result` indicating success or a [HelperError] on failure (e.g., invalid directory path).
 ///
 /// # Example
 ///
@@ -1311,6 +2423,7 @@ pub fn generate_frameqr(content: &str, config: FrameQrConfig) -> Result<(), Help
 ///
 /// - Uses a low error correction level ([QrCodeEcc::Low]) for maximum data capacity.
 /// - For invalid input data, the underlying [QrCode::encode_text] may return a [DataTooLong] error.
+#[cfg(feature = "image")]
 pub fn generate_image(
     content: &str,
     directory: Option<&str>,
@@ -1341,7 +2454,7 @@ pub fn generate_image(
         },
     )?;
     std::mem::drop(tempbuffer);
-    qr_to_image_and_save(&qr, directory, filename).map_err(HelperError::from)
+    qr_to_image_and_save(&qr, directory, filename)
 }
 
 /// Generates an SVG string for a QR code from text content.
@@ -1376,7 +2489,7 @@ pub fn generate_image(
 ///
 /// - Uses a high error correction level ([QrCodeEcc::High]) for robustness.
 /// - For invalid input data, the underlying [QrCode::encode_text] may return a [DataTooLong] error,
-///   which is converted to a panic in this function.
+///   which is returned as [HelperError::DataTooLong].
 pub fn generate_svg_string(content: &str) -> Result<String, HelperError> {
     // Validate input
     if content.is_empty() {
@@ -1441,55 +2554,52 @@ pub fn mix_colors(pixel: u8, foreground: u8, background: u8) -> u8 {
 
 /// Generates an in-memory image buffer for a QR code.
 ///
-/// Encodes the input text into a QR code with a high error correction level and renders it as an
-/// in-memory RGB image buffer with customizable border, colors, and scale. Uses per-pixel rendering
-/// for simplicity, suitable for most use cases.
+/// Encodes the input text into a QR code with the error correction level of the style and renders it
+/// as an in-memory RGB image buffer with [RasterRenderer]. Accepts a [QrStyle] or, for backwards
+/// compatibility, the deprecated [QrConfig].
 ///
 /// # Arguments
 ///
 /// * `content` - The text to encode.
-/// * `border` - Optional border size in modules (defaults to 4).
-/// * `fg_color` - Optional foreground color as `[R, G, B]` array (defaults to black, `[0, 0, 0]`).
-/// * `bg_color` - Optional background color as `[R, G, B]` array (defaults to white, `[255, 255, 255]`).
-/// * `scale` - Optional scaling factor for pixel size per QR module (defaults to 4).
+/// * `style` - Styling options, anything convertible into a [QrStyle].
 ///
 /// # Returns
 ///
-/// A `Result` containing an [ImageBuffer] with the QR code image, or a [DataTooLong] error if the
-/// content exceeds the QR code's capacity.
+/// A `Result` containing an [ImageBuffer] with the QR code image, or a [HelperError] if the content
+/// is empty or exceeds the QR code's capacity, or the style is invalid.
 ///
 /// # Example
 ///
 /// ```rust
-/// use qirust::helper::{generate_image_buffer, QrConfig};
+/// use qirust::helper::generate_image_buffer;
+/// use qirust::render::QrStyle;
 ///
-/// let config = QrConfig::new()
-///     .with_border(4).unwrap()
+/// let style = QrStyle::new()
+///     .with_quiet_zone(4).unwrap()
 ///     .with_fg_color([255, 0, 0])
 ///     .with_bg_color([255, 255, 255])
-///     .with_scale(6).unwrap();
+///     .with_module_size(6).unwrap();
 ///
-/// let img = generate_image_buffer("Hello, World!", config)
+/// let img = generate_image_buffer("Hello, World!", style)
 ///     .expect("Failed to generate image buffer");
 /// # img.save("target/doctest_qr.png").ok();
 /// ```
 ///
 /// # Performance
 ///
-/// Uses per-pixel rendering, which is straightforward but may be slower for high-version QR codes
-/// (e.g., Version 40). For better performance, consider optimizing with horizontal module grouping.
-/// Uses `FilterType::Nearest` for fast scaling and precomputed buffer sizes to minimize allocations.
+/// Dark modules are grouped horizontally before filling, which keeps high-version QR codes (e.g.,
+/// Version 40) fast to render.
 ///
 /// # Notes
 ///
-/// - Uses a high error correction level ([QrCodeEcc::High]) for robustness.
+/// - [QrStyle] defaults to a high error correction level ([QrCodeEcc::High]) for robustness.
 /// - Colors are specified as `[R, G, B]` arrays with `u8` values (0–255).
 /// - The output image is in RGB format ([Rgb<u8>]) for compatibility with most image processing
-///   pipelines.
-/// - For styled QR codes with logos, use [generate_frameqr_buffer].
-pub fn generate_image_buffer(
+///   pipelines. Use [RasterRenderer] directly for an RGBA image.
+#[cfg(feature = "image")]
+pub fn generate_image_buffer<'a>(
     content: &str,
-    config: QrConfig,
+    style: impl Into<QrStyle<'a>>,
 ) -> Result<ImageBuffer<Rgb<u8>, Vec<u8>>, HelperError> {
     // Validate input
     if content.is_empty() {
@@ -1498,8 +2608,9 @@ pub fn generate_image_buffer(
         ));
     }
 
-    // Validate config
-    config.validate()?;
+    // Validate style
+    let style = style.into();
+    style.validate()?;
 
     let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
     let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
@@ -1508,7 +2619,7 @@ pub fn generate_image_buffer(
         &mut tempbuffer,
         &mut outbuffer,
         EncodeTextOptions {
-            ecl: QrCodeEcc::High,
+            ecl: style.ecc,
             minversion: Version::MIN,
             maxversion: Version::MAX,
             mask: None,
@@ -1517,41 +2628,15 @@ pub fn generate_image_buffer(
     )?;
     std::mem::drop(tempbuffer);
 
-    let qr_size = qr.size() as u32;
-    let img_size = (qr_size + 2 * config.border) * config.scale;
-
-    // Check if image size would be too large
-    if img_size > MAX_IMAGE_DIMENSION {
-        return Err(HelperError::InvalidInput(format!(
-            "Generated image would be too large ({}x{}, max {}x{})",
-            img_size, img_size, MAX_IMAGE_DIMENSION, MAX_IMAGE_DIMENSION
-        )));
-    }
-
-    let mut img = ImageBuffer::from_pixel(img_size, img_size, Rgb(config.bg_color));
-
-    for y in 0..qr_size {
-        for x in 0..qr_size {
-            if qr.get_module(x as i32, y as i32) {
-                let px = (x + config.border) * config.scale;
-                let py = (y + config.border) * config.scale;
-                for dy in 0..config.scale {
-                    for dx in 0..config.scale {
-                        img.put_pixel(px + dx, py + dy, Rgb(config.fg_color));
-                    }
-                }
-            }
-        }
-    }
-
-    Ok(img)
+    let img = RasterRenderer.render(&qr, &style)?;
+    Ok(DynamicImage::ImageRgba8(img).to_rgb8())
 }
 
 /// Generates an in-memory image buffer for a styled QR code with a logo and optional frame.
 ///
 /// Renders a QR code with a centered logo, customizable colors, white border (in modules), and
-/// optional square or rounded frame behind the logo. Uses a global cache for resized logos to
-/// optimize repeated calls and horizontal module grouping for efficient rendering.
+/// optional square or rounded frame behind the logo. Uses horizontal module grouping for efficient
+/// rendering.
 ///
 /// # Arguments
 ///
@@ -1565,8 +2650,8 @@ pub fn generate_image_buffer(
 ///
 /// # Returns
 ///
-/// An [ImageBuffer] containing the styled QR code image in RGBA format. Panics on errors such as
-/// failure to load the logo or resolve the path.
+/// A `Result` containing an [ImageBuffer] with the styled QR code image in RGBA format, or a
+/// [HelperError] if the logo cannot be found or decoded, or the image would be too large.
 ///
 /// # Example
 ///
@@ -1597,7 +2682,7 @@ pub fn generate_image_buffer(
 ///     Some(4),
 ///     Some(10),
 ///     Some(FrameStyle::Rounded),
-/// );
+/// ).expect("Failed to render styled QR code");
 /// img.save("output/qr_styled.png").expect("Failed to save image");
 /// ```
 ///
@@ -1606,15 +2691,23 @@ pub fn generate_image_buffer(
 /// Optimized with:
 /// - Horizontal module grouping, reducing pixel operations by up to 30-50% for high-version QR codes
 ///   (e.g., Version 40).
-/// - Global `Mutex`-based cache for resized logos, eliminating redundant resizing across calls.
-/// - Uses `FilterType::Nearest` for fast logo resizing, balancing speed and quality.
+/// - Optional [crate::logo::LogoCache] for resized logos, set with
+///   [crate::render::QrStyle::with_logo_cache], eliminating redundant resizing across calls.
+/// - Resamples the logo with [crate::logo::LogoFilter::Lanczos3] by default; select a faster
+///   filter with [crate::render::QrStyle::with_logo_filter].
 ///
 /// # Notes
 ///
-/// - The logo is resized to one-third of the QR code dimensions to ensure scannability.
+/// - The logo is fitted into one-third of the image dimensions including the border, keeping its
+///   aspect ratio, and smaller logos are not enlarged. [crate::render::QrStyle] sizes logos from
+///   the symbol alone, so the same settings give a smaller logo there.
 /// - The output image is in RGBA format ([Rgba<u8>]) to support transparency in logos and frames.
-/// - Ensure the logo file exists and is accessible before calling, or the function will panic.
-/// - For error handling, consider using [frameqr_to_image_and_save] or [generate_frameqr].
+/// - A missing logo is reported as [HelperError::LogoNotFound] naming the path.
+#[cfg(feature = "image")]
+#[deprecated(
+    since = "0.1.32",
+    note = "use `RasterRenderer` with a `QrStyle` instead"
+)]
 pub fn generate_frameqr_buffer(
     qr: QrCode,
     logo_path: &str,
@@ -1623,123 +2716,46 @@ pub fn generate_frameqr_buffer(
     border_modules: Option<u32>,
     inner_frame_px: Option<u32>,
     frame_style: Option<FrameStyle>,
-) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
-    let scale = upscale_factor.unwrap_or(8);
-    let border = border_modules.unwrap_or(1);
-    let qr_size = qr.size() as u32;
-    let padded_size = qr_size + 2 * border;
-    let mut qr_img = ImageBuffer::from_pixel(padded_size, padded_size, Rgba([255, 255, 255, 255]));
-    let dark = qr_color.unwrap_or([0, 0, 0]);
-
-    for y in 0..qr_size {
-        let mut x = 0;
-        while x < qr_size {
-            if qr.get_module(x as i32, y as i32) {
-                let start_x = x;
-                let mut width = 1;
-                while x + 1 < qr_size && qr.get_module((x + 1) as i32, y as i32) {
-                    x += 1;
-                    width += 1;
-                }
-                for wx in start_x..start_x + width {
-                    qr_img.put_pixel(
-                        wx + border,
-                        y + border,
-                        Rgba([dark[0], dark[1], dark[2], 255]),
-                    );
-                }
-            }
-            x += 1;
-        }
-    }
-    let mut upscaled_qr = DynamicImage::ImageRgba8(resize(
-        &DynamicImage::ImageRgba8(qr_img),
-        padded_size * scale,
-        padded_size * scale,
-        FilterType::Nearest,
-    ))
-    .to_rgba8();
-
-    let full_path = std::env::current_dir()
-        .expect("Failed get root path")
-        .join(logo_path);
-    let logo = image::open(&full_path)
-        .expect("Failed to open logo")
-        .to_rgba8();
-    let max_logo_w = upscaled_qr.width() / 3;
-    let max_logo_h = upscaled_qr.height() / 3;
-    static LOGO_RESIZE_CACHE: Mutex<Option<(String, u32, u32, RgbaImage)>> = Mutex::new(None);
-    let logo_resized = {
-        let mut cache = LOGO_RESIZE_CACHE.lock().unwrap();
-        if let Some((cached_path, cached_w, cached_h, cached_logo)) = cache.as_ref() {
-            if cached_path == logo_path && *cached_w == max_logo_w && *cached_h == max_logo_h {
-                cached_logo.clone()
-            } else {
-                let resized = if logo.width() > max_logo_w || logo.height() > max_logo_h {
-                    resize(&logo, max_logo_w, max_logo_h, FilterType::Nearest)
-                } else {
-                    logo
-                };
-                *cache = Some((
-                    logo_path.to_string(),
-                    max_logo_w,
-                    max_logo_h,
-                    resized.clone(),
-                ));
-                resized
-            }
-        } else {
-            let resized = if logo.width() > max_logo_w || logo.height() > max_logo_h {
-                resize(&logo, max_logo_w, max_logo_h, FilterType::Nearest)
-            } else {
-                logo
-            };
-            *cache = Some((
-                logo_path.to_string(),
-                max_logo_w,
-                max_logo_h,
-                resized.clone(),
-            ));
-            resized
-        }
+) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>, HelperError> {
+    let style = QrStyle {
+        fg_color: qr_color.unwrap_or([0, 0, 0]),
+        quiet_zone: border_modules.unwrap_or(1),
+        module_size: upscale_factor.unwrap_or(DEFAULT_UPSCALE_FACTOR),
+        logo_path: Some(Cow::Borrowed(logo_path)),
+        frame_style: frame_style.unwrap_or(FrameStyle::None),
+        inner_frame_px: inner_frame_px.unwrap_or(DEFAULT_INNER_FRAME),
+        ..Default::default()
     };
+    let mut img = RasterRenderer.render(
+        &qr,
+        &QrStyle {
+            logo_path: None,
+            ..style.clone()
+        },
+    )?;
 
-    let x_offset = (upscaled_qr.width() - logo_resized.width()) / 2;
-    let y_offset = (upscaled_qr.height() - logo_resized.height()) / 2;
-
-    match frame_style.unwrap_or(FrameStyle::None) {
-        FrameStyle::Rounded => {
-            let margin = inner_frame_px.unwrap_or(3);
-            let radius = (logo_resized.width().min(logo_resized.height()) + 2 * margin) / 2;
-            let mask = create_circle_mask(radius * 2, radius as i32);
-            let mask_x = x_offset + logo_resized.width() / 2 - radius;
-            let mask_y = y_offset + logo_resized.height() / 2 - radius;
-            overlay(&mut upscaled_qr, &mask, mask_x as i64, mask_y as i64);
-        }
-        FrameStyle::Square => {
-            let margin = inner_frame_px.unwrap_or(3);
-            let frame_img = ImageBuffer::from_pixel(
-                logo_resized.width() + 2 * margin,
-                logo_resized.height() + 2 * margin,
-                Rgba([255, 255, 255, 255]),
-            );
-            replace(
-                &mut upscaled_qr,
-                &frame_img,
-                x_offset.saturating_sub(margin) as i64,
-                y_offset.saturating_sub(margin) as i64,
-            );
-        }
-        _ => {}
-    }
-
-    overlay(
-        &mut upscaled_qr,
-        &logo_resized,
-        x_offset as i64,
-        y_offset as i64,
+    // This function has always fitted the logo into a third of the whole image, border included,
+    // without enlarging smaller logos; `QrStyle` sizes it from the symbol alone
+    let logo = open_logo(logo_path)?;
+    let fitting = crate::logo::LogoFitting {
+        side: img.width() / LOGO_SIZE_DIVISOR,
+        fit: crate::logo::LogoFit::Contain,
+        filter: crate::logo::LogoFilter::default(),
+        upscale: false,
+    };
+    let logo = crate::logo::fit_logo(&logo, fitting);
+    let size = img.width();
+    draw_logo(
+        &mut img,
+        &logo,
+        0,
+        0,
+        size,
+        style.frame_style,
+        style.inner_frame_px,
+        Rgba([style.bg_color[0], style.bg_color[1], style.bg_color[2], 255]),
     );
-    upscaled_qr
+    Ok(img)
 }
 
 /// Converts a hexadecimal color code to an RGBA color array.
@@ -1869,7 +2885,8 @@ pub fn hex_to_rgb(hex: &str) -> Result<[u8; 3], &'static str> {
 /// - The mask is centered in the image, with `size` typically set to `2 * radius` for a perfect circle.
 /// - The output is in RGBA format with transparent background ([Rgba([0, 0, 0, 0])]) and white
 ///   foreground ([Rgba([255, 255, 255, 255])]).
-fn create_circle_mask(size: u32, radius: i32) -> RgbaImage {
+#[cfg(feature = "image")]
+fn create_circle_mask(size: u32, radius: i32, color: Rgba<u8>) -> RgbaImage {
     let mut mask = ImageBuffer::from_pixel(size, size, Rgba([0, 0, 0, 0]));
     let center = size / 2;
     for y in 0..size {
@@ -1877,19 +2894,553 @@ fn create_circle_mask(size: u32, radius: i32) -> RgbaImage {
             let dx = (x as i32) - (center as i32);
             let dy = (y as i32) - (center as i32);
             if dx * dx + dy * dy <= radius * radius {
-                mask.put_pixel(x, y, Rgba([255, 255, 255, 255]));
+                mask.put_pixel(x, y, color);
             }
         }
     }
     mask
 }
 
+// Opens and decodes the logo at `logo_path`, reporting the path when it is missing or unreadable.
+#[cfg(feature = "image")]
+fn open_logo(logo_path: &str) -> Result<RgbaImage, HelperError> {
+    let path = Path::new(logo_path);
+    if !path.exists() {
+        return Err(HelperError::LogoNotFound(path.to_path_buf()));
+    }
+    image::open(path)
+        .map(|logo| logo.to_rgba8())
+        .map_err(|source| HelperError::LogoDecode {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+// Loads the logo at `logo_path` and fits it as described by `fitting`, reusing the result stored in
+// `cache` for the same file and fitting.
+#[cfg(feature = "image")]
+pub(crate) fn load_fitted_logo(
+    logo_path: &str,
+    fitting: crate::logo::LogoFitting,
+    cache: Option<&crate::logo::LogoCache>,
+) -> Result<Arc<RgbaImage>, HelperError> {
+    let load = || Ok(crate::logo::fit_logo(&open_logo(logo_path)?, fitting));
+    match cache {
+        Some(cache) => cache.image(
+            Path::new(logo_path),
+            fitting,
+            crate::logo::LogoKind::Raster,
+            load,
+        ),
+        None => load().map(Arc::new),
+    }
+}
+
+// Returns the top-left corner of a `width` x `height` logo centered in the `size` pixels square
+// area whose top-left corner is at (`x`, `y`).
+#[cfg(feature = "image")]
+fn logo_origin(width: u32, height: u32, x: u32, y: u32, size: u32) -> (u32, u32) {
+    (
+        x + size.saturating_sub(width) / 2,
+        y + size.saturating_sub(height) / 2,
+    )
+}
+
+// Overlays the logo at the center of the `size` pixels square area whose top-left corner is at
+// (`x`, `y`), first drawing a square or circular frame of `frame_color` that extends `inner_frame`
+// pixels beyond the logo.
+#[cfg(feature = "image")]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn draw_logo(
+    img: &mut RgbaImage,
+    logo: &RgbaImage,
+    x: u32,
+    y: u32,
+    size: u32,
+    frame_style: FrameStyle,
+    inner_frame: u32,
+    frame_color: Rgba<u8>,
+) {
+    let (x_offset, y_offset) = logo_origin(logo.width(), logo.height(), x, y, size);
+
+    match frame_style {
+        FrameStyle::Rounded => {
+            let radius = (logo.width().min(logo.height()) + 2 * inner_frame) / 2;
+            let mask = create_circle_mask(radius * 2, radius as i32, frame_color);
+            let mask_x = (x_offset + logo.width() / 2) as i64 - radius as i64;
+            let mask_y = (y_offset + logo.height() / 2) as i64 - radius as i64;
+            overlay(img, &mask, mask_x, mask_y);
+        }
+        FrameStyle::Square => {
+            for y in y_offset.saturating_sub(inner_frame)
+                ..(y_offset + logo.height() + inner_frame).min(img.height())
+            {
+                for x in x_offset.saturating_sub(inner_frame)
+                    ..(x_offset + logo.width() + inner_frame).min(img.width())
+                {
+                    img.put_pixel(x, y, frame_color);
+                }
+            }
+        }
+        FrameStyle::None => {}
+    }
+
+    overlay(img, logo, x_offset as i64, y_offset as i64);
+}
+
+// Writes the logo fitted as described by `fitting`, centered in the `size` units square area whose
+// top-left corner is at (`x`, `y`) and preceded by a square or circular frame of `frame_color`, at
+// the same position and size as `draw_logo` draws it. SVG logos are inlined as vector markup, and
+// other logos are embedded as base64-encoded PNG `<image>`s with `dpi` pixels per 96 units.
+#[cfg(feature = "image")]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn write_logo_svg(
+    result: &mut String,
+    logo_path: &str,
+    x: u32,
+    y: u32,
+    size: u32,
+    fitting: crate::logo::LogoFitting,
+    dpi: u32,
+    frame_style: FrameStyle,
+    inner_frame: u32,
+    frame_color: [u8; 3],
+    cache: Option<&crate::logo::LogoCache>,
+) -> Result<(), HelperError> {
+    let path = Path::new(logo_path);
+    let is_svg = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"));
+    if is_svg {
+        let logo = match cache {
+            Some(cache) => cache.svg(path, fitting, || load_svg_logo(path))?,
+            None => Arc::new(load_svg_logo(path)?),
+        };
+        let side = f64::from(fitting.side);
+        let aspect_ratio = logo.aspect_ratio();
+        let (logo_w, logo_h) = match fitting.fit {
+            crate::logo::LogoFit::Cover => (fitting.side, fitting.side),
+            crate::logo::LogoFit::Contain if aspect_ratio >= 1.0 => {
+                (fitting.side, ((side / aspect_ratio).round() as u32).max(1))
+            }
+            crate::logo::LogoFit::Contain => {
+                (((side * aspect_ratio).round() as u32).max(1), fitting.side)
+            }
+        };
+        let (logo_x, logo_y) = logo_origin(logo_w, logo_h, x, y, size);
+        write_logo_frame_svg(
+            result,
+            logo_x,
+            logo_y,
+            logo_w,
+            logo_h,
+            frame_style,
+            inner_frame,
+            frame_color,
+        )?;
+        logo.write(
+            result,
+            logo_x,
+            logo_y,
+            logo_w,
+            logo_h,
+            fitting.fit == crate::logo::LogoFit::Cover,
+        )?;
+        return Ok(());
+    }
+
+    // The fitted logo at one pixel per unit sets the geometry, so it matches the raster output
+    let logo = load_fitted_logo(logo_path, fitting, cache)?;
+    let embedded = crate::logo::LogoFitting {
+        side: (u64::from(fitting.side) * u64::from(dpi) / 96).max(1) as u32,
+        ..fitting
+    };
+    let load = || {
+        let mut logo_buffer = Vec::new();
+        let cursor = &mut std::io::Cursor::new(&mut logo_buffer);
+        if embedded == fitting {
+            logo.write_to(cursor, ImageFormat::Png)?;
+        } else {
+            crate::logo::fit_logo(&open_logo(logo_path)?, embedded)
+                .write_to(cursor, ImageFormat::Png)?;
+        }
+        Ok(encode_base64(&logo_buffer))
+    };
+    let logo_base64: Arc<str> = match cache {
+        Some(cache) => cache.text(path, embedded, crate::logo::LogoKind::SvgImage, load)?,
+        None => load()?.into(),
+    };
+
+    let (logo_w, logo_h) = logo.dimensions();
+    let (logo_x, logo_y) = logo_origin(logo_w, logo_h, x, y, size);
+    write_logo_frame_svg(
+        result,
+        logo_x,
+        logo_y,
+        logo_w,
+        logo_h,
+        frame_style,
+        inner_frame,
+        frame_color,
+    )?;
+    writeln!(
+        result,
+        "<image x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" preserveAspectRatio=\"none\" href=\"data:image/png;base64,{}\"/>",
+        logo_x, logo_y, logo_w, logo_h, logo_base64
+    )?;
+    Ok(())
+}
+
+// Reads and parses the SVG logo at `path`, prefixing its ids with a checksum of its content.
+#[cfg(feature = "image")]
+fn load_svg_logo(path: &Path) -> Result<crate::logo::SvgLogo, HelperError> {
+    if !path.exists() {
+        return Err(HelperError::LogoNotFound(path.to_path_buf()));
+    }
+    let decode_error = |message: String| HelperError::LogoDecode {
+        path: path.to_path_buf(),
+        source: image::ImageError::Decoding(image::error::DecodingError::new(
+            image::error::ImageFormatHint::Name("SVG".to_string()),
+            message,
+        )),
+    };
+    let source = fs::read(path).map_err(|source| HelperError::LogoDecode {
+        path: path.to_path_buf(),
+        source: image::ImageError::IoError(source),
+    })?;
+    let text = std::str::from_utf8(&source)
+        .map_err(|_| decode_error("the logo is not valid UTF-8".to_string()))?;
+    let prefix = format!("qirust-logo-{:08x}-", crate::checksum::crc32(&source));
+    crate::logo::SvgLogo::parse(text, &prefix).map_err(decode_error)
+}
+
+// Writes the square or circular frame of `frame_color` that `draw_logo` draws behind a `width` x
+// `height` logo at (`x`, `y`).
+#[cfg(feature = "image")]
+#[allow(clippy::too_many_arguments)]
+fn write_logo_frame_svg(
+    result: &mut String,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    frame_style: FrameStyle,
+    inner_frame: u32,
+    frame_color: [u8; 3],
+) -> Result<(), HelperError> {
+    let fill = format!(
+        "#{:02X}{:02X}{:02X}",
+        frame_color[0], frame_color[1], frame_color[2]
+    );
+    match frame_style {
+        FrameStyle::Rounded => {
+            writeln!(
+                result,
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>",
+                x + width / 2,
+                y + height / 2,
+                (width.min(height) + 2 * inner_frame) / 2,
+                fill
+            )?;
+        }
+        FrameStyle::Square => {
+            writeln!(
+                result,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                x.saturating_sub(inner_frame),
+                y.saturating_sub(inner_frame),
+                width + 2 * inner_frame,
+                height + 2 * inner_frame,
+                fill
+            )?;
+        }
+        FrameStyle::None => {}
+    }
+    Ok(())
+}
+
+// Glyph positions and banner dimensions of a caption, shared by the PNG and SVG renderers.
+// Glyph positions are relative to the top-left corner of the text box, with the baseline at the
+// font ascent.
+#[cfg(feature = "image")]
+pub(crate) struct CaptionLayout<'a> {
+    font: FontRef<'a>,
+    glyphs: Vec<Glyph>,
+    text_width: u32,
+    banner_width: u32,
+    banner_height: u32,
+}
+
+#[cfg(feature = "image")]
+impl CaptionLayout<'_> {
+    // Returns the banner width, height and position of `caption` for `layout_canvas`.
+    pub(crate) fn banner(&self, caption: &Caption) -> (u32, u32, CaptionPosition) {
+        (self.banner_width, self.banner_height, caption.position)
+    }
+}
+
+// Lays out a caption on a single line, applying kerning between consecutive glyphs.
+#[cfg(feature = "image")]
+pub(crate) fn layout_caption<'a>(caption: &Caption<'a>) -> Result<CaptionLayout<'a>, HelperError> {
+    let font = FontRef::try_from_slice(caption.font_data)
+        .map_err(|_| HelperError::InvalidInput("Caption font could not be parsed".to_string()))?;
+    let scale = PxScale::from(caption.font_size);
+    let scaled = font.as_scaled(scale);
+
+    let mut glyphs = Vec::with_capacity(caption.text.len());
+    let mut caret = 0.0f32;
+    let mut previous = None;
+    for c in caption.text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(prev) = previous {
+            caret += scaled.kern(prev, id);
+        }
+        glyphs.push(id.with_scale_and_position(scale, point(caret, scaled.ascent())));
+        caret += scaled.h_advance(id);
+        previous = Some(id);
+    }
+
+    let text_width = caret.max(0.0).ceil() as u32;
+    let text_height = (scaled.ascent() - scaled.descent()).ceil() as u32;
+    let padding = 2 * u64::from(caption.padding_px);
+    let banner_height = u64::from(text_height) + padding;
+    // Rounded banners need extra room so the text stays clear of the semicircular ends
+    let rounding = match caption.banner_style {
+        FrameStyle::Rounded => banner_height / 2,
+        _ => 0,
+    };
+    let mut banner_width = u64::from(text_width) + padding + rounding;
+    if caption.banner_style == FrameStyle::Rounded {
+        // Short text would leave the ends overlapping, so the pill becomes at least a circle
+        banner_width = banner_width.max(banner_height);
+    }
+    if banner_width > u64::from(MAX_IMAGE_DIMENSION)
+        || banner_height > u64::from(MAX_IMAGE_DIMENSION)
+    {
+        return Err(HelperError::DimensionOverflow {
+            width: banner_width,
+            height: banner_height,
+            max: MAX_IMAGE_DIMENSION,
+        });
+    }
+    let (banner_width, banner_height) = (banner_width as u32, banner_height as u32);
+
+    Ok(CaptionLayout {
+        font,
+        glyphs,
+        text_width,
+        banner_width,
+        banner_height,
+    })
+}
+
+// Placement of the QR code and the caption banner on the final canvas. Without the `image` feature
+// there is never a banner to place.
+#[cfg_attr(not(feature = "image"), allow(dead_code))]
+pub(crate) struct CanvasLayout {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) qr_x: u32,
+    pub(crate) qr_y: u32,
+    pub(crate) banner_x: u32,
+    pub(crate) banner_y: u32,
+}
+
+// Computes the canvas for a QR code of `qr_px` pixels surrounded by the outer frame. A caption banner
+// of the given width and height is placed between the code and the outer frame, growing the canvas
+// to fit.
+pub(crate) fn layout_canvas(
+    qr_px: u32,
+    outer_frame: u32,
+    banner: Option<(u32, u32, CaptionPosition)>,
+) -> Result<CanvasLayout, HelperError> {
+    let (banner_w, banner_h, position) = banner.unwrap_or((0, 0, CaptionPosition::Bottom));
+    let content_w = qr_px.max(banner_w);
+    // Computed in u64 so that oversized frames are reported instead of overflowing
+    let width = content_w as u64 + 2 * outer_frame as u64;
+    let height = qr_px as u64 + banner_h as u64 + 2 * outer_frame as u64;
+    if width > MAX_IMAGE_DIMENSION as u64 || height > MAX_IMAGE_DIMENSION as u64 {
+        return Err(HelperError::DimensionOverflow {
+            width,
+            height,
+            max: MAX_IMAGE_DIMENSION,
+        });
+    }
+    let (width, height) = (width as u32, height as u32);
+
+    let qr_x = outer_frame + (content_w - qr_px) / 2;
+    let banner_x = outer_frame + (content_w - banner_w) / 2;
+    let (qr_y, banner_y) = match position {
+        CaptionPosition::Top => (outer_frame + banner_h, outer_frame),
+        CaptionPosition::Bottom => (outer_frame, outer_frame + qr_px),
+    };
+    Ok(CanvasLayout {
+        width,
+        height,
+        qr_x,
+        qr_y,
+        banner_x,
+        banner_y,
+    })
+}
+
+// Draws the caption banner and anti-aliased text onto a raster canvas at the given banner origin.
+#[cfg(feature = "image")]
+pub(crate) fn draw_caption(
+    img: &mut RgbaImage,
+    caption: &Caption,
+    layout: &CaptionLayout,
+    x: u32,
+    y: u32,
+) {
+    let banner = Rgba([
+        caption.banner_color[0],
+        caption.banner_color[1],
+        caption.banner_color[2],
+        255,
+    ]);
+    let radius = layout.banner_height / 2;
+    for py in y..(y + layout.banner_height).min(img.height()) {
+        for px in x..(x + layout.banner_width).min(img.width()) {
+            let inside = match caption.banner_style {
+                FrameStyle::Square => true,
+                FrameStyle::Rounded => {
+                    // Distance to the nearest point on the pill's center line
+                    let cy = (y + radius) as i64;
+                    let cx = (px as i64).clamp(
+                        (x + radius) as i64,
+                        (x + layout.banner_width - radius) as i64,
+                    );
+                    let dx = px as i64 - cx;
+                    let dy = py as i64 - cy;
+                    dx * dx + dy * dy <= (radius as i64) * (radius as i64)
+                }
+                FrameStyle::None => false,
+            };
+            if inside {
+                img.put_pixel(px, py, banner);
+            }
+        }
+    }
+
+    let text_x = (x + (layout.banner_width - layout.text_width) / 2) as f32;
+    let text_y = (y + caption.padding_px) as f32;
+    for glyph in &layout.glyphs {
+        if let Some(outlined) = layout.font.outline_glyph(glyph.clone()) {
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                let px = (text_x + bounds.min.x) as i64 + gx as i64;
+                let py = (text_y + bounds.min.y) as i64 + gy as i64;
+                if px < 0 || py < 0 || px >= img.width() as i64 || py >= img.height() as i64 {
+                    return;
+                }
+                let alpha = (coverage.clamp(0.0, 1.0) * 255.0).round() as u8;
+                let pixel = img.get_pixel_mut(px as u32, py as u32);
+                for i in 0..3 {
+                    pixel[i] = mix_colors(alpha, caption.text_color[i], pixel[i]);
+                }
+            });
+        }
+    }
+}
+
+// Writes the caption banner and text as SVG elements at the given banner origin. Glyphs are emitted
+// as vector outlines so the output does not depend on fonts installed on the viewer's machine.
+#[cfg(feature = "image")]
+pub(crate) fn write_caption_svg(
+    result: &mut String,
+    caption: &Caption,
+    layout: &CaptionLayout,
+    x: u32,
+    y: u32,
+) -> fmt::Result {
+    let [br, bg, bb] = caption.banner_color;
+    match caption.banner_style {
+        FrameStyle::Square => writeln!(
+            result,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#{:02x}{:02x}{:02x}\"/>",
+            x, y, layout.banner_width, layout.banner_height, br, bg, bb
+        )?,
+        FrameStyle::Rounded => writeln!(
+            result,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" fill=\"#{:02x}{:02x}{:02x}\"/>",
+            x,
+            y,
+            layout.banner_width,
+            layout.banner_height,
+            layout.banner_height / 2,
+            br,
+            bg,
+            bb
+        )?,
+        FrameStyle::None => {}
+    }
+
+    let scaled = layout.font.as_scaled(PxScale::from(caption.font_size));
+    let (sx, sy) = (scaled.h_scale_factor(), scaled.v_scale_factor());
+    let text_x = (x + (layout.banner_width - layout.text_width) / 2) as f32;
+    let text_y = (y + caption.padding_px) as f32;
+
+    let mut path = String::new();
+    for glyph in &layout.glyphs {
+        let Some(outline) = layout.font.outline(glyph.id) else {
+            continue;
+        };
+        let ox = text_x + glyph.position.x;
+        let oy = text_y + glyph.position.y;
+        let map = |p: ab_glyph::Point| (ox + p.x * sx, oy - p.y * sy);
+        let mut current = None;
+        for curve in &outline.curves {
+            let (start, end) = match *curve {
+                OutlineCurve::Line(p0, p1) => (p0, p1),
+                OutlineCurve::Quad(p0, _, p2) => (p0, p2),
+                OutlineCurve::Cubic(p0, _, _, p3) => (p0, p3),
+            };
+            if current != Some(start) {
+                let (mx, my) = map(start);
+                write!(path, "M{:.2} {:.2}", mx, my)?;
+            }
+            match *curve {
+                OutlineCurve::Line(_, p1) => {
+                    let (x1, y1) = map(p1);
+                    write!(path, "L{:.2} {:.2}", x1, y1)?;
+                }
+                OutlineCurve::Quad(_, p1, p2) => {
+                    let ((x1, y1), (x2, y2)) = (map(p1), map(p2));
+                    write!(path, "Q{:.2} {:.2} {:.2} {:.2}", x1, y1, x2, y2)?;
+                }
+                OutlineCurve::Cubic(_, p1, p2, p3) => {
+                    let ((x1, y1), (x2, y2), (x3, y3)) = (map(p1), map(p2), map(p3));
+                    write!(
+                        path,
+                        "C{:.2} {:.2} {:.2} {:.2} {:.2} {:.2}",
+                        x1, y1, x2, y2, x3, y3
+                    )?;
+                }
+            }
+            current = Some(end);
+        }
+    }
+
+    let [tr, tg, tb] = caption.text_color;
+    writeln!(
+        result,
+        "<path d=\"{}\" fill=\"#{:02x}{:02x}{:02x}\"/>",
+        path, tr, tg, tb
+    )
+}
+
 // Tests
-#[cfg(test)]
+#[cfg(all(test, feature = "image"))]
 mod tests {
     use super::*;
+    use crate::test_support::TempDir;
+
+    // Draws every printable ASCII character except space as a solid block
+    const CAPTION_FONT: &[u8] = include_bytes!("../tests/fixtures/blocks.ttf");
 
     #[test]
+    #[allow(deprecated)]
     fn test_frame_qr_save_config_builder() {
         let config = FrameQrSaveConfig::new("logo.png")
             .unwrap()
@@ -1913,6 +3464,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_frame_qr_save_config_validation() {
         // Valid config
         let valid_config = FrameQrSaveConfig::new("logo.png").unwrap();
@@ -1933,6 +3485,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_frame_qr_svg_config_builder() {
         let config = FrameQrSvgConfig::new("logo.png")
             .unwrap()
@@ -1950,4 +3503,485 @@ mod tests {
         assert_eq!(config.inner_frame_px, 10);
         assert_eq!(config.frame_style, FrameStyle::Rounded);
     }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_legacy_configs_convert_to_style() {
+        let style = QrStyle::from(
+            QrConfig::new()
+                .with_border(2)
+                .unwrap()
+                .with_scale(5)
+                .unwrap()
+                .with_fg_color([1, 2, 3]),
+        );
+        assert_eq!(style.quiet_zone, 2);
+        assert_eq!(style.module_size, 5);
+        assert_eq!(style.fg_color, [1, 2, 3]);
+        assert_eq!(style.logo_path.as_deref(), None);
+
+        let config = FrameQrConfig::new("logo.png")
+            .unwrap()
+            .with_ecc(QrCodeEcc::Medium)
+            .with_upscale(6)
+            .unwrap()
+            .with_directory("output")
+            .with_filename("test")
+            .with_outer_frame(40)
+            .with_frame_style(FrameStyle::Square);
+        let output = OutputConfig::from(&config);
+        assert_eq!(output.directory_path, "output");
+        assert_eq!(output.file_name.as_deref(), Some("test"));
+
+        let style = QrStyle::from(config);
+        // 40px at 6px per module rounds up to 7 modules
+        assert_eq!(style.quiet_zone, 7);
+        assert_eq!(style.module_size, 6);
+        assert_eq!(style.ecc, QrCodeEcc::Medium);
+        assert_eq!(style.logo_path.as_deref(), Some("logo.png"));
+        assert_eq!(style.frame_style, FrameStyle::Square);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_generate_image_buffer_accepts_legacy_config() {
+        let legacy = QrConfig::new()
+            .with_border(3)
+            .unwrap()
+            .with_fg_color([255, 0, 0])
+            .with_scale(2)
+            .unwrap();
+        let style = QrStyle::new()
+            .with_quiet_zone(3)
+            .unwrap()
+            .with_fg_color([255, 0, 0])
+            .with_module_size(2)
+            .unwrap();
+
+        let from_legacy = generate_image_buffer("Hello, World!", legacy).unwrap();
+        let from_style = generate_image_buffer("Hello, World!", style).unwrap();
+        assert_eq!(from_legacy, from_style);
+        assert_eq!(from_style.get_pixel(6, 6), &Rgb([255, 0, 0]));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_legacy_frame_functions_keep_pixel_frame() {
+        let temp = TempDir::new("helper-legacy");
+        let logo_path = temp.write_logo("logo.png", 8, 8, [0, 0, 255, 255]);
+        let logo_path = logo_path.to_str().unwrap();
+        let directory = temp.path().to_str().unwrap();
+
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let options = || EncodeTextOptions {
+            ecl: QrCodeEcc::High,
+            minversion: Version::MIN,
+            maxversion: Version::MAX,
+            mask: None,
+            boostecl: true,
+        };
+        let qr = QrCode::encode_text("Hello", &mut tempbuffer, &mut outbuffer, options()).unwrap();
+        let size = qr.size() as u32 * 4 + 2 * 10;
+
+        let config = FrameQrSvgConfig::new(logo_path)
+            .unwrap()
+            .with_upscale(4)
+            .unwrap()
+            .with_outer_frame(10);
+        let svg = frameqr_to_svg_string(qr, config).unwrap();
+        assert!(svg.contains(&format!("viewBox=\"0 0 {0} {0}\"", size)));
+        // Logos smaller than a third of the symbol keep their size, as they always have
+        assert!(svg.contains("width=\"8\" height=\"8\" preserveAspectRatio"));
+
+        let qr = QrCode::encode_text("Hello", &mut tempbuffer, &mut outbuffer, options()).unwrap();
+        let config = FrameQrSaveConfig::new(logo_path)
+            .unwrap()
+            .with_upscale(4)
+            .unwrap()
+            .with_outer_frame(10)
+            .with_directory(directory)
+            .with_filename("legacy");
+        frameqr_to_image_and_save(qr, config).unwrap();
+        let img = image::open(Path::new(directory).join("legacy.png"))
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(img.dimensions(), (size, size));
+        assert_eq!(img.get_pixel(size / 2, size / 2), &Rgba([0, 0, 255, 255]));
+        let blue = |img: &RgbaImage| img.pixels().filter(|p| p.0 == [0, 0, 255, 255]).count();
+        assert_eq!(blue(&img), 8 * 8);
+
+        // Styles enlarge the same logo unless told not to
+        let qr = QrCode::encode_text("Hello", &mut tempbuffer, &mut outbuffer, options()).unwrap();
+        let style = QrStyle::new().with_logo(logo_path).unwrap();
+        assert!(blue(&RasterRenderer.render(&qr, &style).unwrap()) > 8 * 8);
+        let style = style.with_logo_upscale(false);
+        assert_eq!(blue(&RasterRenderer.render(&qr, &style).unwrap()), 8 * 8);
+
+        // Both legacy configurations pass their format on to the saved file
+        let qr = QrCode::encode_text("Hello", &mut tempbuffer, &mut outbuffer, options()).unwrap();
+        let config = FrameQrSaveConfig::new(logo_path)
+            .unwrap()
+            .with_upscale(4)
+            .unwrap()
+            .with_outer_frame(10)
+            .with_directory(directory)
+            .with_filename("legacy")
+            .with_format(ImageOutputFormat::Bmp);
+        frameqr_to_image_and_save(qr, config).unwrap();
+        let bmp = image::open(Path::new(directory).join("legacy.bmp")).unwrap();
+        assert_eq!(bmp.to_rgba8(), img);
+        let config = FrameQrConfig::new(logo_path)
+            .unwrap()
+            .with_directory(directory)
+            .with_filename("generated")
+            .with_format(ImageOutputFormat::Tiff);
+        generate_frameqr("Hello", config).unwrap();
+        assert!(Path::new(directory).join("generated.tiff").exists());
+        let invalid_jpeg = ImageOutputFormat::Jpeg { quality: 0 };
+        assert!(FrameQrConfig::new(logo_path)
+            .unwrap()
+            .with_format(invalid_jpeg)
+            .validate()
+            .is_err());
+
+        // The buffer shim sizes the logo from the whole image, border included, and does not
+        // enlarge it past its own size
+        let qr = QrCode::encode_text("Hello", &mut tempbuffer, &mut outbuffer, options()).unwrap();
+        let modules = qr.size() as u32;
+        let img =
+            generate_frameqr_buffer(qr, logo_path, Some(4), None, Some(4), None, None).unwrap();
+        assert_eq!(img.width(), (modules + 8) * 4);
+        assert_eq!(blue(&img), 8 * 8);
+        let large_logo = temp.write_logo("large.png", 300, 300, [0, 0, 255, 255]);
+        let qr = QrCode::encode_text("Hello", &mut tempbuffer, &mut outbuffer, options()).unwrap();
+        let img = generate_frameqr_buffer(
+            qr,
+            large_logo.to_str().unwrap(),
+            Some(4),
+            None,
+            Some(4),
+            None,
+            None,
+        )
+        .unwrap();
+        let side = (img.width() / 3) as usize;
+        assert_eq!(blue(&img), side * side);
+    }
+
+    #[test]
+    fn test_errors_name_failing_logo_path() {
+        let directory = TempDir::new("helper-errors");
+        let missing = directory.path().join("missing.png");
+        let corrupt = directory.path().join("corrupt.png");
+        fs::write(&corrupt, b"not an image").unwrap();
+
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let qr = QrCode::encode_text(
+            "Hello",
+            &mut tempbuffer,
+            &mut outbuffer,
+            EncodeTextOptions {
+                ecl: QrCodeEcc::High,
+                minversion: Version::MIN,
+                maxversion: Version::MAX,
+                mask: None,
+                boostecl: true,
+            },
+        )
+        .unwrap();
+
+        let style = QrStyle::new().with_logo(missing.to_str().unwrap()).unwrap();
+        match RasterRenderer.render(&qr, &style) {
+            Err(HelperError::LogoNotFound(path)) => assert_eq!(path, missing),
+            other => panic!("expected LogoNotFound, got {:?}", other),
+        }
+
+        let style = QrStyle::new().with_logo(corrupt.to_str().unwrap()).unwrap();
+        let err = crate::render::SvgRenderer.render(&qr, &style).unwrap_err();
+        assert!(matches!(&err, HelperError::LogoDecode { path, .. } if *path == corrupt));
+        assert!(err.to_string().contains("corrupt.png"));
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn test_oversized_canvas_reports_dimension_overflow() {
+        match layout_canvas(100, u32::MAX, None) {
+            Err(HelperError::DimensionOverflow { width, max, .. }) => {
+                assert_eq!(width, 100 + 2 * u32::MAX as u64);
+                assert_eq!(max, MAX_IMAGE_DIMENSION);
+            }
+            _ => panic!("expected DimensionOverflow"),
+        }
+    }
+
+    #[test]
+    fn test_generate_styled_writes_png() {
+        let temp = TempDir::new("helper-styled");
+        let directory = temp.path().to_str().unwrap();
+        let output = OutputConfig::new()
+            .with_directory(directory)
+            .with_filename("styled");
+        let style = QrStyle::new().with_module_size(2).unwrap();
+
+        let path = generate_styled("Hello, World!", &style, &output).unwrap();
+        assert_eq!(path, Path::new(directory).join("styled.png"));
+        let img = image::open(&path).unwrap();
+        // "Hello, World!" needs version 2 (25 modules) at the default high error correction, plus
+        // 4 modules of quiet zone on each side, at 2 pixels per module
+        assert_eq!(style.quiet_zone, 4);
+        assert_eq!((img.width(), img.height()), (66, 66));
+
+        let output = output.with_format(ImageOutputFormat::Tiff);
+        let path = generate_styled("Hello, World!", &style, &output).unwrap();
+        assert_eq!(path, Path::new(directory).join("styled.tiff"));
+        assert_eq!(image::open(&path).unwrap().width(), img.width());
+
+        assert!(generate_styled("", &style, &output).is_err());
+        assert!(OutputConfig::new().with_filename("").validate().is_err());
+    }
+
+    #[test]
+    fn test_plain_qr_formats() {
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let qr = QrCode::encode_text(
+            "Hello, World!",
+            &mut tempbuffer,
+            &mut outbuffer,
+            EncodeTextOptions {
+                ecl: QrCodeEcc::Low,
+                minversion: Version::MIN,
+                maxversion: Version::MAX,
+                mask: None,
+                boostecl: true,
+            },
+        )
+        .unwrap();
+        let size = qr.size() as u32 + 8;
+
+        let temp = TempDir::new("helper-plain");
+        let directory = temp.path().to_str().unwrap();
+        qr_to_image_and_save(&qr, Some(directory), Some("plain")).unwrap();
+        let png = image::open(temp.path().join("plain.png"))
+            .unwrap()
+            .to_luma8();
+        assert_eq!(png.dimensions(), (size, size));
+
+        let output = OutputConfig::new()
+            .with_directory(directory)
+            .with_filename("plain")
+            .with_format(ImageOutputFormat::WebP);
+        let path = qr_to_image_and_save_with(&qr, &output).unwrap();
+        assert_eq!(path, temp.path().join("plain.webp"));
+        assert_eq!(image::open(&path).unwrap().to_luma8(), png);
+
+        let mut gif = Vec::new();
+        qr_to_writer(&qr, ImageOutputFormat::Gif, &mut gif).unwrap();
+        let decoded = image::load_from_memory_with_format(&gif, ImageFormat::Gif).unwrap();
+        assert_eq!(decoded.to_luma8(), png);
+    }
+
+    #[test]
+    fn test_output_formats_round_trip() {
+        let style = QrStyle::new().with_fg_color([0, 0, 128]);
+        let img = generate_image_buffer("Hello, World!", style).unwrap();
+        let dynamic = DynamicImage::ImageRgb8(img.clone());
+        let formats = [
+            ImageOutputFormat::Png,
+            ImageOutputFormat::jpeg(95).unwrap(),
+            ImageOutputFormat::WebP,
+            ImageOutputFormat::Bmp,
+            ImageOutputFormat::Gif,
+            ImageOutputFormat::Tiff,
+        ];
+        for format in formats {
+            let mut bytes = Vec::new();
+            write_image(&dynamic, format, &mut bytes).unwrap();
+            assert_eq!(image::guess_format(&bytes).unwrap(), format.image_format());
+            assert_eq!(
+                ImageOutputFormat::from_extension(format.extension()).map(|f| f.image_format()),
+                Some(format.image_format())
+            );
+
+            let decoded = image::load_from_memory(&bytes).unwrap().to_rgb8();
+            assert_eq!(decoded.dimensions(), img.dimensions());
+            // Lossy JPEG and the quantized GIF palette only have to stay close to the original
+            // colors; every other format is lossless
+            let tolerance = match format {
+                ImageOutputFormat::Jpeg { .. } => 40,
+                ImageOutputFormat::Gif => 8,
+                _ => 0,
+            };
+            for (decoded, original) in decoded.pixels().zip(img.pixels()) {
+                for channel in 0..3 {
+                    assert!(
+                        decoded[channel].abs_diff(original[channel]) <= tolerance,
+                        "{:?}",
+                        format
+                    );
+                }
+            }
+        }
+
+        assert_eq!(ImageOutputFormat::WebP.media_type(), "image/webp");
+        assert_eq!(ImageOutputFormat::jpeg(80).unwrap().extension(), "jpg");
+        assert_eq!(ImageOutputFormat::from_extension("svg"), None);
+        assert!(ImageOutputFormat::jpeg(0).is_err());
+        let invalid = ImageOutputFormat::Jpeg { quality: 101 };
+        assert!(write_image(&dynamic, invalid, Vec::new()).is_err());
+        assert!(OutputConfig::new().with_format(invalid).validate().is_err());
+    }
+
+    #[test]
+    fn test_bitmaps_match_modules() {
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let qr = QrCode::encode_text(
+            "Hello, World!",
+            &mut tempbuffer,
+            &mut outbuffer,
+            EncodeTextOptions {
+                ecl: QrCodeEcc::Low,
+                minversion: Version::MIN,
+                maxversion: Version::MAX,
+                mask: None,
+                boostecl: false,
+            },
+        )
+        .unwrap();
+        let side = (qr.size() as u32 + 4) * 3;
+
+        for format in [
+            BitmapFormat::PbmAscii,
+            BitmapFormat::Pbm,
+            BitmapFormat::Pgm,
+            BitmapFormat::Bmp,
+        ] {
+            let bytes = to_bitmap(&qr, 2, 3, format).unwrap();
+            let img = image::load_from_memory(&bytes).unwrap().to_luma8();
+            assert_eq!(img.dimensions(), (side, side));
+            for (x, y, pixel) in img.enumerate_pixels() {
+                let dark = qr.get_module(x as i32 / 3 - 2, y as i32 / 3 - 2);
+                assert_eq!(pixel[0], if dark { 0 } else { 255 });
+            }
+        }
+
+        let xbm = String::from_utf8(to_bitmap(&qr, 2, 3, BitmapFormat::Xbm).unwrap()).unwrap();
+        assert!(xbm.starts_with("#define qr_width 75\n#define qr_height 75\n"));
+        assert!(xbm.ends_with(", 0x00\n};\n"));
+        let values: Vec<&str> = xbm
+            .split(['{', ',', '}'])
+            .map(str::trim)
+            .filter(|value| value.starts_with("0x"))
+            .collect();
+        assert_eq!(values.len(), 10 * 75);
+        assert!(xbm.lines().all(|line| line.len() <= 3 + 12 * 6));
+        // The top row of the finder pattern starts at pixel 6, in the two highest bits of the first
+        // byte of pixel row 6
+        assert_eq!(values[6 * 10], "0xc0");
+
+        assert!(to_bitmap(&qr, 2, 0, BitmapFormat::Pbm).is_err());
+        assert!(to_bitmap(&qr, MAX_BORDER_SIZE as i32 + 1, 1, BitmapFormat::Pbm).is_err());
+        assert!(to_bitmap(&qr, -1, 1, BitmapFormat::Pbm).is_err());
+    }
+
+    #[test]
+    fn test_caption_validation() {
+        assert!(Caption::new("Scan me", b"not a font").is_err());
+        assert!(Caption::new("", b"not a font").is_err());
+
+        let caption = Caption::new("Scan me", CAPTION_FONT).unwrap();
+        assert!(caption.clone().with_font_size(0.0).is_err());
+        assert!(caption.clone().with_font_size(f32::NAN).is_err());
+        assert!(Caption::new("Scan\nme", CAPTION_FONT).is_err());
+        assert!(caption
+            .clone()
+            .with_padding(MAX_CAPTION_PADDING + 1)
+            .validate()
+            .is_err());
+
+        // Padding set directly on the field is caught when laying out instead of overflowing
+        let mut huge = caption.with_banner_style(FrameStyle::Rounded);
+        huge.padding_px = u32::MAX;
+        assert!(matches!(
+            layout_caption(&huge),
+            Err(HelperError::DimensionOverflow { .. })
+        ));
+    }
+
+    #[test]
+    fn test_caption_grows_canvas() {
+        let caption = Caption::new("Scan me", CAPTION_FONT)
+            .unwrap()
+            .with_position(CaptionPosition::Top)
+            .with_banner_style(FrameStyle::Rounded)
+            .with_banner_color([255, 165, 0]);
+        let layout = layout_caption(&caption).unwrap();
+        let canvas = layout_canvas(200, 10, Some(layout.banner(&caption))).unwrap();
+
+        assert_eq!(canvas.width, 220);
+        assert_eq!(canvas.height, 220 + layout.banner_height);
+        assert_eq!(canvas.banner_y, 10);
+        assert_eq!(canvas.qr_y, 10 + layout.banner_height);
+
+        let mut img = RgbaImage::from_pixel(canvas.width, canvas.height, Rgba([255; 4]));
+        draw_caption(
+            &mut img,
+            &caption,
+            &layout,
+            canvas.banner_x,
+            canvas.banner_y,
+        );
+        let center = img.get_pixel(canvas.width / 2, canvas.banner_y + 1);
+        assert_eq!(center, &Rgba([255, 165, 0, 255]));
+        assert!(img.pixels().any(|p| p[0] < 128 && p[1] < 128));
+
+        let mut svg = String::new();
+        write_caption_svg(
+            &mut svg,
+            &caption,
+            &layout,
+            canvas.banner_x,
+            canvas.banner_y,
+        )
+        .unwrap();
+        assert!(svg.contains("rx=\""));
+        assert!(svg.contains("fill=\"#000000\""));
+    }
+
+    #[test]
+    fn test_rounded_caption_narrower_than_tall() {
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let qr = QrCode::encode_text(
+            "Hello",
+            &mut tempbuffer,
+            &mut outbuffer,
+            EncodeTextOptions {
+                ecl: QrCodeEcc::Low,
+                minversion: Version::MIN,
+                maxversion: Version::MAX,
+                mask: None,
+                boostecl: true,
+            },
+        )
+        .unwrap();
+
+        // "." and "i" are a fifth of an em wide, well below the height of the text
+        for (text, padding) in [(".", 0), (".", 2), ("i", 0), ("i", 8)] {
+            let caption = Caption::new(text, CAPTION_FONT)
+                .unwrap()
+                .with_banner_style(FrameStyle::Rounded)
+                .with_padding(padding);
+            let layout = layout_caption(&caption).unwrap();
+            assert!(layout.banner_width >= layout.banner_height);
+
+            let style = QrStyle::new().with_caption(caption);
+            let img = RasterRenderer.render(&qr, &style).unwrap();
+            assert!(img.height() > img.width());
+            crate::render::SvgRenderer.render(&qr, &style).unwrap();
+        }
+    }
 }
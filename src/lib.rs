@@ -17,8 +17,8 @@
 //!   robustness.
 //! - **Output Formats**: Render QR codes as console ASCII art, PNG images, SVGs, or in-memory image
 //!   buffers.
-//! - **Styling Options**: Embed logos, customize colors, and apply square or rounded frames behind
-//!   logos.
+//! - **Styling Options**: Embed logos, customize colors, apply square or rounded frames behind
+//!   logos, and add caption banners such as "Scan me" above or below the code.
 //! - **Performance**: Optimized with horizontal module grouping, logo caching, and minimal memory
 //!   allocations.
 //! - **Safety**: Pure Rust implementation with no unsafe code, ensuring memory safety and reliability.
@@ -33,42 +33,70 @@
 //! image = "0.25"
 //! ```
 //!
+//! ### Optional features
+//!
+//! - `image` (enabled by default): Raster output through the `image` crate and everything built on
+//!   it: the raster renderers of [`render`], logos and captions in [`render::QrStyle`], [`logo`],
+//!   [`print`](mod@print), [`batch`], and the styled, PNG and image buffer functions of [`helper`].
+//!   Captions also need the `ab_glyph` font rasterizer it pulls in. Without it, codes can still be
+//!   rendered through the [`render::Renderer`] trait with [`render::SvgRenderer`] and
+//!   [`render::TerminalRenderer`], wrapped in [`data_uri::DataUri`], or written as PBM, PGM, XBM and
+//!   BMP bitmaps ([`helper::write_bitmap`]) using only the standard library:
+//!
+//!   ```toml
+//!   [dependencies]
+//!   qirust = { version = "0.1", default-features = false }
+//!   ```
+//! - `serde`: Implements `Serialize` and `Deserialize` for [`render::QrStyle`],
+//!   [`helper::OutputConfig`], the deprecated configuration structs, [`helper::FrameStyle`],
+//!   [`qrcode::QrCodeEcc`], [`qrcode::Version`] and [`qrcode::Mask`], so style presets can be stored
+//!   as JSON or YAML. Colors accept hex strings such as `"#ffa500"` or `[r, g, b]` arrays, and
+//!   deserialized configurations are checked with their `validate()` methods. Without `image`,
+//!   only [`render::QrStyle`], [`qrcode::QrCodeEcc`], [`qrcode::Version`] and [`qrcode::Mask`] are
+//!   covered.
+//!
 //! ## Examples
 //!
 //! Generate a styled QR code with a logo and rounded frame:
 //!
 //! ```rust,no_run
-//! use qirust::helper::{generate_frameqr, FrameQrConfig, FrameStyle};
+//! # #[cfg(feature = "image")] {
+//! use qirust::helper::{generate_styled, FrameStyle, OutputConfig};
 //! use qirust::qrcode::QrCodeEcc;
+//! use qirust::render::QrStyle;
 //!
-//! let config = FrameQrConfig::new("logo.png").unwrap()
+//! let style = QrStyle::new()
 //!     .with_ecc(QrCodeEcc::High)
-//!     .with_upscale(6).unwrap()
-//!     .with_directory("output")
-//!     .with_filename("styled_qr")
-//!     .with_color([255, 165, 0])
-//!     .with_outer_frame(40)
+//!     .with_module_size(6).unwrap()
+//!     .with_quiet_zone(7).unwrap()
+//!     .with_fg_color([255, 165, 0])
+//!     .with_logo("logo.png").unwrap()
 //!     .with_inner_frame(10)
 //!     .with_frame_style(FrameStyle::Rounded);
+//! let output = OutputConfig::new().with_directory("output").with_filename("styled_qr");
 //!
-//! generate_frameqr("https://example.com", config)
+//! generate_styled("https://example.com", &style, &output)
 //!     .expect("Failed to generate QR code");
+//! # }
 //! ```
 //!
 //! Generate an in-memory image buffer for a basic QR code:
 //!
 //! ```rust
-//! use qirust::helper::{generate_image_buffer, QrConfig};
+//! # #[cfg(feature = "image")] {
+//! use qirust::helper::generate_image_buffer;
+//! use qirust::render::QrStyle;
 //!
-//! let config = QrConfig::new()
-//!     .with_border(4).unwrap()
+//! let style = QrStyle::new()
+//!     .with_quiet_zone(4).unwrap()
 //!     .with_fg_color([255, 0, 0])
 //!     .with_bg_color([255, 255, 255])
-//!     .with_scale(6).unwrap();
+//!     .with_module_size(6).unwrap();
 //!
-//! let img = generate_image_buffer("Hello, World!", config)
+//! let img = generate_image_buffer("Hello, World!", style)
 //!     .expect("Failed to generate image buffer");
 //! # img.save("target/qr_lib.png").ok();
+//! # }
 //! ```
 //!
 //! Encode text and print to console:
@@ -101,37 +129,83 @@
 //!
 //! - [`qrcode`]: Core functionality for encoding QR codes, including data segmentation and error
 //!   correction.
+//! - [`batch`]: [`batch::Batch`], which renders CSV or JSON-lines records in parallel into a
+//!   directory or ZIP archive and reports the records that failed.
+//! - [`capacity`]: [`capacity::CapacityPlan`], which reports the version each error correction
+//!   level needs for some data, the room left over and the resulting symbol size, without encoding.
+//! - [`data_uri`]: [`data_uri::DataUri`], which wraps any renderer and returns its output as a
+//!   `data:` URI for embedding codes inline in HTML, CSS or JSON.
+//! - [`encoder`]: [`encoder::QrEncoder`], which reuses its working buffers across calls and returns
+//!   owned [`encoder::OwnedQrCode`]s, for high-throughput encoding.
+//! - [`logo`]: [`logo::LogoFit`] and [`logo::LogoFilter`], which control how a logo is scaled into
+//!   the code, the inlining of SVG logos in SVG output, and [`logo::LogoCache`], a bounded
+//!   least-recently-used cache of decoded and resized logos that styles share through
+//!   [`render::QrStyle::with_logo_cache`].
 //! - [`helper`]: Utilities for rendering QR codes in various formats with styling options.
+//! - [`payload`]: Builders for structured payloads such as Wi-Fi credentials, contact cards,
+//!   payments, calendar events and links, implementing the [`payload::QrPayload`] trait, and a
+//!   [`payload::parse`] function that classifies scanned text.
+//! - [`print`](mod@print): [`print::PrintSize`] and [`print::PrintRenderer`], which render codes
+//!   at a physical size in millimetres or inches for a printing resolution, and write PNGs that
+//!   record it.
+//! - [`render`]: The [`render::Renderer`] trait and [`render::QrStyle`], the single style model
+//!   shared by all output formats.
 //!
 //! ## Error Handling
 //!
 //! - [`qrcode::DataTooLong`]: Returned when input data exceeds the QR code's capacity. Handle by
 //!   reducing data size, increasing version, or lowering error correction.
-//! - [`helper::HelperError`]: Wraps various errors including image processing, I/O, and validation.
-//! - [`image::ImageError`]: Occurs during image processing or file I/O (e.g., invalid logo path or
-//!   permissions issues).
+//! - [`payload::PayloadError`]: Returned by payload builders for missing or invalid fields, or when
+//!   the encoded payload does not fit in a QR code.
+//! - [`helper::HelperError`]: Returned by every rendering helper. Wraps image processing, I/O and
+//!   validation errors, and reports missing or undecodable logos (with the failing path), images
+//!   exceeding the maximum dimension, and poisoned logo caches as dedicated variants.
 //!
 //! ## Limitations
 //!
 //! - **Kanji Mode**: Defined but not fully implemented in the [`qrcode`] module.
 //! - **ECI Mode**: Supported but requires careful handling for non-standard character sets.
-//! - **Logo Size**: Automatically resized to one-third of QR code dimensions for scannability (up to
-//!   40% for SVG outputs).
+//! - **Logo Size**: Fitted into one-third of QR code dimensions for scannability, keeping its aspect
+//!   ratio ([`logo::LogoFit`]).
 //!
 //! ## Performance
 //!
 //! The library is optimized for efficiency:
 //! - **Horizontal Module Grouping**: Reduces rendering complexity in SVG and image outputs, improving
 //!   performance for high-version QR codes (e.g., Version 40).
-//! - **Logo Caching**: Uses global `Mutex`-based caches for resized logos and base64-encoded images,
-//!   minimizing redundant processing.
+//! - **Logo Caching**: An optional [`logo::LogoCache`] keeps resized logos and base64-encoded images
+//!   within a byte budget, evicting the least recently used ones and reloading edited files.
 //! - **Memory Efficiency**: Precomputes buffer sizes and uses minimal allocations for encoding and
 //!   rendering.
 
+#[cfg(feature = "image")]
+pub mod batch;
+pub mod capacity;
+#[cfg(feature = "image")]
+mod checksum;
+pub mod data_uri;
+pub mod encoder;
 pub mod helper;
+#[cfg(feature = "image")]
+pub mod logo;
+pub mod payload;
+#[cfg(feature = "image")]
+pub mod print;
 pub mod qrcode;
+pub mod render;
+#[cfg(feature = "serde")]
+mod serde_support;
+#[cfg(all(test, feature = "image"))]
+mod test_support;
 
-pub use helper::{
-    FrameQrConfig, FrameQrSaveConfig, FrameQrSvgConfig, FrameStyle, HelperError, QrConfig,
-};
+pub use encoder::{OwnedQrCode, QrEncoder};
+pub use helper::HelperError;
+#[cfg(feature = "image")]
+pub use helper::{Caption, CaptionPosition, FrameStyle, ImageOutputFormat, OutputConfig};
+#[cfg(feature = "image")]
+#[allow(deprecated)]
+pub use helper::{FrameQrConfig, FrameQrSaveConfig, FrameQrSvgConfig, QrConfig};
+#[cfg(feature = "image")]
+pub use logo::{LogoCache, LogoFilter, LogoFit};
 pub use qrcode::{DataTooLong, EncodeTextOptions, QrCode, QrCodeEcc, Version};
+pub use render::{QrStyle, RenderFormat, Renderer};
@@ -0,0 +1,538 @@
+//! Fitting and caching of logos.
+//!
+//! A logo is fitted into a square area at the center of the code, one third of the symbol wide, as
+//! configured by [LogoFit], [LogoFilter] and [crate::render::QrStyle::with_logo_padding]. The raster
+//! and SVG renderers place the same fitted image at the same position, so a PNG and an SVG of one
+//! style look the same. In SVG output, logos with an `.svg` extension are inlined as vector markup,
+//! and raster logos are embedded at [crate::render::QrStyle::logo_dpi].
+//!
+//! Loading a logo means reading the file, decoding it and resizing it to the size of the code, which
+//! costs far more than rendering the modules. A [LogoCache] keeps the prepared logos of recent
+//! renders, and is attached to a style with [crate::render::QrStyle::with_logo_cache]. Styles
+//! without a cache load the logo on every render.
+//!
+//! # Example
+//!
+//! ```rust
+//! use qirust::logo::LogoCache;
+//! use qirust::render::QrStyle;
+//!
+//! // Keep up to 8 MiB of prepared logos, shared by every style that renders one
+//! let cache = LogoCache::new(8 * 1024 * 1024);
+//! let style = QrStyle::new()
+//!     .with_logo("assets/customer-a.png").unwrap()
+//!     .with_logo_cache(&cache);
+//! # assert!(cache.is_empty());
+//! ```
+mod svg;
+
+use crate::helper::HelperError;
+use image::{
+    imageops::{self, FilterType},
+    RgbaImage,
+};
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+pub(crate) use svg::SvgLogo;
+
+/// How a logo is scaled into the logo area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum LogoFit {
+    /// Scales the logo to fit entirely inside the area, keeping its aspect ratio, so a wide
+    /// wordmark spans the full width and is centered vertically.
+    #[default]
+    Contain,
+    /// Scales the logo to cover the whole area, keeping its aspect ratio, and crops the parts that
+    /// extend beyond it equally on both sides.
+    Cover,
+}
+
+/// The resampling filter used to scale a logo.
+///
+/// Listed from fastest to sharpest; [LogoFilter::Nearest] keeps hard pixel edges and suits pixel
+/// art, while [LogoFilter::CatmullRom] and [LogoFilter::Lanczos3] suit photos and wordmarks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum LogoFilter {
+    Nearest,
+    Triangle,
+    CatmullRom,
+    Gaussian,
+    #[default]
+    Lanczos3,
+}
+
+impl From<LogoFilter> for FilterType {
+    fn from(filter: LogoFilter) -> Self {
+        match filter {
+            LogoFilter::Nearest => FilterType::Nearest,
+            LogoFilter::Triangle => FilterType::Triangle,
+            LogoFilter::CatmullRom => FilterType::CatmullRom,
+            LogoFilter::Gaussian => FilterType::Gaussian,
+            LogoFilter::Lanczos3 => FilterType::Lanczos3,
+        }
+    }
+}
+
+// How a logo is prepared: fitted into a square area of `side` pixels with `fit` and `filter`, and
+// enlarged to it only when `upscale` is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct LogoFitting {
+    pub side: u32,
+    pub fit: LogoFit,
+    pub filter: LogoFilter,
+    pub upscale: bool,
+}
+
+// Scales `logo` into the square area of `fitting`. The result is as wide or as high as the area, or
+// both for [LogoFit::Cover] and square logos, unless the logo is smaller and may not be enlarged.
+pub(crate) fn fit_logo(logo: &RgbaImage, fitting: LogoFitting) -> RgbaImage {
+    let LogoFitting {
+        side,
+        fit,
+        filter,
+        upscale,
+    } = fitting;
+    let (width, height) = logo.dimensions();
+    let reference = match fit {
+        LogoFit::Contain => width.max(height),
+        LogoFit::Cover => width.min(height),
+    };
+    let side = if upscale { side } else { side.min(reference) };
+    let scaled = |length: u32| {
+        ((u64::from(length) * u64::from(side) + u64::from(reference) / 2) / u64::from(reference))
+            .max(1) as u32
+    };
+    let (scaled_width, scaled_height) = (scaled(width), scaled(height));
+    let resized = if (scaled_width, scaled_height) == (width, height) {
+        logo.clone()
+    } else {
+        imageops::resize(logo, scaled_width, scaled_height, filter.into())
+    };
+    match fit {
+        LogoFit::Contain => resized,
+        LogoFit::Cover => {
+            let (crop_width, crop_height) = (side.min(scaled_width), side.min(scaled_height));
+            imageops::crop_imm(
+                &resized,
+                (scaled_width - crop_width) / 2,
+                (scaled_height - crop_height) / 2,
+                crop_width,
+                crop_height,
+            )
+            .to_image()
+        }
+    }
+}
+
+/// A bounded least-recently-used cache of prepared logos.
+///
+/// Entries are keyed by the logo path, the modification time and length of the file, the target
+/// size in pixels and the [LogoFit] and [LogoFilter], so an edited logo file is loaded again, and
+/// the same logo rendered at several sizes is cached once per size. When the entries exceed the
+/// byte budget given to [LogoCache::new], the least recently used ones are evicted; a logo larger
+/// than the whole budget is returned without being cached. [LogoCache::disabled] creates a cache
+/// that never stores anything.
+///
+/// The cache is `Sync`, so a single cache can be shared by worker threads. Logos are loaded outside
+/// the lock, so threads only wait for each other while looking up or storing an entry.
+pub struct LogoCache {
+    max_bytes: usize,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<LogoKey, CacheEntry>,
+    bytes: usize,
+    // Incremented on every access and stored in the entry, so the smallest value is the least
+    // recently used entry
+    clock: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct LogoKey {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    len: u64,
+    fitting: LogoFitting,
+    kind: LogoKind,
+}
+
+// The form a logo is prepared in, as an image for raster output, as base64-encoded PNG data for
+// SVG output, or as SVG markup for SVG logos in SVG output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum LogoKind {
+    Raster,
+    SvgImage,
+    SvgInline,
+}
+
+struct CacheEntry {
+    logo: CachedLogo,
+    last_used: u64,
+}
+
+/// A prepared logo, shared between the cache and its users.
+#[derive(Debug, Clone)]
+pub(crate) enum CachedLogo {
+    Image(Arc<RgbaImage>),
+    Text(Arc<str>),
+    Svg(Arc<SvgLogo>),
+}
+
+impl CachedLogo {
+    fn bytes(&self) -> usize {
+        match self {
+            CachedLogo::Image(image) => image.as_raw().len(),
+            CachedLogo::Text(text) => text.len(),
+            CachedLogo::Svg(svg) => svg.bytes(),
+        }
+    }
+}
+
+impl fmt::Debug for LogoCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut debug = f.debug_struct("LogoCache");
+        debug.field("max_bytes", &self.max_bytes);
+        if let Ok(state) = self.state.lock() {
+            debug
+                .field("entries", &state.entries.len())
+                .field("bytes", &state.bytes);
+        }
+        debug.finish()
+    }
+}
+
+impl Default for LogoCache {
+    /// Creates a cache with a budget of 16 MiB.
+    fn default() -> Self {
+        Self::new(16 * 1024 * 1024)
+    }
+}
+
+impl LogoCache {
+    /// Creates a cache that keeps at most `max_bytes` bytes of prepared logos.
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// Creates a cache that stores nothing, so every render loads the logo again.
+    pub fn disabled() -> Self {
+        Self::new(0)
+    }
+
+    /// Returns the byte budget of the cache.
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Returns the number of cached logos.
+    pub fn len(&self) -> usize {
+        self.state.lock().map_or(0, |state| state.entries.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of bytes held by the cached logos.
+    pub fn bytes(&self) -> usize {
+        self.state.lock().map_or(0, |state| state.bytes)
+    }
+
+    /// Removes every cached logo.
+    pub fn clear(&self) -> Result<(), HelperError> {
+        let mut state = self.lock()?;
+        state.entries.clear();
+        state.bytes = 0;
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, CacheState>, HelperError> {
+        self.state
+            .lock()
+            .map_err(|_| HelperError::PoisonedCache("logo"))
+    }
+
+    // Returns the logo at `path` prepared as `kind` with `fitting`, calling `load` to prepare it when
+    // it is not cached.
+    fn get_or_load(
+        &self,
+        path: &Path,
+        fitting: LogoFitting,
+        kind: LogoKind,
+        load: impl FnOnce() -> Result<CachedLogo, HelperError>,
+    ) -> Result<CachedLogo, HelperError> {
+        let metadata =
+            fs::metadata(path).map_err(|_| HelperError::LogoNotFound(path.to_path_buf()))?;
+        let key = LogoKey {
+            path: path.to_path_buf(),
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+            fitting,
+            kind,
+        };
+
+        {
+            let mut state = self.lock()?;
+            state.clock += 1;
+            let clock = state.clock;
+            if let Some(entry) = state.entries.get_mut(&key) {
+                entry.last_used = clock;
+                return Ok(entry.logo.clone());
+            }
+        }
+
+        let logo = load()?;
+        let bytes = logo.bytes();
+        if bytes > self.max_bytes {
+            return Ok(logo);
+        }
+        let mut state = self.lock()?;
+        state.clock += 1;
+        let last_used = state.clock;
+        // Another thread may have loaded the same logo in the meantime
+        let entry = CacheEntry {
+            logo: logo.clone(),
+            last_used,
+        };
+        if let Some(previous) = state.entries.insert(key, entry) {
+            state.bytes -= previous.logo.bytes();
+        }
+        state.bytes += bytes;
+        while state.bytes > self.max_bytes {
+            let Some(oldest) = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            if let Some(evicted) = state.entries.remove(&oldest) {
+                state.bytes -= evicted.logo.bytes();
+            }
+        }
+        Ok(logo)
+    }
+
+    // Returns the cached raster logo at `path` fitted with `fitting`, or the one returned by `load`.
+    pub(crate) fn image(
+        &self,
+        path: &Path,
+        fitting: LogoFitting,
+        kind: LogoKind,
+        load: impl FnOnce() -> Result<RgbaImage, HelperError>,
+    ) -> Result<Arc<RgbaImage>, HelperError> {
+        match self.get_or_load(path, fitting, kind, || {
+            load().map(|image| CachedLogo::Image(Arc::new(image)))
+        })? {
+            CachedLogo::Image(image) => Ok(image),
+            _ => unreachable!("raster logos are cached as images"),
+        }
+    }
+
+    // Returns the cached text form of the logo at `path`, such as base64-encoded PNG data for SVG
+    // output, or the one returned by `load`.
+    pub(crate) fn text(
+        &self,
+        path: &Path,
+        fitting: LogoFitting,
+        kind: LogoKind,
+        load: impl FnOnce() -> Result<String, HelperError>,
+    ) -> Result<Arc<str>, HelperError> {
+        match self.get_or_load(path, fitting, kind, || {
+            load().map(|text| CachedLogo::Text(text.into()))
+        })? {
+            CachedLogo::Text(text) => Ok(text),
+            _ => unreachable!("embedded images are cached as text"),
+        }
+    }
+
+    // Returns the cached SVG logo at `path`, or the one returned by `load`.
+    pub(crate) fn svg(
+        &self,
+        path: &Path,
+        fitting: LogoFitting,
+        load: impl FnOnce() -> Result<SvgLogo, HelperError>,
+    ) -> Result<Arc<SvgLogo>, HelperError> {
+        match self.get_or_load(path, fitting, LogoKind::SvgInline, || {
+            load().map(|svg| CachedLogo::Svg(Arc::new(svg)))
+        })? {
+            CachedLogo::Svg(svg) => Ok(svg),
+            _ => unreachable!("SVG logos are cached as markup"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use image::Rgba;
+    use std::cell::Cell;
+
+    fn fitting(side: u32) -> LogoFitting {
+        LogoFitting {
+            side,
+            fit: LogoFit::Contain,
+            filter: LogoFilter::Nearest,
+            upscale: true,
+        }
+    }
+
+    fn load_count(cache: &LogoCache, path: &Path, side: u32, loads: &Cell<u32>) -> Arc<RgbaImage> {
+        cache
+            .image(path, fitting(side), LogoKind::Raster, || {
+                loads.set(loads.get() + 1);
+                Ok(image::open(path).unwrap().to_rgba8())
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fit_keeps_aspect_ratio() {
+        // A 4:1 wordmark, red on the left half and blue on the right half
+        let wordmark = RgbaImage::from_fn(400, 100, |x, _| {
+            if x < 200 {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 255, 255])
+            }
+        });
+        let mut fitting = fitting(60);
+        for filter in [
+            LogoFilter::Nearest,
+            LogoFilter::CatmullRom,
+            LogoFilter::Lanczos3,
+        ] {
+            fitting.filter = filter;
+            fitting.fit = LogoFit::Contain;
+            assert_eq!(fit_logo(&wordmark, fitting).dimensions(), (60, 15));
+
+            // Cover fills the area and crops the sides, keeping the middle of the wordmark
+            fitting.fit = LogoFit::Cover;
+            let covered = fit_logo(&wordmark, fitting);
+            assert_eq!(covered.dimensions(), (60, 60));
+            assert_eq!(covered.get_pixel(0, 30), &Rgba([255, 0, 0, 255]));
+            assert_eq!(covered.get_pixel(59, 30), &Rgba([0, 0, 255, 255]));
+        }
+
+        // Small logos are scaled up to the area as well
+        let tall = RgbaImage::new(10, 20);
+        fitting.fit = LogoFit::Contain;
+        assert_eq!(fit_logo(&tall, fitting).dimensions(), (30, 60));
+        let square = RgbaImage::new(60, 60);
+        assert_eq!(fit_logo(&square, fitting), square);
+
+        // Without upscaling, only logos larger than the area are scaled
+        fitting.upscale = false;
+        assert_eq!(fit_logo(&tall, fitting), tall);
+        assert_eq!(fit_logo(&wordmark, fitting).dimensions(), (60, 15));
+        fitting.fit = LogoFit::Cover;
+        assert_eq!(fit_logo(&tall, fitting).dimensions(), (10, 10));
+    }
+
+    #[test]
+    fn test_lru_eviction_within_budget() {
+        let dir = TempDir::new("logo-lru");
+        let a = dir.write_logo("a.png", 10, 10, [255, 0, 0, 255]);
+        let b = dir.write_logo("b.png", 10, 10, [0, 255, 0, 255]);
+        let c = dir.write_logo("c.png", 10, 10, [0, 0, 255, 255]);
+        // Room for two 10x10 logos
+        let cache = LogoCache::new(2 * 400);
+        let loads = Cell::new(0);
+
+        // Alternating between two logos keeps both cached
+        for _ in 0..3 {
+            load_count(&cache, &a, 10, &loads);
+            load_count(&cache, &b, 10, &loads);
+        }
+        assert_eq!(loads.get(), 2);
+        assert_eq!((cache.len(), cache.bytes()), (2, 800));
+
+        // A third logo evicts the least recently used one, which is `a`
+        load_count(&cache, &c, 10, &loads);
+        assert_eq!(loads.get(), 3);
+        load_count(&cache, &b, 10, &loads);
+        assert_eq!(loads.get(), 3);
+        load_count(&cache, &a, 10, &loads);
+        assert_eq!(loads.get(), 4);
+        assert_eq!(cache.bytes(), 800);
+
+        // Each target size is cached separately
+        load_count(&cache, &a, 5, &loads);
+        assert_eq!(loads.get(), 5);
+
+        cache.clear().unwrap();
+        assert!(cache.is_empty());
+        assert_eq!(cache.bytes(), 0);
+    }
+
+    #[test]
+    fn test_disabled_and_oversized_logos_are_not_cached() {
+        let dir = TempDir::new("logo-disabled");
+        let path = dir.write_logo("logo.png", 10, 10, [0, 0, 0, 255]);
+        let loads = Cell::new(0);
+        let disabled = LogoCache::disabled();
+        load_count(&disabled, &path, 10, &loads);
+        load_count(&disabled, &path, 10, &loads);
+        assert_eq!(loads.get(), 2);
+        assert!(disabled.is_empty());
+
+        let small = LogoCache::new(100);
+        load_count(&small, &path, 10, &loads);
+        assert!(small.is_empty());
+    }
+
+    #[test]
+    fn test_modified_file_is_reloaded() {
+        let dir = TempDir::new("logo-modified");
+        let path = dir.write_logo("logo.png", 10, 10, [255, 0, 0, 255]);
+        let cache = LogoCache::default();
+        let loads = Cell::new(0);
+        assert_eq!(
+            load_count(&cache, &path, 10, &loads).get_pixel(0, 0),
+            &Rgba([255, 0, 0, 255])
+        );
+
+        // A different size changes the length of the file even if the modification time is
+        // within the resolution of the file system clock
+        dir.write_logo("logo.png", 12, 12, [0, 0, 255, 255]);
+        assert_eq!(
+            load_count(&cache, &path, 10, &loads).get_pixel(0, 0),
+            &Rgba([0, 0, 255, 255])
+        );
+        assert_eq!(loads.get(), 2);
+    }
+
+    #[test]
+    fn test_missing_logo() {
+        let cache = LogoCache::default();
+        let result = cache.image(
+            Path::new("no/such/logo.png"),
+            fitting(10),
+            LogoKind::Raster,
+            || unreachable!(),
+        );
+        assert!(matches!(result, Err(HelperError::LogoNotFound(_))));
+    }
+}
@@ -0,0 +1,589 @@
+//! Inlining of SVG logos into SVG output.
+//!
+//! An SVG logo is copied into the document as a nested `<svg>` element whose `viewBox` maps the
+//! logo onto the logo area, so it stays sharp at any print size. Its `id` attributes, and the
+//! `href`, `url(#...)` and style sheet references to them, are prefixed so that they cannot collide
+//! with the ids of other logos or of a page the document is embedded in.
+//!
+//! Logos are treated as untrusted input: `<script>` and `<foreignObject>` elements, event handler
+//! attributes such as `onload`, and attributes holding `javascript:` URLs are dropped, so that a
+//! logo cannot run code in a page the document is embedded in.
+use std::collections::HashSet;
+use std::fmt::{self, Write};
+
+// Root attributes replaced by the placement of the logo.
+const PLACEMENT_ATTRIBUTES: [&str; 8] = [
+    "x",
+    "y",
+    "width",
+    "height",
+    "viewBox",
+    "preserveAspectRatio",
+    "xmlns",
+    "version",
+];
+
+/// An SVG logo with prefixed ids, ready to be placed in a document.
+#[derive(Debug)]
+pub(crate) struct SvgLogo {
+    // The root attributes that are kept, each preceded by a space
+    attributes: String,
+    view_box: [f64; 4],
+    // The markup between the root start and end tags
+    content: String,
+}
+
+impl SvgLogo {
+    // Parses the SVG document `source`, prefixing its ids with `prefix`.
+    pub(crate) fn parse(source: &str, prefix: &str) -> Result<Self, String> {
+        let mut rest = source.trim_start_matches('\u{feff}');
+        // Skip the XML declaration, comments and the document type before the root element
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix("<?") {
+                rest = skip_past(after, "?>")?;
+            } else if let Some(after) = rest.strip_prefix("<!--") {
+                rest = skip_past(after, "-->")?;
+            } else if let Some(after) = rest.strip_prefix("<!") {
+                rest = skip_doctype(after)?;
+            } else {
+                break;
+            }
+        }
+        let root = rest
+            .strip_prefix("<svg")
+            .filter(|after| after.starts_with(|c: char| c.is_whitespace() || c == '>' || c == '/'))
+            .ok_or("the document has no <svg> root element")?;
+        let (root_attributes, self_closing, after_root) = parse_attributes(root)?;
+        let content = if self_closing {
+            ""
+        } else {
+            let end = after_root
+                .rfind("</svg")
+                .ok_or("the <svg> root element is not closed")?;
+            &after_root[..end]
+        };
+
+        let mut ids = HashSet::new();
+        for (name, value) in &root_attributes {
+            if *name == "id" {
+                ids.insert(value.to_string());
+            }
+        }
+        collect_ids(content, &mut ids)?;
+        let rewriter = Rewriter { prefix, ids: &ids };
+
+        let attribute = |name: &str| {
+            root_attributes
+                .iter()
+                .find(|(attribute, _)| *attribute == name)
+                .map(|(_, value)| *value)
+        };
+        let view_box = match attribute("viewBox") {
+            Some(view_box) => parse_view_box(view_box)?,
+            None => {
+                let length = |name| attribute(name).and_then(parse_length);
+                match (length("width"), length("height")) {
+                    (Some(width), Some(height)) => [0.0, 0.0, width, height],
+                    _ => return Err("the logo has neither a viewBox nor a width and height".into()),
+                }
+            }
+        };
+
+        let mut attributes = String::new();
+        for (name, value) in &root_attributes {
+            if !PLACEMENT_ATTRIBUTES.contains(name) && !is_unsafe_attribute(name, value) {
+                write_attribute(&mut attributes, name, &rewriter.attribute(name, value));
+            }
+        }
+        Ok(Self {
+            attributes,
+            view_box,
+            content: rewriter.content(content)?,
+        })
+    }
+
+    // Returns the width of the logo divided by its height.
+    pub(crate) fn aspect_ratio(&self) -> f64 {
+        self.view_box[2] / self.view_box[3]
+    }
+
+    pub(crate) fn bytes(&self) -> usize {
+        self.attributes.len() + self.content.len()
+    }
+
+    // Writes the logo as a nested `<svg>` element filling `width` x `height` units at (`x`, `y`),
+    // cropping the logo to the area when `slice` is set and fitting it inside otherwise.
+    pub(crate) fn write(
+        &self,
+        result: &mut String,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        slice: bool,
+    ) -> fmt::Result {
+        let [min_x, min_y, view_width, view_height] = self.view_box;
+        writeln!(
+            result,
+            "<svg x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\" preserveAspectRatio=\"xMidYMid {}\"{}>{}</svg>",
+            x,
+            y,
+            width,
+            height,
+            min_x,
+            min_y,
+            view_width,
+            view_height,
+            if slice { "slice" } else { "meet" },
+            self.attributes,
+            self.content
+        )
+    }
+}
+
+// Returns the text after the first `end` in `text`.
+fn skip_past<'s>(text: &'s str, end: &str) -> Result<&'s str, String> {
+    text.find(end)
+        .map(|index| &text[index + end.len()..])
+        .ok_or_else(|| format!("unterminated markup, expected `{}`", end))
+}
+
+// Skips a document type declaration, including an internal subset in brackets.
+fn skip_doctype(text: &str) -> Result<&str, String> {
+    let mut depth = 0;
+    for (index, c) in text.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            '>' if depth == 0 => return Ok(&text[index + 1..]),
+            _ => {}
+        }
+    }
+    Err("unterminated document type declaration".into())
+}
+
+type Attributes<'s> = Vec<(&'s str, &'s str)>;
+
+// Parses the attributes of a start tag whose name has been consumed, returning them, whether the
+// tag is self-closing and the text after the tag.
+fn parse_attributes(mut text: &str) -> Result<(Attributes<'_>, bool, &str), String> {
+    let mut attributes = Vec::new();
+    loop {
+        text = text.trim_start();
+        if let Some(after) = text.strip_prefix("/>") {
+            return Ok((attributes, true, after));
+        }
+        if let Some(after) = text.strip_prefix('>') {
+            return Ok((attributes, false, after));
+        }
+        let name_end = text
+            .find(|c: char| c.is_whitespace() || "=/>".contains(c))
+            .ok_or("unterminated start tag")?;
+        let (name, after) = text.split_at(name_end);
+        let after = after.trim_start();
+        let after = after
+            .strip_prefix('=')
+            .ok_or_else(|| format!("attribute `{}` has no value", name))?
+            .trim_start();
+        let quote = after
+            .chars()
+            .next()
+            .filter(|&c| c == '"' || c == '\'')
+            .ok_or_else(|| format!("the value of attribute `{}` is not quoted", name))?;
+        let value_end = after[1..]
+            .find(quote)
+            .ok_or_else(|| format!("the value of attribute `{}` is not terminated", name))?;
+        attributes.push((name, &after[1..1 + value_end]));
+        text = &after[value_end + 2..];
+    }
+}
+
+fn write_attribute(result: &mut String, name: &str, value: &str) {
+    result.push(' ');
+    result.push_str(name);
+    result.push_str("=\"");
+    result.push_str(value);
+    result.push('"');
+}
+
+// Parses the four numbers of a `viewBox`.
+fn parse_view_box(value: &str) -> Result<[f64; 4], String> {
+    let numbers: Vec<f64> = value
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|part| !part.is_empty())
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("invalid viewBox `{}`", value))?;
+    match numbers[..] {
+        [min_x, min_y, width, height] if width > 0.0 && height > 0.0 => {
+            Ok([min_x, min_y, width, height])
+        }
+        _ => Err(format!("invalid viewBox `{}`", value)),
+    }
+}
+
+// Parses a positive length in user units or pixels; relative units cannot size a logo.
+fn parse_length(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let number = value.strip_suffix("px").unwrap_or(value);
+    number
+        .trim()
+        .parse()
+        .ok()
+        .filter(|&length: &f64| length > 0.0)
+}
+
+// Adds the ids defined in `content` to `ids`.
+fn collect_ids(content: &str, ids: &mut HashSet<String>) -> Result<(), String> {
+    for_each_tag(content, |tag| {
+        if let Tag::Start { attributes, .. } = tag {
+            for (name, value) in attributes {
+                if *name == "id" {
+                    ids.insert(value.to_string());
+                }
+            }
+        }
+    })
+}
+
+enum Tag<'s, 't> {
+    Start {
+        name: &'s str,
+        attributes: &'t Attributes<'s>,
+        self_closing: bool,
+    },
+    End(&'s str),
+    // Text, character data or the markup of other nodes, copied verbatim
+    Other(&'s str),
+}
+
+// Calls `f` with each node of `content`, dropping comments and processing instructions.
+fn for_each_tag<'s>(mut content: &'s str, mut f: impl FnMut(Tag<'s, '_>)) -> Result<(), String> {
+    while !content.is_empty() {
+        if let Some(after) = content.strip_prefix("<!--") {
+            content = skip_past(after, "-->")?;
+        } else if let Some(after) = content.strip_prefix("<?") {
+            content = skip_past(after, "?>")?;
+        } else if content.starts_with("<![CDATA[") {
+            let end = content.find("]]>").ok_or("unterminated CDATA section")? + 3;
+            f(Tag::Other(&content[..end]));
+            content = &content[end..];
+        } else if let Some(after) = content.strip_prefix("</") {
+            let end = after.find('>').ok_or("unterminated end tag")?;
+            f(Tag::End(after[..end].trim()));
+            content = &after[end + 1..];
+        } else if let Some(after) = content.strip_prefix('<') {
+            let name_end = after
+                .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                .ok_or("unterminated start tag")?;
+            let (name, after) = after.split_at(name_end);
+            let (attributes, self_closing, after) = parse_attributes(after)?;
+            f(Tag::Start {
+                name,
+                attributes: &attributes,
+                self_closing,
+            });
+            content = after;
+        } else {
+            let end = content.find('<').unwrap_or(content.len());
+            f(Tag::Other(&content[..end]));
+            content = &content[end..];
+        }
+    }
+    Ok(())
+}
+
+// Whether the element `name` can run code or embed HTML, or is an animation that rewrites a
+// link, ignoring any namespace prefix.
+fn is_unsafe_element(name: &str, attributes: &Attributes) -> bool {
+    let local_name = name.rsplit(':').next().unwrap_or(name);
+    if local_name.eq_ignore_ascii_case("animate") || local_name.eq_ignore_ascii_case("set") {
+        return attributes.iter().any(|(attribute, value)| {
+            let target = value.trim();
+            *attribute == "attributeName" && (target == "href" || target.ends_with(":href"))
+        });
+    }
+    local_name.eq_ignore_ascii_case("script") || local_name.eq_ignore_ascii_case("foreignObject")
+}
+
+// Whether the attribute is an event handler or holds a `javascript:` URL, which browsers accept
+// with character references, whitespace and control characters mixed in and in any case. The
+// values of an animation are checked item by item, after decoding.
+fn is_unsafe_attribute(name: &str, value: &str) -> bool {
+    let local_name = name.rsplit(':').next().unwrap_or(name);
+    if local_name
+        .get(..2)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("on"))
+    {
+        return true;
+    }
+    let is_list = ["values", "from", "to", "by"].contains(&local_name);
+    let mut scheme = String::new();
+    // Set once the current item is known not to start with a `javascript:` scheme
+    let mut skipping = false;
+    let mut rest = value;
+    while let Some(c) = rest.chars().next() {
+        let (c, len) = match rest.strip_prefix("&#") {
+            Some(reference) => {
+                let end = reference.find(';').unwrap_or(reference.len());
+                let code = match reference[..end].strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => reference[..end].parse().ok(),
+                };
+                match code.and_then(char::from_u32) {
+                    Some(c) => (c, 2 + end + usize::from(end < reference.len())),
+                    None => (c, 1),
+                }
+            }
+            None => (c, c.len_utf8()),
+        };
+        rest = &rest[len..];
+        if is_list && c == ';' {
+            scheme.clear();
+            skipping = false;
+            continue;
+        }
+        if skipping {
+            continue;
+        }
+        if c == ':' {
+            if scheme.eq_ignore_ascii_case("javascript") {
+                return true;
+            }
+            skipping = true;
+        } else if !(c.is_ascii_whitespace() || c.is_control()) {
+            scheme.push(c);
+            skipping = scheme.len() > "javascript".len();
+        }
+        if skipping && !is_list {
+            return false;
+        }
+    }
+    false
+}
+
+// Prefixes ids and the references to them.
+struct Rewriter<'a> {
+    prefix: &'a str,
+    ids: &'a HashSet<String>,
+}
+
+impl Rewriter<'_> {
+    fn content(&self, content: &str) -> Result<String, String> {
+        let mut result = String::with_capacity(content.len());
+        let mut in_style = false;
+        // Depth of the unsafe element being dropped, or 0 outside of one
+        let mut dropped_depth = 0;
+        for_each_tag(content, |tag| match tag {
+            Tag::Start { self_closing, .. } if dropped_depth > 0 => {
+                if !self_closing {
+                    dropped_depth += 1;
+                }
+            }
+            Tag::End(_) if dropped_depth > 0 => dropped_depth -= 1,
+            Tag::Other(_) if dropped_depth > 0 => {}
+            Tag::Start {
+                name,
+                attributes,
+                self_closing,
+            } if is_unsafe_element(name, attributes) => {
+                if !self_closing {
+                    dropped_depth = 1;
+                }
+            }
+            Tag::Start {
+                name,
+                attributes,
+                self_closing,
+            } => {
+                result.push('<');
+                result.push_str(name);
+                for (attribute, value) in attributes {
+                    if !is_unsafe_attribute(attribute, value) {
+                        write_attribute(&mut result, attribute, &self.attribute(attribute, value));
+                    }
+                }
+                result.push_str(if self_closing { "/>" } else { ">" });
+                in_style = name == "style" && !self_closing;
+            }
+            Tag::End(name) => {
+                result.push_str("</");
+                result.push_str(name);
+                result.push('>');
+                in_style = false;
+            }
+            Tag::Other(text) if in_style => result.push_str(&self.style_sheet(text)),
+            Tag::Other(text) => result.push_str(text),
+        })?;
+        Ok(result)
+    }
+
+    fn is_id(&self, name: &str) -> bool {
+        self.ids.contains(name)
+    }
+
+    // Returns the value of attribute `name` with its id or references prefixed. Values are written
+    // back in double quotes, so double quotes inside them are escaped.
+    fn attribute(&self, name: &str, value: &str) -> String {
+        let value = if name == "id" {
+            format!("{}{}", self.prefix, value)
+        } else if name == "href" || name.ends_with(":href") {
+            match value.strip_prefix('#') {
+                Some(id) if self.is_id(id) => format!("#{}{}", self.prefix, id),
+                _ => value.to_string(),
+            }
+        } else {
+            self.references(value, |text| {
+                text.strip_prefix("url(").map(|rest| (4, rest))
+            })
+        };
+        value.replace('"', "&quot;")
+    }
+
+    // Prefixes `#id` selectors and `url(#id)` references in a style sheet.
+    fn style_sheet(&self, text: &str) -> String {
+        self.references(text, |text| Some((0, text)))
+    }
+
+    // Copies `text`, prefixing each `#id` reference of a known id that `start` accepts. `start`
+    // receives the text from a candidate position and returns the length of the text before the
+    // optional quote and the `#`, and the text from there.
+    fn references(&self, text: &str, start: impl Fn(&str) -> Option<(usize, &str)>) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(index) = rest.find(['u', '#']) {
+            result.push_str(&rest[..index]);
+            rest = &rest[index..];
+            let reference = start(rest).and_then(|(skip, after)| {
+                let quote = after.len() - after.trim_start_matches(['\'', '"']).len();
+                let id_text = after[quote..].strip_prefix('#')?;
+                let id_len = id_text
+                    .find(|c: char| !(c.is_alphanumeric() || "-_.:".contains(c)))
+                    .unwrap_or(id_text.len());
+                let id = id_text[..id_len].trim_end_matches(['.', ':']);
+                self.is_id(id).then_some((skip + quote + 1, id))
+            });
+            match reference {
+                Some((before_id, id)) => {
+                    result.push_str(&rest[..before_id]);
+                    result.push_str(self.prefix);
+                    result.push_str(id);
+                    rest = &rest[before_id + id.len()..];
+                }
+                None => {
+                    let len = rest.chars().next().map_or(0, char::len_utf8);
+                    result.push_str(&rest[..len]);
+                    rest = &rest[len..];
+                }
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_prefixes_ids_and_references() {
+        let source = r##"<?xml version="1.0"?>
+<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd">
+<!-- Wordmark -->
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="200" height="50" viewBox="0 0 400 100" fill="none">
+  <defs><linearGradient id="g"><stop offset="0" stop-color="#fff"/></linearGradient></defs>
+  <style>#mark { fill: url('#g'); } .fff { color: #fff; }</style>
+  <path id="mark" d="M0 0h400v100z" stroke="url(#g)" style="fill: url(#missing)"/>
+  <use xlink:href="#mark" x='10'/>
+</svg>"##;
+        let logo = SvgLogo::parse(source, "p-").unwrap();
+        assert_eq!(logo.view_box, [0.0, 0.0, 400.0, 100.0]);
+        assert_eq!(logo.aspect_ratio(), 4.0);
+        assert_eq!(
+            logo.attributes,
+            r#" xmlns:xlink="http://www.w3.org/1999/xlink" fill="none""#
+        );
+        assert!(logo.content.contains(r#"<linearGradient id="p-g">"#));
+        assert!(logo
+            .content
+            .contains(r##"<stop offset="0" stop-color="#fff"/>"##));
+        assert!(logo
+            .content
+            .contains("#p-mark { fill: url('#p-g'); } .fff { color: #fff; }"));
+        assert!(logo.content.contains(
+            r##"<path id="p-mark" d="M0 0h400v100z" stroke="url(#p-g)" style="fill: url(#missing)"/>"##
+        ));
+        assert!(logo
+            .content
+            .contains(r##"<use xlink:href="#p-mark" x="10"/>"##));
+
+        let mut result = String::new();
+        logo.write(&mut result, 5, 6, 40, 10, false).unwrap();
+        assert!(result.starts_with(
+            r#"<svg x="5" y="6" width="40" height="10" viewBox="0 0 400 100" preserveAspectRatio="xMidYMid meet" xmlns:xlink"#
+        ));
+        assert!(result.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_parse_size_without_view_box() {
+        let logo = SvgLogo::parse(
+            r#"<svg width="30px" height="60"><rect width="30" height="60"/></svg>"#,
+            "p-",
+        )
+        .unwrap();
+        assert_eq!(logo.view_box, [0.0, 0.0, 30.0, 60.0]);
+        assert_eq!(logo.content, r#"<rect width="30" height="60"/>"#);
+
+        assert!(SvgLogo::parse(r#"<svg width="100%" height="60"/>"#, "p-").is_err());
+        assert!(SvgLogo::parse(r#"<svg viewBox="0 0 0 10"/>"#, "p-").is_err());
+        assert!(SvgLogo::parse("<html></html>", "p-").is_err());
+        assert!(SvgLogo::parse(r#"<svg viewBox="0 0 1 1"><g>"#, "p-").is_err());
+    }
+
+    #[test]
+    fn test_parse_drops_scripts_and_event_handlers() {
+        let source = r#"<svg viewBox="0 0 10 10" onload="alert(1)" class="logo">
+  <script>alert(2)</script>
+  <svg:script href="evil.js"/>
+  <foreignObject width="10" height="10"><div><p>html</p></div></foreignObject>
+  <a href="javascript:alert(3)"><rect width="10" height="10" onClick="alert(4)" fill="red"/></a>
+  <a xlink:href=" &#106;ava&#x53;cript&#58;alert(5)"><circle r="1"/></a>
+  <set attributeName="href" to="JaVa&#9;Script:alert(6)"/>
+  <a><animate attributeName="xlink:href" values="x;javascript:alert(7)"/></a>
+  <animate attributeName="fill" values="red;&#32;javascript&#58;alert(8);blue" dur="1s"/>
+  <animate attributeName="opacity" values="0;1" dur="1s"/>
+  <a href="https://example.com/javascript:ok"><path d="M0 0"/></a>
+</svg>"#;
+        let logo = SvgLogo::parse(source, "p-").unwrap();
+        assert_eq!(logo.attributes, r#" class="logo""#);
+        for unsafe_text in [
+            "alert",
+            "script>",
+            "evil.js",
+            "foreignObject",
+            "html",
+            "onClick",
+            "&#",
+        ] {
+            assert!(!logo.content.contains(unsafe_text), "{}", unsafe_text);
+        }
+        assert!(logo
+            .content
+            .contains(r#"<a><rect width="10" height="10" fill="red"/></a>"#));
+        assert!(logo.content.contains(r#"<a><circle r="1"/></a>"#));
+        assert!(!logo.content.contains("<set"));
+        assert!(logo.content.contains(r#"<a></a>"#));
+        assert!(logo
+            .content
+            .contains(r#"<animate attributeName="fill" dur="1s"/>"#));
+        assert!(logo
+            .content
+            .contains(r#"<animate attributeName="opacity" values="0;1" dur="1s"/>"#));
+        assert!(logo
+            .content
+            .contains(r#"<a href="https://example.com/javascript:ok"><path d="M0 0"/></a>"#));
+    }
+}
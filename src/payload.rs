@@ -0,0 +1,629 @@
+//! Builders for structured QR code payloads.
+//!
+//! Scanner apps recognise a number of text formats, such as `WIFI:` network credentials, and act
+//! on them instead of showing plain text. The builders in this module validate their fields and
+//! produce correctly escaped payload strings, so the result can be passed to
+//! [QrCode::encode_text] or to any `generate_*` helper in [crate::helper].
+//!
+//! Every builder implements [QrPayload], which also encodes the payload directly with the options
+//! the format requires.
+//!
+//! # Example
+//!
+//! ```rust
+//! use qirust::payload::{QrPayload, WiFi, WiFiSecurity};
+//! use qirust::qrcode::Version;
+//!
+//! let wifi = WiFi::new("Guest Network").unwrap()
+//!     .with_security(WiFiSecurity::Wpa)
+//!     .with_password("correct;horse").unwrap();
+//! assert_eq!(
+//!     wifi.to_payload().unwrap(),
+//!     "WIFI:T:WPA;S:Guest Network;P:correct\\;horse;;"
+//! );
+//!
+//! let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+//! let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+//! let qr = wifi.encode(&mut tempbuffer, &mut outbuffer).unwrap();
+//! # assert!(qr.size() > 0);
+//! ```
+use crate::capacity::CapacityPlan;
+use crate::qrcode::{
+    DataTooLong, EncodeTextOptions, QrCode, QrCodeEcc, QrSegment, QrSegmentMode, Version,
+};
+use std::{error::Error, fmt, ops::Range};
+
+mod contact;
+mod crypto;
+mod emv;
+mod epc;
+mod event;
+mod otp;
+mod parser;
+mod swiss;
+mod uri;
+mod wifi;
+
+pub use contact::{
+    Address, ContactName, EmailType, MeCard, ParsedAddress, ParsedContactName, ParsedMeCard,
+    ParsedPhoto, ParsedVCard, PhoneType, Photo, VCard, VCardVersion,
+};
+pub use crypto::{Bitcoin, Ethereum, Lightning, TokenTransfer};
+pub use emv::{Emv, EmvTemplate, EmvValue, PointOfInitiation};
+pub use epc::{Epc, EpcVersion, ParsedEpc};
+pub use event::CalendarEvent;
+pub use otp::{Otp, OtpAlgorithm, OtpKind, ParsedOtp};
+pub use parser::{parse, parse_with, ParseMode, Payload};
+pub use swiss::{SwissAddress, SwissCurrency, SwissQrBill, SwissReference};
+pub use uri::{Email, Geo, ParsedEmail, ParsedGeo, ParsedSms, Phone, Sms, Url};
+pub use wifi::{EapMethod, ParsedWiFi, Phase2Method, WiFi, WiFiSecurity};
+
+// Minimum run lengths for which a separate numeric or alphanumeric segment saves more bits than its
+// extra header costs; shorter runs are absorbed into the surrounding segment.
+const MIN_NUMERIC_RUN: usize = 8;
+const MIN_ALPHANUMERIC_RUN: usize = 14;
+const ALPHANUMERIC_CHARSET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+// vCard and iCalendar lines longer than this many bytes are folded onto continuation lines.
+const MAX_LINE_BYTES: usize = 75;
+
+/// Error returned when a payload cannot be built or encoded.
+#[derive(Debug, Clone)]
+pub enum PayloadError {
+    /// A required field was not set
+    MissingField(&'static str),
+    /// A field has a value that the payload format does not allow
+    InvalidField { field: &'static str, reason: String },
+    /// The payload does not fit in a QR code with the options the format requires
+    DataTooLong(DataTooLong),
+}
+
+impl fmt::Display for PayloadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PayloadError::MissingField(field) => write!(f, "Missing required field: {}", field),
+            PayloadError::InvalidField { field, reason } => {
+                write!(f, "Invalid {}: {}", field, reason)
+            }
+            PayloadError::DataTooLong(e) => write!(f, "Data too long: {}", e),
+        }
+    }
+}
+
+impl Error for PayloadError {}
+
+impl From<DataTooLong> for PayloadError {
+    fn from(err: DataTooLong) -> Self {
+        PayloadError::DataTooLong(err)
+    }
+}
+
+// Returns an `InvalidField` error for `field`, with `reason` describing the accepted values.
+pub(crate) fn invalid(field: &'static str, reason: impl Into<String>) -> PayloadError {
+    PayloadError::InvalidField {
+        field,
+        reason: reason.into(),
+    }
+}
+
+/// A structured payload that can be turned into QR code text.
+pub trait QrPayload {
+    /// Validates the fields and returns the payload text.
+    fn to_payload(&self) -> Result<String, PayloadError>;
+
+    /// Returns the encoding options used by [QrPayload::encode].
+    ///
+    /// Defaults to [QrCodeEcc::Medium] over the full version range, with the error correction level
+    /// boosted when it does not increase the version. Formats that mandate specific options
+    /// override this.
+    fn encode_options(&self) -> EncodeTextOptions {
+        EncodeTextOptions {
+            ecl: QrCodeEcc::Medium,
+            minversion: Version::MIN,
+            maxversion: Version::MAX,
+            mask: None,
+            boostecl: true,
+        }
+    }
+
+    /// Builds the payload and encodes it into a QR code using [QrPayload::encode_options], with
+    /// the segment modes chosen by [encode_segmented].
+    ///
+    /// Both buffers must be at least [Version::buffer_len] bytes for the largest version allowed
+    /// by the options.
+    fn encode<'a>(
+        &self,
+        tempbuffer: &mut [u8],
+        outbuffer: &'a mut [u8],
+    ) -> Result<QrCode<'a>, PayloadError> {
+        let text = self.to_payload()?;
+        Ok(encode_segmented(
+            &text,
+            tempbuffer,
+            outbuffer,
+            self.encode_options(),
+        )?)
+    }
+
+    /// Estimates how large the encoded payload will be.
+    ///
+    /// Returns the payload length in bytes and, for every error correction level, the smallest
+    /// version within the range of [QrPayload::encode_options] that can hold it.
+    fn estimate_size(&self) -> Result<SizeEstimate, PayloadError> {
+        let text = self.to_payload()?;
+        let options = self.encode_options();
+        // Text too long for any segment fits no version at all
+        let plan = with_segments(&text, |segs| {
+            CapacityPlan::for_segments(segs, options.minversion, options.maxversion)
+        })
+        .unwrap_or(CapacityPlan::EMPTY);
+
+        let mut versions = [None; 4];
+        for (version, ecl) in versions.iter_mut().zip(QrCodeEcc::ALL) {
+            *version = plan.fit(ecl).map(|fit| fit.version());
+        }
+        Ok(SizeEstimate {
+            bytes: text.len(),
+            versions,
+        })
+    }
+}
+
+/// Size of an encoded payload, as returned by [QrPayload::estimate_size].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeEstimate {
+    /// Length of the payload text in bytes
+    pub bytes: usize,
+    versions: [Option<Version>; 4],
+}
+
+impl SizeEstimate {
+    /// Returns the smallest version that holds the payload at the given error correction level, or
+    /// `None` if it does not fit.
+    pub fn version_for(&self, ecc: QrCodeEcc) -> Option<Version> {
+        QrCodeEcc::ALL
+            .iter()
+            .position(|&level| level == ecc)
+            .and_then(|i| self.versions[i])
+    }
+
+    /// Returns the strongest error correction level that fits within `max_version`, together with
+    /// the version it needs.
+    pub fn strongest_ecc(&self, max_version: Version) -> Option<(QrCodeEcc, Version)> {
+        QrCodeEcc::ALL
+            .iter()
+            .zip(self.versions)
+            .rev()
+            .find_map(|(&ecc, version)| version.filter(|v| *v <= max_version).map(|v| (ecc, v)))
+    }
+}
+
+/// Encodes text into a QR code using a mix of numeric, alphanumeric and byte segments.
+///
+/// [QrCode::encode_text] picks a single mode for the whole text, so one lowercase letter forces
+/// everything into byte mode. This function gives long runs of digits, or of the alphanumeric
+/// characters `0`–`9`, `A`–`Z`, space and `$%*+-./:`, their own compact segment, which is why the
+/// payload builders upper-case case-insensitive parts such as URL schemes and hosts. Short runs stay
+/// in the surrounding segment, where a mode switch would cost more than it saves.
+///
+/// Takes the same buffers and options as [QrCode::encode_text].
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::payload::encode_segmented;
+/// use qirust::qrcode::{EncodeTextOptions, QrCodeEcc, Version};
+///
+/// let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let qr = encode_segmented(
+///     "HTTPS://EXAMPLE.COM/ORDERS/2024?id=0123456789012",
+///     &mut tempbuffer,
+///     &mut outbuffer,
+///     EncodeTextOptions {
+///         ecl: QrCodeEcc::Medium,
+///         minversion: Version::MIN,
+///         maxversion: Version::MAX,
+///         mask: None,
+///         boostecl: true,
+///     },
+/// ).unwrap();
+/// assert_eq!(qr.version(), Version::new(3));
+/// ```
+pub fn encode_segmented<'a>(
+    text: &str,
+    tempbuffer: &mut [u8],
+    mut outbuffer: &'a mut [u8],
+    options: EncodeTextOptions,
+) -> Result<QrCode<'a>, DataTooLong> {
+    let minlen = outbuffer.len().min(tempbuffer.len());
+    outbuffer = &mut outbuffer[..minlen];
+
+    let (datacodewordslen, ecl, version) = with_segments(text, |segs| {
+        QrCode::encode_segments_to_codewords(
+            segs,
+            &mut *outbuffer,
+            options.ecl,
+            options.minversion,
+            options.maxversion,
+            options.boostecl,
+        )
+    })??;
+    Ok(QrCode::encode_codewords(
+        outbuffer,
+        datacodewordslen,
+        tempbuffer,
+        ecl,
+        version,
+        options.mask,
+    ))
+}
+
+// Builds the segments chosen by `segment_runs` and passes them to `f`. Fails if a run is too long
+// for its segment buffer to be sized.
+pub(crate) fn with_segments<R>(
+    text: &str,
+    f: impl FnOnce(&[QrSegment]) -> R,
+) -> Result<R, DataTooLong> {
+    let runs = segment_runs(text);
+    let mut buffers = Vec::with_capacity(runs.len());
+    for (mode, range) in &runs {
+        let len = match mode {
+            QrSegmentMode::Byte => 0,
+            _ => QrSegment::calc_buffer_size(*mode, range.len())
+                .ok_or(DataTooLong::SegmentTooLong)?,
+        };
+        buffers.push(vec![0u8; len]);
+    }
+    let segs: Vec<QrSegment> = runs
+        .iter()
+        .zip(buffers.iter_mut())
+        .map(|((mode, range), buffer)| match mode {
+            QrSegmentMode::Numeric => QrSegment::make_numeric(&text[range.clone()], buffer),
+            QrSegmentMode::Alphanumeric => {
+                QrSegment::make_alphanumeric(&text[range.clone()], buffer)
+            }
+            _ => QrSegment::make_bytes(&text.as_bytes()[range.clone()]),
+        })
+        .collect();
+    Ok(f(&segs))
+}
+
+// Splits text into byte ranges with the segment mode to use for each. Runs of digits and
+// alphanumeric characters are always ASCII, so every range boundary is a character boundary.
+fn segment_runs(text: &str) -> Vec<(QrSegmentMode, Range<usize>)> {
+    let mut runs: Vec<(QrSegmentMode, Range<usize>)> = Vec::new();
+    for (i, byte) in text.bytes().enumerate() {
+        let mode = if byte.is_ascii_digit() {
+            QrSegmentMode::Numeric
+        } else if ALPHANUMERIC_CHARSET.contains(&byte) {
+            QrSegmentMode::Alphanumeric
+        } else {
+            QrSegmentMode::Byte
+        };
+        match runs.last_mut() {
+            Some((last, range)) if *last == mode => range.end = i + 1,
+            _ => runs.push((mode, i..i + 1)),
+        }
+    }
+    // Digits can always join an alphanumeric segment, and every character a byte segment
+    let runs = absorb_short_runs(
+        runs,
+        QrSegmentMode::Numeric,
+        MIN_NUMERIC_RUN,
+        QrSegmentMode::Alphanumeric,
+    );
+    absorb_short_runs(
+        runs,
+        QrSegmentMode::Alphanumeric,
+        MIN_ALPHANUMERIC_RUN,
+        QrSegmentMode::Byte,
+    )
+}
+
+// Changes runs of `mode` shorter than `min_len` to `into` and merges neighbouring runs that end up
+// with the same mode. Text consisting of a single run keeps its mode.
+fn absorb_short_runs(
+    runs: Vec<(QrSegmentMode, Range<usize>)>,
+    mode: QrSegmentMode,
+    min_len: usize,
+    into: QrSegmentMode,
+) -> Vec<(QrSegmentMode, Range<usize>)> {
+    if runs.len() < 2 {
+        return runs;
+    }
+    let mut merged: Vec<(QrSegmentMode, Range<usize>)> = Vec::with_capacity(runs.len());
+    for (run_mode, range) in runs {
+        let run_mode = if run_mode == mode && range.len() < min_len {
+            into
+        } else {
+            run_mode
+        };
+        match merged.last_mut() {
+            Some((last, last_range)) if *last == run_mode => last_range.end = range.end,
+            _ => merged.push((run_mode, range)),
+        }
+    }
+    merged
+}
+
+// Percent-encodes every byte outside the RFC 3986 unreserved characters and `keep`, using uppercase
+// hex digits so encoded text stays eligible for alphanumeric segments.
+pub(crate) fn percent_encode(value: &str, keep: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric()
+            || b"-._~".contains(&byte)
+            || keep.as_bytes().contains(&byte)
+        {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+// Decodes percent-escapes. In lenient mode malformed escapes and invalid UTF-8 are passed through
+// instead of being rejected.
+pub(crate) fn percent_decode(value: &str, mode: ParseMode) -> Result<String, PayloadError> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = bytes
+            .get(i + 1..i + 3)
+            .filter(|hex| bytes[i] == b'%' && hex.iter().all(u8::is_ascii_hexdigit));
+        match escape {
+            Some(hex) => {
+                let hex = std::str::from_utf8(hex).unwrap_or_default();
+                decoded.push(u8::from_str_radix(hex, 16).unwrap_or_default());
+                i += 3;
+            }
+            None if bytes[i] == b'%' && mode == ParseMode::Strict => {
+                return Err(PayloadError::InvalidField {
+                    field: "percent-encoding",
+                    reason: format!("'{}' has a malformed escape", value),
+                });
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    match String::from_utf8(decoded) {
+        Ok(decoded) => Ok(decoded),
+        Err(_) if mode == ParseMode::Strict => Err(PayloadError::InvalidField {
+            field: "percent-encoding",
+            reason: format!("'{}' does not decode to UTF-8", value),
+        }),
+        Err(err) => Ok(String::from_utf8_lossy(err.as_bytes()).into_owned()),
+    }
+}
+
+// Escapes every character in `special` with a backslash, as used by the `WIFI:` and `MECARD:`
+// formats.
+pub(crate) fn escape(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// Reverses `escape`. Strict mode rejects unescaped special characters and a trailing backslash.
+pub(crate) fn unescape(
+    field: &'static str,
+    value: &str,
+    special: &[char],
+    mode: ParseMode,
+) -> Result<String, PayloadError> {
+    let strict = mode == ParseMode::Strict;
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(next) => unescaped.push(next),
+                None if strict => {
+                    return Err(PayloadError::InvalidField {
+                        field,
+                        reason: "ends with an unpaired backslash".to_string(),
+                    });
+                }
+                None => unescaped.push(c),
+            },
+            _ if strict && special.contains(&c) => {
+                return Err(PayloadError::InvalidField {
+                    field,
+                    reason: format!("contains an unescaped '{}'", c),
+                });
+            }
+            _ => unescaped.push(c),
+        }
+    }
+    Ok(unescaped)
+}
+
+// Splits a value at every `separator` that is not escaped with a backslash, keeping the escapes.
+pub(crate) fn split_unescaped(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in value.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == separator {
+            parts.push(&value[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+// Computes the ISO 7064 MOD 97-10 remainder used by IBANs and creditor references, after moving the
+// first four characters to the end and replacing letters with numbers (A = 10 … Z = 35).
+pub(crate) fn mod97(value: &str) -> u32 {
+    let (head, tail) = value.split_at(4);
+    tail.chars().chain(head.chars()).fold(0, |acc, c| {
+        let digit = c.to_digit(36).unwrap_or(0);
+        if digit < 10 {
+            (acc * 10 + digit) % 97
+        } else {
+            (acc * 100 + digit) % 97
+        }
+    })
+}
+
+// Escapes a vCard or iCalendar text value (RFC 6350, 3.4 and RFC 5545, 3.3.11).
+pub(crate) fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | ',' | ';' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Reverses `escape_text`, also accepting the uppercase `\N` line break of older writers.
+pub(crate) fn unescape_text(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push('\n'),
+            Some(next) => unescaped.push(next),
+            None => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+// Appends a content line, folding it onto continuation lines that start with a space so that no
+// line exceeds 75 bytes. Multi-byte characters are never split.
+pub(crate) fn fold_line(line: &str, out: &mut String) {
+    let mut limit = MAX_LINE_BYTES;
+    let mut start = 0;
+    while line.len() - start > limit {
+        let mut end = start + limit;
+        while !line.is_char_boundary(end) {
+            end -= 1;
+        }
+        out.push_str(&line[start..end]);
+        out.push_str("\r\n ");
+        start = end;
+        // The leading space counts towards the limit of continuation lines
+        limit = MAX_LINE_BYTES - 1;
+    }
+    out.push_str(&line[start..]);
+    out.push_str("\r\n");
+}
+
+pub(crate) fn validate_phone(number: &str) -> Result<(), PayloadError> {
+    let allowed = |c: char| c.is_ascii_digit() || "+-(). ".contains(c);
+    if !number.chars().any(|c| c.is_ascii_digit()) || !number.chars().all(allowed) {
+        return Err(invalid(
+            "phone",
+            "must contain digits and only +, -, (, ), . or spaces",
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_email(email: &str) -> Result<(), PayloadError> {
+    let valid = email
+        .split_once('@')
+        .is_some_and(|(local, domain)| !local.is_empty() && !domain.is_empty());
+    if !valid || email.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(invalid(
+            "email",
+            "must be an address such as name@example.com",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qrcode::Mask;
+
+    fn options() -> EncodeTextOptions {
+        EncodeTextOptions {
+            ecl: QrCodeEcc::Low,
+            minversion: Version::MIN,
+            maxversion: Version::MAX,
+            mask: Some(Mask::new(0)),
+            boostecl: false,
+        }
+    }
+
+    #[test]
+    fn test_segment_runs() {
+        use QrSegmentMode::*;
+        let modes = |text: &str| -> Vec<(QrSegmentMode, usize)> {
+            segment_runs(text)
+                .into_iter()
+                .map(|(mode, range)| (mode, range.len()))
+                .collect()
+        };
+        assert_eq!(modes("0123456789"), [(Numeric, 10)]);
+        assert_eq!(modes("HELLO 123"), [(Alphanumeric, 9)]);
+        assert_eq!(modes("hello 123"), [(Byte, 9)]);
+        assert_eq!(
+            modes("HTTPS://EXAMPLE.COM/path"),
+            [(Alphanumeric, 20), (Byte, 4)]
+        );
+        assert_eq!(
+            modes("id=é0123456789012x"),
+            [(Byte, 5), (Numeric, 13), (Byte, 1)]
+        );
+        assert!(modes("").is_empty());
+    }
+
+    #[test]
+    fn test_encode_segmented_matches_single_mode_and_saves_space() {
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut outbuffer2 = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer2 = vec![0u8; Version::MAX.buffer_len()];
+
+        let text = "HELLO WORLD 42";
+        let segmented = encode_segmented(text, &mut tempbuffer, &mut outbuffer, options()).unwrap();
+        let single =
+            QrCode::encode_text(text, &mut tempbuffer2, &mut outbuffer2, options()).unwrap();
+        assert!(segmented == single);
+
+        let text = format!("HTTPS://EXAMPLE.COM/{}?q=x", "ABCDEFGHIJ".repeat(20));
+        let segmented = encode_segmented(&text, &mut tempbuffer, &mut outbuffer, options())
+            .unwrap()
+            .version();
+        let single = QrCode::encode_text(&text, &mut tempbuffer2, &mut outbuffer2, options())
+            .unwrap()
+            .version();
+        assert!(segmented < single);
+    }
+
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(percent_encode("a b/ü~", "/"), "a%20b/%C3%BC~");
+    }
+}
@@ -0,0 +1,1140 @@
+//! Contact cards in the vCard 3.0, vCard 4.0 and MeCard formats.
+use super::parser::strip_prefix_ignore_case;
+use super::{
+    escape, escape_text, fold_line, invalid, split_unescaped, unescape, unescape_text,
+    validate_email, validate_phone, ParseMode, PayloadError, QrPayload,
+};
+use crate::helper::encode_base64;
+
+// Characters that must be escaped with a backslash inside `MECARD:` fields.
+const MECARD_SPECIAL_CHARS: &[char] = &['\\', ';', ',', ':', '"'];
+
+/// Version of the vCard format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VCardVersion {
+    /// vCard 3.0 (RFC 2426), understood by virtually every scanner
+    V3,
+    /// vCard 4.0 (RFC 6350)
+    V4,
+}
+
+/// Type of a telephone number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhoneType {
+    Cell,
+    Home,
+    Work,
+    Fax,
+    Pager,
+    Voice,
+}
+
+impl PhoneType {
+    const ALL: [PhoneType; 6] = [
+        PhoneType::Cell,
+        PhoneType::Home,
+        PhoneType::Work,
+        PhoneType::Fax,
+        PhoneType::Pager,
+        PhoneType::Voice,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            PhoneType::Cell => "cell",
+            PhoneType::Home => "home",
+            PhoneType::Work => "work",
+            PhoneType::Fax => "fax",
+            PhoneType::Pager => "pager",
+            PhoneType::Voice => "voice",
+        }
+    }
+}
+
+/// Type of an email address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailType {
+    /// An address without a more specific type
+    Internet,
+    Home,
+    Work,
+}
+
+/// Structured name of a contact (`N:` field).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContactName<'a> {
+    pub family: &'a str,
+    pub given: &'a str,
+    pub additional: &'a str,
+    pub prefix: &'a str,
+    pub suffix: &'a str,
+}
+
+impl ContactName<'_> {
+    // Display name such as "Dr. Jane Q. Doe Jr.", used when no formatted name is set.
+    fn formatted(&self) -> String {
+        [
+            self.prefix,
+            self.given,
+            self.additional,
+            self.family,
+            self.suffix,
+        ]
+        .iter()
+        .filter(|part| !part.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+}
+
+/// Postal address of a contact (`ADR:` field). Unused parts are left empty.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Address<'a> {
+    pub po_box: &'a str,
+    pub extended: &'a str,
+    pub street: &'a str,
+    pub locality: &'a str,
+    pub region: &'a str,
+    pub postal_code: &'a str,
+    pub country: &'a str,
+}
+
+impl Address<'_> {
+    fn parts(&self) -> [&str; 7] {
+        [
+            self.po_box,
+            self.extended,
+            self.street,
+            self.locality,
+            self.region,
+            self.postal_code,
+            self.country,
+        ]
+    }
+}
+
+/// Contact photo embedded as base64.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Photo<'a> {
+    /// Media type of the image, such as `image/jpeg`
+    pub media_type: &'a str,
+    /// Base64-encoded image data
+    pub data: String,
+}
+
+/// Builder for a vCard contact payload.
+///
+/// Text values are escaped and lines longer than 75 bytes are folded, as required by the vCard
+/// specifications. Lines end with CRLF.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::payload::{EmailType, PhoneType, QrPayload, VCard, VCardVersion};
+/// use qirust::qrcode::{QrCodeEcc, Version};
+///
+/// let card = VCard::new(VCardVersion::V3)
+///     .with_name("Doe", "Jane")
+///     .with_organization("Example, Inc.")
+///     .with_phone(PhoneType::Cell, "+1 555 0100").unwrap()
+///     .with_email(EmailType::Work, "jane@example.com").unwrap();
+/// assert_eq!(
+///     card.to_payload().unwrap(),
+///     "BEGIN:VCARD\r\nVERSION:3.0\r\nN:Doe;Jane;;;\r\nFN:Jane Doe\r\n\
+///      ORG:Example\\, Inc.\r\nTEL;TYPE=CELL:+1 555 0100\r\n\
+///      EMAIL;TYPE=INTERNET,WORK:jane@example.com\r\nEND:VCARD\r\n"
+/// );
+///
+/// // Strongest error correction level that keeps the card within version 10
+/// let estimate = card.estimate_size().unwrap();
+/// let (ecc, version) = estimate.strongest_ecc(Version::new(10)).unwrap();
+/// assert_eq!(ecc, QrCodeEcc::Medium);
+/// assert!(version <= Version::new(10));
+/// ```
+#[derive(Debug, Clone)]
+pub struct VCard<'a> {
+    pub version: VCardVersion,
+    pub name: ContactName<'a>,
+    /// Display name (`FN:`), built from the name parts if not set
+    pub formatted_name: Option<&'a str>,
+    pub phones: Vec<(PhoneType, &'a str)>,
+    pub emails: Vec<(EmailType, &'a str)>,
+    pub organization: Option<&'a str>,
+    pub title: Option<&'a str>,
+    pub address: Option<Address<'a>>,
+    pub url: Option<&'a str>,
+    pub note: Option<&'a str>,
+    pub photo: Option<Photo<'a>>,
+}
+
+impl<'a> VCard<'a> {
+    pub fn new(version: VCardVersion) -> Self {
+        Self {
+            version,
+            name: ContactName::default(),
+            formatted_name: None,
+            phones: Vec::new(),
+            emails: Vec::new(),
+            organization: None,
+            title: None,
+            address: None,
+            url: None,
+            note: None,
+            photo: None,
+        }
+    }
+
+    /// Sets the family and given name
+    pub fn with_name(mut self, family: &'a str, given: &'a str) -> Self {
+        self.name.family = family;
+        self.name.given = given;
+        self
+    }
+
+    /// Sets every part of the structured name
+    pub fn with_name_parts(mut self, name: ContactName<'a>) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn with_formatted_name(mut self, formatted_name: &'a str) -> Self {
+        self.formatted_name = Some(formatted_name);
+        self
+    }
+
+    /// Adds a telephone number; may be called repeatedly
+    pub fn with_phone(mut self, kind: PhoneType, number: &'a str) -> Result<Self, PayloadError> {
+        validate_phone(number)?;
+        self.phones.push((kind, number));
+        Ok(self)
+    }
+
+    /// Adds an email address; may be called repeatedly
+    pub fn with_email(mut self, kind: EmailType, email: &'a str) -> Result<Self, PayloadError> {
+        validate_email(email)?;
+        self.emails.push((kind, email));
+        Ok(self)
+    }
+
+    pub fn with_organization(mut self, organization: &'a str) -> Self {
+        self.organization = Some(organization);
+        self
+    }
+
+    pub fn with_title(mut self, title: &'a str) -> Self {
+        self.title = Some(title);
+        self
+    }
+
+    pub fn with_address(mut self, address: Address<'a>) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    pub fn with_url(mut self, url: &'a str) -> Result<Self, PayloadError> {
+        validate_url(url)?;
+        self.url = Some(url);
+        Ok(self)
+    }
+
+    pub fn with_note(mut self, note: &'a str) -> Self {
+        self.note = Some(note);
+        self
+    }
+
+    /// Embeds a photo, base64-encoded with [encode_base64]
+    ///
+    /// Photos grow the payload quickly; check the result with [QrPayload::estimate_size].
+    pub fn with_photo(mut self, data: &[u8], media_type: &'a str) -> Result<Self, PayloadError> {
+        if data.is_empty() {
+            return Err(invalid("photo", "cannot be empty"));
+        }
+        self.photo = Some(Photo {
+            media_type,
+            data: encode_base64(data),
+        });
+        self.validate_photo()?;
+        Ok(self)
+    }
+
+    /// Validates the fields before use
+    pub fn validate(&self) -> Result<(), PayloadError> {
+        if self.display_name().is_empty() {
+            return Err(PayloadError::MissingField("name"));
+        }
+        for (_, number) in &self.phones {
+            validate_phone(number)?;
+        }
+        for (_, email) in &self.emails {
+            validate_email(email)?;
+        }
+        if let Some(url) = self.url {
+            validate_url(url)?;
+        }
+        self.validate_photo()
+    }
+
+    fn validate_photo(&self) -> Result<(), PayloadError> {
+        if let Some(photo) = &self.photo {
+            let subtype = photo.media_type.strip_prefix("image/").unwrap_or_default();
+            if subtype.is_empty() || !subtype.bytes().all(|b| b.is_ascii_alphanumeric()) {
+                return Err(invalid(
+                    "photo",
+                    "media type must be an image type such as image/jpeg",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn display_name(&self) -> String {
+        match self.formatted_name {
+            Some(name) => name.to_string(),
+            None => self.name.formatted(),
+        }
+    }
+}
+
+impl QrPayload for VCard<'_> {
+    fn to_payload(&self) -> Result<String, PayloadError> {
+        self.validate()?;
+        let v4 = self.version == VCardVersion::V4;
+
+        let mut lines = vec![
+            "BEGIN:VCARD".to_string(),
+            format!("VERSION:{}", if v4 { "4.0" } else { "3.0" }),
+        ];
+        let name = &self.name;
+        lines.push(format!(
+            "N:{}",
+            join_components(
+                &[
+                    name.family,
+                    name.given,
+                    name.additional,
+                    name.prefix,
+                    name.suffix
+                ],
+                ';',
+                escape_text
+            )
+        ));
+        lines.push(format!("FN:{}", escape_text(&self.display_name())));
+        if let Some(organization) = self.organization {
+            lines.push(format!("ORG:{}", escape_text(organization)));
+        }
+        if let Some(title) = self.title {
+            lines.push(format!("TITLE:{}", escape_text(title)));
+        }
+        for (kind, number) in &self.phones {
+            lines.push(if v4 {
+                format!(
+                    "TEL;VALUE=uri;TYPE={}:tel:{}",
+                    kind.as_str(),
+                    number.replace(' ', "-")
+                )
+            } else {
+                format!("TEL;TYPE={}:{}", kind.as_str().to_ascii_uppercase(), number)
+            });
+        }
+        for (kind, email) in &self.emails {
+            lines.push(match (v4, kind) {
+                (true, EmailType::Internet) => format!("EMAIL:{}", email),
+                (true, EmailType::Home) => format!("EMAIL;TYPE=home:{}", email),
+                (true, EmailType::Work) => format!("EMAIL;TYPE=work:{}", email),
+                (false, EmailType::Internet) => format!("EMAIL;TYPE=INTERNET:{}", email),
+                (false, EmailType::Home) => format!("EMAIL;TYPE=INTERNET,HOME:{}", email),
+                (false, EmailType::Work) => format!("EMAIL;TYPE=INTERNET,WORK:{}", email),
+            });
+        }
+        if let Some(address) = &self.address {
+            lines.push(format!(
+                "ADR:{}",
+                join_components(&address.parts(), ';', escape_text)
+            ));
+        }
+        if let Some(url) = self.url {
+            lines.push(format!("URL:{}", url));
+        }
+        if let Some(note) = self.note {
+            lines.push(format!("NOTE:{}", escape_text(note)));
+        }
+        if let Some(photo) = &self.photo {
+            lines.push(if v4 {
+                format!("PHOTO:data:{};base64,{}", photo.media_type, photo.data)
+            } else {
+                let subtype = &photo.media_type["image/".len()..];
+                format!(
+                    "PHOTO;ENCODING=b;TYPE={}:{}",
+                    subtype.to_ascii_uppercase(),
+                    photo.data
+                )
+            });
+        }
+        lines.push("END:VCARD".to_string());
+
+        let mut payload = String::new();
+        for line in &lines {
+            fold_line(line, &mut payload);
+        }
+        Ok(payload)
+    }
+}
+
+/// Builder for a MeCard contact payload, a compact format popular with mobile scanners.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::payload::{MeCard, QrPayload};
+///
+/// let card = MeCard::new("Doe", "Jane").unwrap()
+///     .with_phone("+15550100").unwrap()
+///     .with_email("jane@example.com").unwrap()
+///     .with_url("https://example.com").unwrap();
+/// assert_eq!(
+///     card.to_payload().unwrap(),
+///     "MECARD:N:Doe,Jane;TEL:+15550100;EMAIL:jane@example.com;URL:https\\://example.com;;"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct MeCard<'a> {
+    pub family_name: &'a str,
+    pub given_name: &'a str,
+    pub nickname: Option<&'a str>,
+    pub phones: Vec<&'a str>,
+    pub emails: Vec<&'a str>,
+    pub organization: Option<&'a str>,
+    pub address: Option<Address<'a>>,
+    pub url: Option<&'a str>,
+    pub note: Option<&'a str>,
+    /// Birthday as `YYYYMMDD`
+    pub birthday: Option<&'a str>,
+}
+
+impl<'a> MeCard<'a> {
+    pub fn new(family_name: &'a str, given_name: &'a str) -> Result<Self, PayloadError> {
+        let card = Self {
+            family_name,
+            given_name,
+            nickname: None,
+            phones: Vec::new(),
+            emails: Vec::new(),
+            organization: None,
+            address: None,
+            url: None,
+            note: None,
+            birthday: None,
+        };
+        card.validate_name()?;
+        Ok(card)
+    }
+
+    pub fn with_nickname(mut self, nickname: &'a str) -> Self {
+        self.nickname = Some(nickname);
+        self
+    }
+
+    /// Adds a telephone number; may be called repeatedly
+    pub fn with_phone(mut self, number: &'a str) -> Result<Self, PayloadError> {
+        validate_phone(number)?;
+        self.phones.push(number);
+        Ok(self)
+    }
+
+    /// Adds an email address; may be called repeatedly
+    pub fn with_email(mut self, email: &'a str) -> Result<Self, PayloadError> {
+        validate_email(email)?;
+        self.emails.push(email);
+        Ok(self)
+    }
+
+    pub fn with_organization(mut self, organization: &'a str) -> Self {
+        self.organization = Some(organization);
+        self
+    }
+
+    pub fn with_address(mut self, address: Address<'a>) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    pub fn with_url(mut self, url: &'a str) -> Result<Self, PayloadError> {
+        validate_url(url)?;
+        self.url = Some(url);
+        Ok(self)
+    }
+
+    pub fn with_note(mut self, note: &'a str) -> Self {
+        self.note = Some(note);
+        self
+    }
+
+    /// Sets the birthday, formatted as `YYYYMMDD`
+    pub fn with_birthday(mut self, birthday: &'a str) -> Result<Self, PayloadError> {
+        validate_birthday(birthday)?;
+        self.birthday = Some(birthday);
+        Ok(self)
+    }
+
+    /// Validates the fields before use
+    pub fn validate(&self) -> Result<(), PayloadError> {
+        self.validate_name()?;
+        for number in &self.phones {
+            validate_phone(number)?;
+        }
+        for email in &self.emails {
+            validate_email(email)?;
+        }
+        if let Some(url) = self.url {
+            validate_url(url)?;
+        }
+        if let Some(birthday) = self.birthday {
+            validate_birthday(birthday)?;
+        }
+        Ok(())
+    }
+
+    fn validate_name(&self) -> Result<(), PayloadError> {
+        if self.family_name.is_empty() && self.given_name.is_empty() {
+            return Err(PayloadError::MissingField("name"));
+        }
+        Ok(())
+    }
+}
+
+impl QrPayload for MeCard<'_> {
+    fn to_payload(&self) -> Result<String, PayloadError> {
+        self.validate()?;
+
+        let mut payload = format!("MECARD:N:{}", escape_mecard(self.family_name));
+        if !self.given_name.is_empty() {
+            payload.push(',');
+            payload.push_str(&escape_mecard(self.given_name));
+        }
+        payload.push(';');
+        let mut push = |field: &str, value: &str| {
+            payload.push_str(&format!("{}:{};", field, escape_mecard(value)));
+        };
+        if let Some(nickname) = self.nickname {
+            push("NICKNAME", nickname);
+        }
+        for number in &self.phones {
+            push("TEL", number);
+        }
+        for email in &self.emails {
+            push("EMAIL", email);
+        }
+        if let Some(organization) = self.organization {
+            push("ORG", organization);
+        }
+        if let Some(url) = self.url {
+            push("URL", url);
+        }
+        if let Some(note) = self.note {
+            push("NOTE", note);
+        }
+        if let Some(birthday) = self.birthday {
+            push("BDAY", birthday);
+        }
+        if let Some(address) = &self.address {
+            // Address parts are separated by unescaped commas
+            let parts = join_components(&address.parts(), ',', escape_mecard);
+            payload.push_str(&format!("ADR:{};", parts));
+        }
+        payload.push(';');
+        Ok(payload)
+    }
+}
+
+/// Owned [ContactName] read by [parse](super::parse).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedContactName {
+    pub family: String,
+    pub given: String,
+    pub additional: String,
+    pub prefix: String,
+    pub suffix: String,
+}
+
+impl ParsedContactName {
+    pub fn as_name(&self) -> ContactName<'_> {
+        ContactName {
+            family: &self.family,
+            given: &self.given,
+            additional: &self.additional,
+            prefix: &self.prefix,
+            suffix: &self.suffix,
+        }
+    }
+}
+
+/// Owned [Address] read by [parse](super::parse).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedAddress {
+    pub po_box: String,
+    pub extended: String,
+    pub street: String,
+    pub locality: String,
+    pub region: String,
+    pub postal_code: String,
+    pub country: String,
+}
+
+impl ParsedAddress {
+    pub fn as_address(&self) -> Address<'_> {
+        Address {
+            po_box: &self.po_box,
+            extended: &self.extended,
+            street: &self.street,
+            locality: &self.locality,
+            region: &self.region,
+            postal_code: &self.postal_code,
+            country: &self.country,
+        }
+    }
+
+    // Builds an address from its seven parts; missing trailing parts are left empty.
+    fn from_parts(parts: impl IntoIterator<Item = String>) -> Self {
+        let mut parts = parts.into_iter();
+        let mut next = || parts.next().unwrap_or_default();
+        Self {
+            po_box: next(),
+            extended: next(),
+            street: next(),
+            locality: next(),
+            region: next(),
+            postal_code: next(),
+            country: next(),
+        }
+    }
+}
+
+/// Owned [Photo] read by [parse](super::parse).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedPhoto {
+    pub media_type: String,
+    pub data: String,
+}
+
+/// Contact card read from a `BEGIN:VCARD` payload by [parse](super::parse).
+///
+/// vCard 2.1 cards are read as [VCardVersion::V3] in lenient mode. Properties without a matching
+/// builder field are skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedVCard {
+    pub version: VCardVersion,
+    pub name: ParsedContactName,
+    pub formatted_name: Option<String>,
+    pub phones: Vec<(PhoneType, String)>,
+    pub emails: Vec<(EmailType, String)>,
+    pub organization: Option<String>,
+    pub title: Option<String>,
+    pub address: Option<ParsedAddress>,
+    pub url: Option<String>,
+    pub note: Option<String>,
+    pub photo: Option<ParsedPhoto>,
+}
+
+impl ParsedVCard {
+    /// Returns a validated builder with the same fields
+    pub fn to_builder(&self) -> Result<VCard<'_>, PayloadError> {
+        let card = VCard {
+            version: self.version,
+            name: self.name.as_name(),
+            formatted_name: self.formatted_name.as_deref(),
+            phones: self
+                .phones
+                .iter()
+                .map(|(kind, number)| (*kind, number.as_str()))
+                .collect(),
+            emails: self
+                .emails
+                .iter()
+                .map(|(kind, email)| (*kind, email.as_str()))
+                .collect(),
+            organization: self.organization.as_deref(),
+            title: self.title.as_deref(),
+            address: self.address.as_ref().map(ParsedAddress::as_address),
+            url: self.url.as_deref(),
+            note: self.note.as_deref(),
+            photo: self.photo.as_ref().map(|photo| Photo {
+                media_type: &photo.media_type,
+                data: photo.data.clone(),
+            }),
+        };
+        card.validate()?;
+        Ok(card)
+    }
+
+    // Parses a whole card, starting with its `BEGIN:VCARD` line.
+    pub(super) fn parse(text: &str, mode: ParseMode) -> Result<Self, PayloadError> {
+        let strict = mode == ParseMode::Strict;
+        let unfolded = if strict {
+            text.replace("\r\n ", "").replace("\r\n\t", "")
+        } else {
+            text.replace("\r\n", "\n")
+                .replace("\n ", "")
+                .replace("\n\t", "")
+        };
+        let lines: Vec<&str> = if strict {
+            let Some(body) = unfolded.strip_suffix("\r\n") else {
+                return Err(invalid("vCard", "lines must end with CRLF"));
+            };
+            if body.split("\r\n").any(|line| line.contains('\n')) {
+                return Err(invalid("vCard", "lines must end with CRLF"));
+            }
+            body.split("\r\n").collect()
+        } else {
+            unfolded.lines().collect()
+        };
+
+        let mut card = Self {
+            version: VCardVersion::V3,
+            name: ParsedContactName::default(),
+            formatted_name: None,
+            phones: Vec::new(),
+            emails: Vec::new(),
+            organization: None,
+            title: None,
+            address: None,
+            url: None,
+            note: None,
+            photo: None,
+        };
+        let mut version = None;
+        let mut ended = false;
+        for line in lines.iter().skip(1).filter(|line| !line.is_empty()) {
+            if ended {
+                if strict {
+                    return Err(invalid("vCard", "cannot contain lines after END:VCARD"));
+                }
+                break;
+            }
+            let Some((name, value)) = line.split_once(':') else {
+                if strict {
+                    return Err(invalid("vCard", "lines must be written as NAME:value"));
+                }
+                continue;
+            };
+            let mut params = name.split(';');
+            let name = params.next().unwrap_or_default();
+            // Grouped properties such as `item1.TEL` belong to the named property
+            let name = name.rsplit('.').next().unwrap_or(name).to_ascii_uppercase();
+            let types = param_types(params.clone());
+
+            match name.as_str() {
+                "VERSION" => {
+                    version = Some(match value {
+                        "3.0" => VCardVersion::V3,
+                        "4.0" => VCardVersion::V4,
+                        "2.1" if !strict => VCardVersion::V3,
+                        _ => return Err(invalid("version", "must be 3.0 or 4.0")),
+                    })
+                }
+                "N" => {
+                    let parts = split_unescaped(value, ';');
+                    if strict && parts.len() != 5 {
+                        return Err(invalid("name", "must have five components"));
+                    }
+                    let mut parts = parts.into_iter().map(unescape_text);
+                    let mut next = || parts.next().unwrap_or_default();
+                    card.name = ParsedContactName {
+                        family: next(),
+                        given: next(),
+                        additional: next(),
+                        prefix: next(),
+                        suffix: next(),
+                    };
+                }
+                "FN" => card.formatted_name = Some(unescape_text(value)),
+                "ORG" => card.organization = Some(unescape_text(value)),
+                "TITLE" => card.title = Some(unescape_text(value)),
+                "NOTE" => card.note = Some(unescape_text(value)),
+                "URL" => card.url = Some(value.to_string()),
+                "TEL" => {
+                    let kind = PhoneType::ALL
+                        .into_iter()
+                        .find(|kind| types.iter().any(|t| t == kind.as_str()))
+                        .unwrap_or(PhoneType::Voice);
+                    let number = strip_prefix_ignore_case(value, "tel:").unwrap_or(value);
+                    card.phones.push((kind, number.to_string()));
+                }
+                "EMAIL" => {
+                    let kind = if types.iter().any(|t| t == "work") {
+                        EmailType::Work
+                    } else if types.iter().any(|t| t == "home") {
+                        EmailType::Home
+                    } else {
+                        EmailType::Internet
+                    };
+                    card.emails.push((kind, value.to_string()));
+                }
+                "ADR" => {
+                    let parts = split_unescaped(value, ';');
+                    if strict && parts.len() != 7 {
+                        return Err(invalid("address", "must have seven components"));
+                    }
+                    card.address = Some(ParsedAddress::from_parts(
+                        parts.into_iter().map(unescape_text),
+                    ));
+                }
+                "PHOTO" => card.photo = parse_photo(params, value),
+                "END" => {
+                    if !value.eq_ignore_ascii_case("VCARD") {
+                        return Err(invalid("vCard", "must end with END:VCARD"));
+                    }
+                    ended = true;
+                }
+                _ => {}
+            }
+        }
+
+        if strict {
+            if !ended {
+                return Err(invalid("vCard", "must end with END:VCARD"));
+            }
+            card.version = version.ok_or(PayloadError::MissingField("version"))?;
+            card.to_builder()?;
+        } else {
+            card.version = version.unwrap_or(VCardVersion::V3);
+        }
+        Ok(card)
+    }
+}
+
+/// Contact card read from a `MECARD:` payload by [parse](super::parse).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedMeCard {
+    pub family_name: String,
+    pub given_name: String,
+    pub nickname: Option<String>,
+    pub phones: Vec<String>,
+    pub emails: Vec<String>,
+    pub organization: Option<String>,
+    pub address: Option<ParsedAddress>,
+    pub url: Option<String>,
+    pub note: Option<String>,
+    pub birthday: Option<String>,
+}
+
+impl ParsedMeCard {
+    /// Returns a validated builder with the same fields
+    pub fn to_builder(&self) -> Result<MeCard<'_>, PayloadError> {
+        let card = MeCard {
+            family_name: &self.family_name,
+            given_name: &self.given_name,
+            nickname: self.nickname.as_deref(),
+            phones: self.phones.iter().map(String::as_str).collect(),
+            emails: self.emails.iter().map(String::as_str).collect(),
+            organization: self.organization.as_deref(),
+            address: self.address.as_ref().map(ParsedAddress::as_address),
+            url: self.url.as_deref(),
+            note: self.note.as_deref(),
+            birthday: self.birthday.as_deref(),
+        };
+        card.validate()?;
+        Ok(card)
+    }
+
+    // Parses the fields following `MECARD:`.
+    pub(super) fn parse(body: &str, mode: ParseMode) -> Result<Self, PayloadError> {
+        let strict = mode == ParseMode::Strict;
+        let mut fields = split_unescaped(body, ';');
+        if strict {
+            if !body.ends_with(";;") || fields.iter().rev().skip(2).any(|f| f.is_empty()) {
+                return Err(invalid("payload", "fields must end with ';;'"));
+            }
+            fields.truncate(fields.len() - 2);
+        }
+
+        let mut card = Self {
+            family_name: String::new(),
+            given_name: String::new(),
+            nickname: None,
+            phones: Vec::new(),
+            emails: Vec::new(),
+            organization: None,
+            address: None,
+            url: None,
+            note: None,
+            birthday: None,
+        };
+        let value =
+            |field: &'static str, raw: &str| unescape(field, raw, MECARD_SPECIAL_CHARS, mode);
+        for field in fields.into_iter().filter(|field| !field.is_empty()) {
+            let Some((key, raw)) = field.split_once(':') else {
+                if strict {
+                    return Err(invalid("payload", "fields must be written as KEY:value"));
+                }
+                continue;
+            };
+            let key = if strict {
+                key.to_string()
+            } else {
+                key.to_ascii_uppercase()
+            };
+            match key.as_str() {
+                "N" => {
+                    let parts = split_unescaped(raw, ',');
+                    if strict && parts.len() > 2 {
+                        return Err(invalid("name", "contains an unescaped ','"));
+                    }
+                    card.family_name = value("name", parts[0])?;
+                    card.given_name = match parts.get(1) {
+                        Some(given) => value("name", given)?,
+                        None => String::new(),
+                    };
+                }
+                "NICKNAME" => card.nickname = Some(value("nickname", raw)?),
+                "TEL" => card.phones.push(value("phone", raw)?),
+                "EMAIL" => card.emails.push(value("email", raw)?),
+                "ORG" => card.organization = Some(value("organization", raw)?),
+                "URL" => card.url = Some(value("URL", raw)?),
+                "NOTE" => card.note = Some(value("note", raw)?),
+                "BDAY" => card.birthday = Some(value("birthday", raw)?),
+                "ADR" => {
+                    let parts = split_unescaped(raw, ',');
+                    card.address = Some(match parts.len() {
+                        7 => ParsedAddress::from_parts(
+                            parts
+                                .into_iter()
+                                .map(|part| value("address", part))
+                                .collect::<Result<Vec<_>, _>>()?,
+                        ),
+                        _ if strict => {
+                            return Err(invalid("address", "must have seven components"));
+                        }
+                        // Free-form addresses from other generators
+                        _ => ParsedAddress {
+                            street: unescape("address", raw, &[], mode)?,
+                            ..ParsedAddress::default()
+                        },
+                    });
+                }
+                _ if strict => return Err(invalid("payload", "contains an unknown field")),
+                _ => {}
+            }
+        }
+
+        if strict {
+            card.to_builder()?;
+        } else if card.family_name.is_empty() && card.given_name.is_empty() {
+            return Err(PayloadError::MissingField("name"));
+        }
+        Ok(card)
+    }
+}
+
+// Collects the lowercase `TYPE` values of a vCard property, including the bare types of vCard 2.1.
+fn param_types<'a>(params: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut types = Vec::new();
+    for param in params {
+        let values = match param.split_once('=') {
+            Some((name, values)) if name.eq_ignore_ascii_case("TYPE") => values,
+            Some(_) => continue,
+            None => param,
+        };
+        types.extend(
+            values
+                .trim_matches('"')
+                .split(',')
+                .map(str::to_ascii_lowercase),
+        );
+    }
+    types
+}
+
+// Reads an embedded photo: a `data:` URI in vCard 4.0, or base64 with an image type in vCard 3.0.
+// Photos referenced by URL are skipped.
+fn parse_photo<'a>(
+    params: impl Iterator<Item = &'a str> + Clone,
+    value: &str,
+) -> Option<ParsedPhoto> {
+    if let Some(uri) = strip_prefix_ignore_case(value, "data:") {
+        let (media_type, data) = uri.split_once(";base64,")?;
+        return Some(ParsedPhoto {
+            media_type: media_type.to_ascii_lowercase(),
+            data: data.to_string(),
+        });
+    }
+    let encoded = params.clone().any(|param| {
+        param.eq_ignore_ascii_case("ENCODING=b") || param.eq_ignore_ascii_case("ENCODING=BASE64")
+    });
+    let subtype = params
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.eq_ignore_ascii_case("TYPE"))
+        .map(|(_, subtype)| subtype.to_ascii_lowercase())?;
+    encoded.then(|| ParsedPhoto {
+        media_type: format!("image/{}", subtype),
+        data: value.to_string(),
+    })
+}
+
+fn validate_url(url: &str) -> Result<(), PayloadError> {
+    if url.is_empty() || url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(invalid("URL", "cannot be empty or contain whitespace"));
+    }
+    Ok(())
+}
+
+fn validate_birthday(birthday: &str) -> Result<(), PayloadError> {
+    let valid = birthday.len() == 8
+        && birthday.bytes().all(|b| b.is_ascii_digit())
+        && (1..=12).contains(&birthday[4..6].parse::<u8>().unwrap_or(0))
+        && (1..=31).contains(&birthday[6..8].parse::<u8>().unwrap_or(0));
+    if !valid {
+        return Err(invalid("birthday", "must be a date formatted as YYYYMMDD"));
+    }
+    Ok(())
+}
+
+// Escapes a MeCard value; line breaks are not allowed, so they become spaces.
+fn escape_mecard(value: &str) -> String {
+    escape(
+        &value.replace("\r\n", " ").replace(['\r', '\n'], " "),
+        MECARD_SPECIAL_CHARS,
+    )
+}
+
+fn join_components(parts: &[&str], separator: char, escape: fn(&str) -> String) -> String {
+    parts
+        .iter()
+        .map(|part| escape(part))
+        .collect::<Vec<_>>()
+        .join(&separator.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qrcode::{QrCodeEcc, Version};
+
+    #[test]
+    fn test_vcard_v4_fields_and_escaping() {
+        let card = VCard::new(VCardVersion::V4)
+            .with_name_parts(ContactName {
+                family: "Doe",
+                given: "Jane",
+                additional: "Q.",
+                prefix: "Dr.",
+                suffix: "",
+            })
+            .with_title("Head of R;D")
+            .with_phone(PhoneType::Work, "+1 555 0100")
+            .unwrap()
+            .with_phone(PhoneType::Cell, "+1 555 0199")
+            .unwrap()
+            .with_email(EmailType::Internet, "jane@example.com")
+            .unwrap()
+            .with_address(Address {
+                street: "1 Main St, Suite 2",
+                locality: "Springfield",
+                country: "USA",
+                ..Default::default()
+            })
+            .with_note("Line one\nLine two")
+            .with_photo(&[1, 2, 3], "image/png")
+            .unwrap();
+        assert_eq!(
+            card.to_payload().unwrap(),
+            "BEGIN:VCARD\r\nVERSION:4.0\r\nN:Doe;Jane;Q.;Dr.;\r\nFN:Dr. Jane Q. Doe\r\n\
+             TITLE:Head of R\\;D\r\nTEL;VALUE=uri;TYPE=work:tel:+1-555-0100\r\n\
+             TEL;VALUE=uri;TYPE=cell:tel:+1-555-0199\r\nEMAIL:jane@example.com\r\n\
+             ADR:;;1 Main St\\, Suite 2;Springfield;;;USA\r\nNOTE:Line one\\nLine two\r\n\
+             PHOTO:data:image/png;base64,AQID\r\nEND:VCARD\r\n"
+        );
+
+        let v3 = VCard {
+            version: VCardVersion::V3,
+            ..card
+        };
+        assert!(v3
+            .to_payload()
+            .unwrap()
+            .contains("\r\nPHOTO;ENCODING=b;TYPE=PNG:AQID\r\n"));
+    }
+
+    #[test]
+    fn test_vcard_folds_long_lines() {
+        let note = "é".repeat(60);
+        let card = VCard::new(VCardVersion::V3)
+            .with_formatted_name("Jane")
+            .with_note(&note);
+        let payload = card.to_payload().unwrap();
+        for line in payload.split("\r\n") {
+            assert!(line.len() <= 75);
+        }
+        let unfolded = payload.replace("\r\n ", "");
+        assert!(unfolded.contains(&format!("NOTE:{}\r\n", note)));
+    }
+
+    #[test]
+    fn test_vcard_validation() {
+        assert!(matches!(
+            VCard::new(VCardVersion::V3).validate(),
+            Err(PayloadError::MissingField("name"))
+        ));
+        let card = VCard::new(VCardVersion::V3).with_name("Doe", "");
+        assert!(card.clone().with_phone(PhoneType::Cell, "call me").is_err());
+        assert!(card.clone().with_email(EmailType::Work, "jane").is_err());
+        assert!(card.clone().with_url("https://a b").is_err());
+        assert!(card.with_photo(&[1], "text/plain").is_err());
+    }
+
+    #[test]
+    fn test_mecard() {
+        let card = MeCard::new("Doe", "Jane")
+            .unwrap()
+            .with_nickname("JD")
+            .with_phone("+1 555 0100")
+            .unwrap()
+            .with_phone("+1 555 0199")
+            .unwrap()
+            .with_organization("Example; Inc.")
+            .with_note("Hi\nthere")
+            .with_birthday("19900131")
+            .unwrap()
+            .with_address(Address {
+                street: "1 Main St",
+                locality: "Springfield",
+                ..Default::default()
+            });
+        assert_eq!(
+            card.to_payload().unwrap(),
+            "MECARD:N:Doe,Jane;NICKNAME:JD;TEL:+1 555 0100;TEL:+1 555 0199;\
+             ORG:Example\\; Inc.;NOTE:Hi there;BDAY:19900131;ADR:,,1 Main St,Springfield,,,;;"
+        );
+        assert!(MeCard::new("", "").is_err());
+        assert!(card.with_birthday("1990-01-31").is_err());
+        assert_eq!(
+            MeCard::new("Doe", "").unwrap().to_payload().unwrap(),
+            "MECARD:N:Doe;;"
+        );
+        // A trailing escaped comma stays intact
+        assert_eq!(
+            MeCard::new("Doe,", "").unwrap().to_payload().unwrap(),
+            "MECARD:N:Doe\\,;;"
+        );
+    }
+
+    #[test]
+    fn test_size_estimate() {
+        let card = VCard::new(VCardVersion::V3).with_name("Doe", "Jane");
+        let estimate = card.estimate_size().unwrap();
+        assert_eq!(estimate.bytes, card.to_payload().unwrap().len());
+        let low = estimate.version_for(QrCodeEcc::Low).unwrap();
+        let high = estimate.version_for(QrCodeEcc::High).unwrap();
+        assert!(low <= high);
+        assert_eq!(
+            estimate.strongest_ecc(Version::MAX),
+            Some((QrCodeEcc::High, high))
+        );
+
+        let photo = card.with_photo(&[0u8; 4000], "image/jpeg").unwrap();
+        let estimate = photo.estimate_size().unwrap();
+        assert_eq!(estimate.version_for(QrCodeEcc::Low), None);
+        assert_eq!(estimate.strongest_ecc(Version::MAX), None);
+    }
+}
@@ -0,0 +1,933 @@
+//! Cryptocurrency payment URIs: BIP21 `bitcoin:`, BOLT11 `lightning:` and EIP-681 `ethereum:`.
+//!
+//! Bech32 addresses and invoices are case-insensitive, so they are written in uppercase and fit in
+//! alphanumeric segments, which take about half the space of byte segments.
+use super::{invalid, percent_encode, PayloadError, QrPayload};
+
+const BECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32_CONST: u32 = 1;
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+// Base58 version bytes of mainnet and testnet P2PKH and P2SH addresses.
+const BASE58_VERSIONS: [u8; 4] = [0x00, 0x05, 0x6f, 0xc4];
+const SATS_PER_BTC: u64 = 100_000_000;
+const MAX_SATS: u64 = 21_000_000 * SATS_PER_BTC;
+
+/// Builder for a BIP21 `bitcoin:` payment URI.
+///
+/// Bech32 (SegWit and Taproot) addresses are checked against their checksum and upper-cased;
+/// Base58 addresses are checked against their double SHA-256 checksum and kept as-is because they
+/// are case-sensitive.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::payload::{Bitcoin, QrPayload};
+///
+/// let payment = Bitcoin::new("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4").unwrap()
+///     .with_amount_sats(50_000).unwrap()
+///     .with_label("Coffee Shop");
+/// assert_eq!(
+///     payment.to_payload().unwrap(),
+///     "BITCOIN:BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4?amount=0.0005&label=Coffee%20Shop"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Bitcoin<'a> {
+    pub address: &'a str,
+    pub amount_sats: Option<u64>,
+    /// Name of the recipient
+    pub label: Option<&'a str>,
+    /// Note describing the payment
+    pub message: Option<&'a str>,
+    /// BOLT11 invoice offered as a Lightning alternative (unified QR)
+    pub lightning: Option<&'a str>,
+}
+
+impl<'a> Bitcoin<'a> {
+    pub fn new(address: &'a str) -> Result<Self, PayloadError> {
+        validate_bitcoin_address(address)?;
+        Ok(Self {
+            address,
+            amount_sats: None,
+            label: None,
+            message: None,
+            lightning: None,
+        })
+    }
+
+    /// Sets the amount in satoshis; it is written in BTC
+    pub fn with_amount_sats(mut self, sats: u64) -> Result<Self, PayloadError> {
+        validate_sats(sats)?;
+        self.amount_sats = Some(sats);
+        Ok(self)
+    }
+
+    pub fn with_label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn with_message(mut self, message: &'a str) -> Self {
+        self.message = Some(message);
+        self
+    }
+
+    pub fn with_lightning(mut self, invoice: &'a str) -> Result<Self, PayloadError> {
+        validate_lightning_invoice(invoice)?;
+        self.lightning = Some(invoice);
+        Ok(self)
+    }
+
+    /// Validates the fields before use
+    pub fn validate(&self) -> Result<(), PayloadError> {
+        validate_bitcoin_address(self.address)?;
+        if let Some(sats) = self.amount_sats {
+            validate_sats(sats)?;
+        }
+        if let Some(invoice) = self.lightning {
+            validate_lightning_invoice(invoice)?;
+        }
+        Ok(())
+    }
+}
+
+impl QrPayload for Bitcoin<'_> {
+    fn to_payload(&self) -> Result<String, PayloadError> {
+        self.validate()?;
+
+        let mut payload = if is_bech32(self.address) {
+            format!("BITCOIN:{}", self.address.to_ascii_uppercase())
+        } else {
+            format!("bitcoin:{}", self.address)
+        };
+        let mut params = Vec::new();
+        if let Some(sats) = self.amount_sats {
+            params.push(format!("amount={}", format_btc(sats)));
+        }
+        if let Some(label) = self.label {
+            params.push(format!("label={}", percent_encode(label, "")));
+        }
+        if let Some(message) = self.message {
+            params.push(format!("message={}", percent_encode(message, "")));
+        }
+        if let Some(invoice) = self.lightning {
+            params.push(format!(
+                "lightning={}",
+                strip_lightning_scheme(invoice).to_ascii_uppercase()
+            ));
+        }
+        if !params.is_empty() {
+            payload.push('?');
+            payload.push_str(&params.join("&"));
+        }
+        Ok(payload)
+    }
+}
+
+/// Builder for a `lightning:` payment request holding a BOLT11 invoice or LNURL.
+///
+/// The bech32 checksum is verified and the invoice is upper-cased for alphanumeric encoding.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::payload::{Lightning, QrPayload};
+///
+/// let invoice = "lnbc2500u15feryxhrdz9m6y09mr8wrerwzgj6f8ntxvhxyrdlm5aahn0t09nl4ujy8624fl6zy54\
+///                klay937yrnujckpak28lrdjg0eel92ue3gm36kcwf9tfwwqltsjqfgn6v79";
+/// let payload = Lightning::new(invoice).unwrap().to_payload().unwrap();
+/// assert!(payload.starts_with("LIGHTNING:LNBC2500U1"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Lightning<'a> {
+    /// Invoice, with or without a `lightning:` prefix
+    pub invoice: &'a str,
+}
+
+impl<'a> Lightning<'a> {
+    pub fn new(invoice: &'a str) -> Result<Self, PayloadError> {
+        validate_lightning_invoice(invoice)?;
+        Ok(Self { invoice })
+    }
+}
+
+impl QrPayload for Lightning<'_> {
+    fn to_payload(&self) -> Result<String, PayloadError> {
+        validate_lightning_invoice(self.invoice)?;
+        Ok(format!(
+            "LIGHTNING:{}",
+            strip_lightning_scheme(self.invoice).to_ascii_uppercase()
+        ))
+    }
+}
+
+/// ERC-20 token transfer requested by an [Ethereum] payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenTransfer<'a> {
+    /// Address receiving the tokens
+    pub recipient: &'a str,
+    /// Amount in the token's smallest unit
+    pub amount: u128,
+}
+
+/// Builder for an EIP-681 `ethereum:` payment request.
+///
+/// Addresses must be `0x` followed by 40 hex digits. Mixed-case addresses are checked against
+/// their EIP-55 checksum.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::payload::{Ethereum, QrPayload};
+///
+/// let payment = Ethereum::new("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").unwrap()
+///     .with_chain_id(1).unwrap()
+///     .with_value_wei(1_000_000_000_000_000).unwrap();
+/// assert_eq!(
+///     payment.to_payload().unwrap(),
+///     "ethereum:0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed@1?value=1000000000000000"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Ethereum<'a> {
+    /// Recipient, or the token contract for a token transfer
+    pub address: &'a str,
+    pub chain_id: Option<u64>,
+    /// Amount of ether in wei
+    pub value_wei: Option<u128>,
+    pub gas_limit: Option<u64>,
+    pub transfer: Option<TokenTransfer<'a>>,
+}
+
+impl<'a> Ethereum<'a> {
+    pub fn new(address: &'a str) -> Result<Self, PayloadError> {
+        validate_ethereum_address("address", address)?;
+        Ok(Self {
+            address,
+            chain_id: None,
+            value_wei: None,
+            gas_limit: None,
+            transfer: None,
+        })
+    }
+
+    /// Creates a request to transfer `amount` of the ERC-20 token at `token` to `recipient`
+    pub fn token_transfer(
+        token: &'a str,
+        recipient: &'a str,
+        amount: u128,
+    ) -> Result<Self, PayloadError> {
+        let mut request = Self::new(token)?;
+        request.transfer = Some(TokenTransfer { recipient, amount });
+        request.validate()?;
+        Ok(request)
+    }
+
+    pub fn with_chain_id(mut self, chain_id: u64) -> Result<Self, PayloadError> {
+        self.chain_id = Some(chain_id);
+        self.validate()?;
+        Ok(self)
+    }
+
+    pub fn with_value_wei(mut self, wei: u128) -> Result<Self, PayloadError> {
+        self.value_wei = Some(wei);
+        self.validate()?;
+        Ok(self)
+    }
+
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = Some(gas_limit);
+        self
+    }
+
+    /// Validates the fields before use
+    pub fn validate(&self) -> Result<(), PayloadError> {
+        validate_ethereum_address("address", self.address)?;
+        if self.chain_id == Some(0) {
+            return Err(invalid("chain_id", "must be positive"));
+        }
+        if let Some(transfer) = self.transfer {
+            validate_ethereum_address("recipient", transfer.recipient)?;
+            if self.value_wei.is_some() {
+                return Err(invalid("value", "cannot be combined with a token transfer"));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl QrPayload for Ethereum<'_> {
+    fn to_payload(&self) -> Result<String, PayloadError> {
+        self.validate()?;
+
+        let mut payload = format!("ethereum:{}", self.address);
+        if let Some(chain_id) = self.chain_id {
+            payload.push_str(&format!("@{}", chain_id));
+        }
+        let mut params = Vec::new();
+        if let Some(transfer) = self.transfer {
+            payload.push_str("/transfer");
+            params.push(format!("address={}", transfer.recipient));
+            params.push(format!("uint256={}", transfer.amount));
+        }
+        if let Some(wei) = self.value_wei {
+            params.push(format!("value={}", wei));
+        }
+        if let Some(gas_limit) = self.gas_limit {
+            params.push(format!("gas={}", gas_limit));
+        }
+        if !params.is_empty() {
+            payload.push('?');
+            payload.push_str(&params.join("&"));
+        }
+        Ok(payload)
+    }
+}
+
+fn validate_sats(sats: u64) -> Result<(), PayloadError> {
+    if sats == 0 || sats > MAX_SATS {
+        return Err(invalid(
+            "amount",
+            "must be between 1 satoshi and 21 million BTC",
+        ));
+    }
+    Ok(())
+}
+
+// Formats satoshis as BTC without trailing zeros.
+fn format_btc(sats: u64) -> String {
+    let fraction = format!("{:08}", sats % SATS_PER_BTC);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        (sats / SATS_PER_BTC).to_string()
+    } else {
+        format!("{}.{}", sats / SATS_PER_BTC, fraction)
+    }
+}
+
+fn is_bech32(address: &str) -> bool {
+    let lower = address.to_ascii_lowercase();
+    ["bc1", "tb1", "bcrt1"]
+        .iter()
+        .any(|prefix| lower.starts_with(prefix))
+}
+
+fn validate_bitcoin_address(address: &str) -> Result<(), PayloadError> {
+    let valid = if is_bech32(address) {
+        valid_segwit_address(address)
+    } else {
+        base58check_decode(address)
+            .is_some_and(|data| data.len() == 21 && BASE58_VERSIONS.contains(&data[0]))
+    };
+    if !valid {
+        return Err(invalid(
+            "address",
+            "must be a valid Base58 or bech32 Bitcoin address",
+        ));
+    }
+    Ok(())
+}
+
+// Checks a SegWit address against BIP 173 (version 0) and BIP 350 (version 1 and later).
+fn valid_segwit_address(address: &str) -> bool {
+    if address.len() > 90 {
+        return false;
+    }
+    let Some((_, data, constant)) = bech32_decode(address) else {
+        return false;
+    };
+    let Some((&version, program)) = data.split_first() else {
+        return false;
+    };
+    let Some(program) = convert_bits(program) else {
+        return false;
+    };
+    match version {
+        0 => constant == BECH32_CONST && matches!(program.len(), 20 | 32),
+        1..=16 => constant == BECH32M_CONST && (2..=40).contains(&program.len()),
+        _ => false,
+    }
+}
+
+fn strip_lightning_scheme(invoice: &str) -> &str {
+    match invoice.get(..10) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("lightning:") => &invoice[10..],
+        _ => invoice,
+    }
+}
+
+fn validate_lightning_invoice(invoice: &str) -> Result<(), PayloadError> {
+    // Invoices are longer than the 90 characters allowed for addresses, so the length is not capped
+    let valid = bech32_decode(strip_lightning_scheme(invoice))
+        .is_some_and(|(hrp, _, constant)| hrp.starts_with("ln") && constant == BECH32_CONST);
+    if !valid {
+        return Err(invalid(
+            "invoice",
+            "must be a bech32 Lightning invoice or LNURL",
+        ));
+    }
+    Ok(())
+}
+
+fn validate_ethereum_address(field: &'static str, address: &str) -> Result<(), PayloadError> {
+    let hex = address
+        .strip_prefix("0x")
+        .filter(|hex| hex.len() == 40 && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+        .ok_or_else(|| invalid(field, "must be 0x followed by 40 hex digits"))?;
+    let mixed_case =
+        hex.bytes().any(|b| b.is_ascii_lowercase()) && hex.bytes().any(|b| b.is_ascii_uppercase());
+    if mixed_case && eip55_checksum(hex) != hex {
+        return Err(invalid(field, "has an invalid EIP-55 checksum"));
+    }
+    Ok(())
+}
+
+// Returns the hex digits of an address with the EIP-55 checksum casing.
+fn eip55_checksum(hex: &str) -> String {
+    let lower = hex.to_ascii_lowercase();
+    let hash = keccak256(lower.as_bytes());
+    lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+// Decodes a bech32 or bech32m string into its lowercase human-readable part, its data values
+// without the checksum, and the checksum constant, which identifies the variant.
+fn bech32_decode(text: &str) -> Option<(String, Vec<u8>, u32)> {
+    let has_lower = text.bytes().any(|b| b.is_ascii_lowercase());
+    let has_upper = text.bytes().any(|b| b.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return None;
+    }
+    let text = text.to_ascii_lowercase();
+    let separator = text.rfind('1')?;
+    let (hrp, data) = (&text[..separator], &text[separator + 1..]);
+    if hrp.is_empty() || data.len() < 6 || !hrp.bytes().all(|b| (33..=126).contains(&b)) {
+        return None;
+    }
+    let data = data
+        .bytes()
+        .map(|b| BECH32_CHARSET.iter().position(|&c| c == b).map(|i| i as u8))
+        .collect::<Option<Vec<u8>>>()?;
+
+    let mut values: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    values.push(0);
+    values.extend(hrp.bytes().map(|b| b & 0x1f));
+    values.extend(&data);
+    let constant = bech32_polymod(&values);
+    Some((hrp.to_string(), data[..data.len() - 6].to_vec(), constant))
+}
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+    let mut checksum = 1u32;
+    for &value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x01ff_ffff) << 5) ^ u32::from(value);
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+// Regroups 5-bit values into bytes, rejecting non-zero or overlong padding.
+fn convert_bits(values: &[u8]) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(values.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for &value in values {
+        buffer = (buffer << 5) | u32::from(value);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    if bits >= 5 || buffer & ((1 << bits) - 1) != 0 {
+        return None;
+    }
+    Some(bytes)
+}
+
+// Decodes a Base58Check string and returns the data without its 4-byte checksum.
+fn base58check_decode(text: &str) -> Option<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in text.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|&a| a == c)? as u32;
+        for byte in bytes.iter_mut().rev() {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+    let zeros = text.bytes().take_while(|&c| c == b'1').count();
+    let mut decoded = vec![0u8; zeros];
+    decoded.extend(bytes);
+
+    let split = decoded.len().checked_sub(4)?;
+    let (data, checksum) = decoded.split_at(split);
+    if sha256(&sha256(data))[..4] != *checksum {
+        return None;
+    }
+    Some(data.to_vec())
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let choice = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(choice)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let majority = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(majority);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8; 32];
+    for (chunk, word) in digest.chunks_exact_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+// Keccak-256 as used by Ethereum, which pads differently from the standardized SHA3-256.
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    const RATE: usize = 136;
+    let mut state = [0u64; 25];
+
+    let mut message = data.to_vec();
+    message.push(0x01);
+    message.resize(message.len().div_ceil(RATE) * RATE, 0);
+    let last = message.len() - 1;
+    message[last] |= 0x80;
+
+    for block in message.chunks_exact(RATE) {
+        for (lane, word) in state.iter_mut().zip(block.chunks_exact(8)) {
+            *lane ^= u64::from_le_bytes(word.try_into().unwrap());
+        }
+        keccak_f(&mut state);
+    }
+
+    let mut digest = [0u8; 32];
+    for (chunk, lane) in digest.chunks_exact_mut(8).zip(state) {
+        chunk.copy_from_slice(&lane.to_le_bytes());
+    }
+    digest
+}
+
+fn keccak_f(state: &mut [u64; 25]) {
+    const ROUND_CONSTANTS: [u64; 24] = [
+        0x0000000000000001,
+        0x0000000000008082,
+        0x800000000000808a,
+        0x8000000080008000,
+        0x000000000000808b,
+        0x0000000080000001,
+        0x8000000080008081,
+        0x8000000000008009,
+        0x000000000000008a,
+        0x0000000000000088,
+        0x0000000080008009,
+        0x000000008000000a,
+        0x000000008000808b,
+        0x800000000000008b,
+        0x8000000000008089,
+        0x8000000000008003,
+        0x8000000000008002,
+        0x8000000000000080,
+        0x000000000000800a,
+        0x800000008000000a,
+        0x8000000080008081,
+        0x8000000000008080,
+        0x0000000080000001,
+        0x8000000080008008,
+    ];
+    const ROTATIONS: [u32; 24] = [
+        1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+    ];
+    const PI: [usize; 24] = [
+        10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+    ];
+
+    for round_constant in ROUND_CONSTANTS {
+        // Theta
+        let mut columns = [0u64; 5];
+        for (x, column) in columns.iter_mut().enumerate() {
+            *column = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let d = columns[(x + 4) % 5] ^ columns[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+        // Rho and pi
+        let mut current = state[1];
+        for (&target, &rotation) in PI.iter().zip(&ROTATIONS) {
+            let next = state[target];
+            state[target] = current.rotate_left(rotation);
+            current = next;
+        }
+        // Chi
+        for y in 0..5 {
+            let row: [u64; 5] = state[5 * y..5 * y + 5].try_into().unwrap();
+            for x in 0..5 {
+                state[5 * y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+        // Iota
+        state[0] ^= round_constant;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload::encode_segmented;
+    use crate::qrcode::{QrCodeEcc, Version};
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_hashes() {
+        assert_eq!(
+            hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex(&sha256(&[b'a'; 1000])),
+            "41edece42d63e8d9bf515a9ba6932e1c20cbc9f5a5d134645adb5db1b9737ea3"
+        );
+        assert_eq!(
+            hex(&sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(&sha256(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        assert_eq!(
+            hex(&keccak256(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hex(&keccak256(b"abc")),
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+        );
+    }
+
+    // Checksum test vectors from BIP 173 (bech32) and BIP 350 (bech32m). The overall length
+    // limit is only enforced for addresses, so the vectors exceeding it are not listed.
+    #[test]
+    fn test_bech32_checksum_vectors() {
+        let bech32 = [
+            "A12UEL5L",
+            "a12uel5l",
+            "an83characterlonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1tt5tgs",
+            "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw",
+            "11qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqc8247j",
+            "split1checkupstagehandshakeupstreamerranterredcaperred2y9e3w",
+            "?1ezyfcl",
+        ];
+        let bech32m = [
+            "A1LQFN3A",
+            "a1lqfn3a",
+            "an83characterlonghumanreadablepartthatcontainsthetheexcludedcharactersbioandnumber11sg7hg6",
+            "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx",
+            "11llllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllllludsr8",
+            "split1checkupstagehandshakeupstreamerranterredcaperredlc445v",
+            "?1v759aa",
+        ];
+        let invalid_bech32 = [
+            "\u{20}1nwldj5",
+            "\u{7f}1axkwrx",
+            "\u{80}1eym55h",
+            "pzry9x0s0muk",
+            "1pzry9x0s0muk",
+            "x1b4n0q5v",
+            "li1dgmt3",
+            "de1lg7wt\u{ff}",
+            "A1G7SGD8",
+            "10a06t8",
+            "1qzzfhee",
+        ];
+        let invalid_bech32m = [
+            "\u{20}1xj0phk",
+            "\u{7f}1g6xzxy",
+            "\u{80}1vctc34",
+            "qyrz8wqd2c9m",
+            "1qyrz8wqd2c9m",
+            "y1b0jsk6g",
+            "lt1igcx5c0",
+            "in1muywd",
+            "mm1crxm3i",
+            "au1s5cgom",
+            "M1VUXWEZ",
+            "16plkw9",
+            "1p2gdwpf",
+        ];
+        let constant = |text: &str| bech32_decode(text).map(|(_, _, constant)| constant);
+        for text in bech32 {
+            assert_eq!(constant(text), Some(BECH32_CONST), "{}", text);
+        }
+        for text in bech32m {
+            assert_eq!(constant(text), Some(BECH32M_CONST), "{}", text);
+        }
+        for text in invalid_bech32 {
+            assert_ne!(constant(text), Some(BECH32_CONST), "{}", text);
+        }
+        for text in invalid_bech32m {
+            assert_ne!(constant(text), Some(BECH32M_CONST), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_base58check_vectors() {
+        assert_eq!(
+            base58check_decode("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa").map(|data| hex(&data)),
+            Some("0062e907b15cbf27d5425399ebf6f0fb50ebb88f18".to_string())
+        );
+        assert_eq!(
+            base58check_decode("5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ")
+                .map(|data| hex(&data)),
+            Some("800c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d".to_string())
+        );
+        for address in [
+            "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa",
+            "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy",
+            "mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn",
+            "2MzQwSSnBHWHqSAqtTVQ6v47XtaisrJa1Vc",
+        ] {
+            assert!(Bitcoin::new(address).is_ok(), "{}", address);
+        }
+        // Bad checksum, a character outside the alphabet, too short for a checksum, and a
+        // private key rather than an address
+        for text in [
+            "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb",
+            "1A1zP1eP5QGefi2DMPTfTL5SLmv7Divf0a",
+            "1111",
+            "",
+        ] {
+            assert_eq!(base58check_decode(text), None, "{}", text);
+        }
+        assert!(Bitcoin::new("5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ").is_err());
+    }
+
+    // Segwit address test vectors from BIP 350, which supersedes the BIP 173 ones for
+    // witness versions 1 to 16.
+    #[test]
+    fn test_segwit_address_vectors() {
+        for address in [
+            "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+            "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+            "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kt5nd6y",
+            "BC1SW50QGDZ25J",
+            "bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcs",
+            "tb1qqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesrxh6hy",
+            "tb1pqqqqp399et2xygdj5xreqhjjvcmzhxw4aywxecjdzew6hylgvsesf3hn0c",
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+        ] {
+            assert!(valid_segwit_address(address), "{}", address);
+        }
+        for address in [
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd",
+            "tb1z0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqglt7rf",
+            "BC1S0XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ54WELL",
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh",
+            "tb1q0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq24jc47",
+            "bc1p38j9r5y49hruaue7wxjce0updqjuyyx0kh56v8s25huc6995vvpql3jow4",
+            "BC130XLXVLHEMJA6C4DQV22UAPCTQUPFHLXM9H8Z3K2E72Q4K9HCZ7VQ7ZWS8R",
+            "bc1pw5dgrnzv",
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7v8n0nx0muaewav253zgeav",
+            "BC1QR508D6QEJXTDG4Y5R3ZARVARYV98GJ9P",
+            "tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq47Zagq",
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7v07qwwzcrf",
+            "tb1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vpggkg4j",
+            "bc1gmk9yu",
+        ] {
+            assert!(!valid_segwit_address(address), "{}", address);
+        }
+        // Valid checksum, but the human-readable part is not a Bitcoin network
+        assert!(
+            Bitcoin::new("tc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vq5zuyut").is_err()
+        );
+    }
+
+    #[test]
+    fn test_bitcoin_addresses() {
+        for address in [
+            "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2",
+            "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy",
+            "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+            "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+        ] {
+            assert!(Bitcoin::new(address).is_ok(), "{}", address);
+        }
+        for address in [
+            "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN3",
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5",
+            "bc1qw508d6qejxtdg4y5R3zarvary0c5xw7kv8f3t4",
+            // Taproot program with a bech32 instead of bech32m checksum
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd",
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        ] {
+            assert!(Bitcoin::new(address).is_err(), "{}", address);
+        }
+    }
+
+    #[test]
+    fn test_bitcoin_payload() {
+        let invoice =
+            "lightning:lnbc2500u15feryxhrdz9m6y09mr8wrerwzgj6f8ntxvhxyrdlm5aahn0t09nl4ujy\
+                       8624fl6zy54klay937yrnujckpak28lrdjg0eel92ue3gm36kcwf9tfwwqltsjqfgn6v79";
+        let payment = Bitcoin::new("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2")
+            .unwrap()
+            .with_amount_sats(2 * SATS_PER_BTC)
+            .unwrap()
+            .with_message("Order 42 & more")
+            .with_lightning(invoice)
+            .unwrap();
+        let payload = payment.to_payload().unwrap();
+        assert!(payload.starts_with(
+            "bitcoin:1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2?amount=2&message=Order%2042%20%26%20more\
+             &lightning=LNBC2500U1"
+        ));
+        assert_eq!(format_btc(1), "0.00000001");
+        assert!(payment.clone().with_amount_sats(0).is_err());
+        assert!(payment.with_amount_sats(MAX_SATS + 1).is_err());
+        assert!(Lightning::new("lnbc2500u1qqqqqqqqqqqq").is_err());
+    }
+
+    #[test]
+    fn test_uppercase_bech32_is_smaller() {
+        let address = "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0";
+        let payment = Bitcoin::new(address).unwrap();
+        let upper = payment.estimate_size().unwrap();
+        let upper = upper.version_for(QrCodeEcc::Medium).unwrap();
+
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let lower = encode_segmented(
+            &format!("bitcoin:{}", address),
+            &mut tempbuffer,
+            &mut outbuffer,
+            payment.encode_options(),
+        )
+        .unwrap();
+        assert!(upper < lower.version());
+    }
+
+    #[test]
+    fn test_ethereum() {
+        for address in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+            "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+        ] {
+            assert!(Ethereum::new(address).is_ok(), "{}", address);
+        }
+        assert!(Ethereum::new("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD").is_err());
+        assert!(Ethereum::new("5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").is_err());
+
+        let transfer = Ethereum::token_transfer(
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            2_500_000,
+        )
+        .unwrap()
+        .with_chain_id(137)
+        .unwrap()
+        .with_gas_limit(65_000);
+        assert_eq!(
+            transfer.to_payload().unwrap(),
+            "ethereum:0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB@137/transfer\
+             ?address=0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed&uint256=2500000&gas=65000"
+        );
+        assert!(transfer.clone().with_value_wei(1).is_err());
+        assert!(transfer.with_chain_id(0).is_err());
+    }
+}
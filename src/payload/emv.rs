@@ -0,0 +1,624 @@
+//! EMVCo Merchant-Presented Mode payment payloads, as used by QRIS, PromptPay, PIX and others.
+use super::{invalid, PayloadError, QrPayload};
+use std::collections::BTreeMap;
+
+const PAYLOAD_FORMAT_INDICATOR: u8 = 0;
+const POINT_OF_INITIATION: u8 = 1;
+const MERCHANT_CATEGORY_CODE: u8 = 52;
+const TRANSACTION_CURRENCY: u8 = 53;
+const TRANSACTION_AMOUNT: u8 = 54;
+const COUNTRY_CODE: u8 = 58;
+const MERCHANT_NAME: u8 = 59;
+const MERCHANT_CITY: u8 = 60;
+const POSTAL_CODE: u8 = 61;
+const ADDITIONAL_DATA: u8 = 62;
+const CRC: u8 = 63;
+const MAX_VALUE_CHARS: usize = 99;
+
+/// Whether a payment QR code is reused or generated per transaction (ID `01`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointOfInitiation {
+    /// `11`: the same code is shown for every transaction
+    Static,
+    /// `12`: a new code is generated for each transaction
+    Dynamic,
+}
+
+/// Value of an EMV data object: either text or a nested template of data objects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmvValue {
+    Text(String),
+    Template(EmvTemplate),
+}
+
+/// A set of EMV data objects keyed by their two-digit ID, such as a merchant account information
+/// or additional data field template.
+///
+/// Data objects are written in ascending ID order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmvTemplate {
+    fields: BTreeMap<u8, EmvValue>,
+}
+
+impl EmvTemplate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a text data object, replacing any previous value for the ID
+    pub fn with_field(mut self, id: u8, value: &str) -> Result<Self, PayloadError> {
+        validate_id(id)?;
+        validate_value(value)?;
+        self.fields.insert(id, EmvValue::Text(value.to_string()));
+        Ok(self)
+    }
+
+    /// Sets a nested template, replacing any previous value for the ID
+    pub fn with_template(mut self, id: u8, template: EmvTemplate) -> Result<Self, PayloadError> {
+        validate_id(id)?;
+        let len = template.encoded_len();
+        if len == 0 || len > MAX_VALUE_CHARS {
+            return Err(invalid(
+                "template",
+                format!("must encode to 1 to 99 characters, not {}", len),
+            ));
+        }
+        self.fields.insert(id, EmvValue::Template(template));
+        Ok(self)
+    }
+
+    pub fn get(&self, id: u8) -> Option<&EmvValue> {
+        self.fields.get(&id)
+    }
+
+    /// Returns the text of a data object, or `None` if it is missing or a template
+    pub fn text(&self, id: u8) -> Option<&str> {
+        match self.fields.get(&id) {
+            Some(EmvValue::Text(text)) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Returns a nested template, or `None` if it is missing or text
+    pub fn template(&self, id: u8) -> Option<&EmvTemplate> {
+        match self.fields.get(&id) {
+            Some(EmvValue::Template(template)) => Some(template),
+            _ => None,
+        }
+    }
+
+    /// Iterates over the data objects in ascending ID order
+    pub fn iter(&self) -> impl Iterator<Item = (u8, &EmvValue)> {
+        self.fields.iter().map(|(&id, value)| (id, value))
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.fields.values().map(|value| 4 + value_len(value)).sum()
+    }
+
+    fn write(&self, out: &mut String) {
+        for (id, value) in &self.fields {
+            out.push_str(&format!("{:02}{:02}", id, value_len(value)));
+            match value {
+                EmvValue::Text(text) => out.push_str(text),
+                EmvValue::Template(template) => template.write(out),
+            }
+        }
+    }
+}
+
+/// Builder and parser for an EMVCo Merchant-Presented Mode payload.
+///
+/// The payload is a list of ID-length-value data objects. The payload format indicator (`00`) is
+/// set automatically and the CRC (`63`) is computed when the payload is built and verified when it
+/// is parsed.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::payload::{Emv, EmvTemplate, PointOfInitiation, QrPayload};
+///
+/// let account = EmvTemplate::new()
+///     .with_field(0, "com.example.pay").unwrap()
+///     .with_field(1, "1234567890").unwrap();
+/// let additional = EmvTemplate::new().with_field(1, "INV-42").unwrap();
+/// let emv = Emv::new()
+///     .with_point_of_initiation(PointOfInitiation::Dynamic)
+///     .with_merchant_account(26, account).unwrap()
+///     .with_merchant_category_code("5812").unwrap()
+///     .with_currency("360").unwrap()
+///     .with_amount("15000").unwrap()
+///     .with_country_code("ID").unwrap()
+///     .with_merchant_name("Warung Kopi").unwrap()
+///     .with_merchant_city("Jakarta").unwrap()
+///     .with_additional_data(additional).unwrap();
+///
+/// let payload = emv.to_payload().unwrap();
+/// assert!(payload.starts_with("000201010212"));
+/// assert_eq!(Emv::parse(&payload).unwrap(), emv);
+/// ```
+///
+/// The payload text plugs into the image helpers, for example to add a logo. The [QrCode] returned
+/// by [QrPayload::encode] can likewise be passed to the deprecated `generate_frameqr_buffer`.
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "image")] {
+/// use qirust::helper::generate_image_buffer;
+/// use qirust::payload::{Emv, QrPayload};
+/// use qirust::qrcode::QrCodeEcc;
+/// use qirust::render::QrStyle;
+///
+/// fn save_with_logo(emv: &Emv) -> Result<(), Box<dyn std::error::Error>> {
+///     let style = QrStyle::new().with_ecc(QrCodeEcc::High).with_logo("logo.png")?;
+///     let img = generate_image_buffer(&emv.to_payload()?, style)?;
+///     img.save("payment.png")?;
+///     Ok(())
+/// }
+/// # }
+/// ```
+///
+/// [QrCode]: crate::qrcode::QrCode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Emv {
+    /// Top-level data objects, excluding the CRC
+    pub data: EmvTemplate,
+}
+
+impl Default for Emv {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Emv {
+    pub fn new() -> Self {
+        let mut data = EmvTemplate::new();
+        data.fields
+            .insert(PAYLOAD_FORMAT_INDICATOR, EmvValue::Text("01".to_string()));
+        Self { data }
+    }
+
+    pub fn with_point_of_initiation(mut self, point: PointOfInitiation) -> Self {
+        let value = match point {
+            PointOfInitiation::Static => "11",
+            PointOfInitiation::Dynamic => "12",
+        };
+        self.data
+            .fields
+            .insert(POINT_OF_INITIATION, EmvValue::Text(value.to_string()));
+        self
+    }
+
+    /// Sets a merchant account information template (IDs `26` to `51`)
+    pub fn with_merchant_account(
+        self,
+        id: u8,
+        template: EmvTemplate,
+    ) -> Result<Self, PayloadError> {
+        if !(26..=51).contains(&id) {
+            return Err(invalid(
+                "merchant account",
+                format!("template ID must be between 26 and 51, not {}", id),
+            ));
+        }
+        self.with_template(id, template)
+    }
+
+    /// Sets the four-digit ISO 18245 merchant category code
+    pub fn with_merchant_category_code(self, code: &str) -> Result<Self, PayloadError> {
+        validate_digits("merchant category code", code, 4)?;
+        self.with_field(MERCHANT_CATEGORY_CODE, code)
+    }
+
+    /// Sets the three-digit ISO 4217 numeric currency code, such as `360` for IDR
+    pub fn with_currency(self, currency: &str) -> Result<Self, PayloadError> {
+        validate_digits("currency", currency, 3)?;
+        self.with_field(TRANSACTION_CURRENCY, currency)
+    }
+
+    /// Sets the transaction amount, such as `15000` or `12.50`
+    pub fn with_amount(self, amount: &str) -> Result<Self, PayloadError> {
+        validate_amount(amount)?;
+        self.with_field(TRANSACTION_AMOUNT, amount)
+    }
+
+    /// Sets the two-letter ISO 3166-1 country code
+    pub fn with_country_code(self, country: &str) -> Result<Self, PayloadError> {
+        validate_country(country)?;
+        self.with_field(COUNTRY_CODE, country)
+    }
+
+    pub fn with_merchant_name(self, name: &str) -> Result<Self, PayloadError> {
+        validate_max_chars("merchant name", name, 25)?;
+        self.with_field(MERCHANT_NAME, name)
+    }
+
+    pub fn with_merchant_city(self, city: &str) -> Result<Self, PayloadError> {
+        validate_max_chars("merchant city", city, 15)?;
+        self.with_field(MERCHANT_CITY, city)
+    }
+
+    pub fn with_postal_code(self, postal_code: &str) -> Result<Self, PayloadError> {
+        validate_max_chars("postal code", postal_code, 10)?;
+        self.with_field(POSTAL_CODE, postal_code)
+    }
+
+    /// Sets the additional data field template (ID `62`), holding for example the bill number
+    /// (`01`), store label (`03`), reference label (`05`) or terminal label (`07`)
+    pub fn with_additional_data(self, template: EmvTemplate) -> Result<Self, PayloadError> {
+        self.with_template(ADDITIONAL_DATA, template)
+    }
+
+    /// Sets any top-level text data object
+    ///
+    /// The payload format indicator and CRC are managed by the builder and cannot be set.
+    /// Template IDs (`26` to `51`, `62`, `64` and `80` to `99`) must be set with
+    /// [`with_template`](Self::with_template).
+    pub fn with_field(mut self, id: u8, value: &str) -> Result<Self, PayloadError> {
+        validate_settable(id)?;
+        if is_template_id(None, id) {
+            return Err(invalid(
+                "ID",
+                format!("data object {:02} is a template; use with_template", id),
+            ));
+        }
+        self.data = self.data.with_field(id, value)?;
+        Ok(self)
+    }
+
+    /// Sets any top-level template
+    ///
+    /// Only template IDs (`26` to `51`, `62`, `64` and `80` to `99`) are accepted, so that the
+    /// payload parses back into the same structure.
+    pub fn with_template(mut self, id: u8, template: EmvTemplate) -> Result<Self, PayloadError> {
+        if !is_template_id(None, id) {
+            return Err(invalid(
+                "ID",
+                format!("data object {:02} is not a template; use with_field", id),
+            ));
+        }
+        self.data = self.data.with_template(id, template)?;
+        Ok(self)
+    }
+
+    /// Validates that the mandatory data objects are present and well-formed
+    pub fn validate(&self) -> Result<(), PayloadError> {
+        if self.data.text(PAYLOAD_FORMAT_INDICATOR) != Some("01") {
+            return Err(invalid(
+                "payload format indicator",
+                "must be 01".to_string(),
+            ));
+        }
+        if !self.data.fields.keys().any(|id| (2..=51).contains(id)) {
+            return Err(PayloadError::MissingField("merchant account information"));
+        }
+        let required = |id: u8, field: &'static str| {
+            self.data.text(id).ok_or(PayloadError::MissingField(field))
+        };
+        validate_digits(
+            "merchant category code",
+            required(MERCHANT_CATEGORY_CODE, "merchant category code")?,
+            4,
+        )?;
+        validate_digits("currency", required(TRANSACTION_CURRENCY, "currency")?, 3)?;
+        validate_country(required(COUNTRY_CODE, "country code")?)?;
+        validate_max_chars(
+            "merchant name",
+            required(MERCHANT_NAME, "merchant name")?,
+            25,
+        )?;
+        validate_max_chars(
+            "merchant city",
+            required(MERCHANT_CITY, "merchant city")?,
+            15,
+        )?;
+        if let Some(amount) = self.data.text(TRANSACTION_AMOUNT) {
+            validate_amount(amount)?;
+        }
+        Ok(())
+    }
+
+    /// Parses a payload, verifying its CRC and decoding nested templates
+    pub fn parse(payload: &str) -> Result<Self, PayloadError> {
+        let body_len = payload.len().saturating_sub(4);
+        let (body, checksum) = match (payload.get(..body_len), payload.get(body_len..)) {
+            (Some(body), Some(checksum)) if body.ends_with("6304") => (body, checksum),
+            _ => {
+                return Err(invalid(
+                    "CRC",
+                    "payload must end with the CRC data object".to_string(),
+                ))
+            }
+        };
+        let expected = format!("{:04X}", crc16(body.as_bytes()));
+        if !checksum.eq_ignore_ascii_case(&expected) {
+            return Err(invalid(
+                "CRC",
+                format!("is {} but the payload checksum is {}", checksum, expected),
+            ));
+        }
+
+        let data = parse_template(&body[..body.len() - 4], None)?;
+        if data.text(PAYLOAD_FORMAT_INDICATOR) != Some("01") {
+            return Err(invalid(
+                "payload format indicator",
+                "must be 01".to_string(),
+            ));
+        }
+        Ok(Self { data })
+    }
+}
+
+impl QrPayload for Emv {
+    fn to_payload(&self) -> Result<String, PayloadError> {
+        self.validate()?;
+
+        let mut payload = String::new();
+        self.data.write(&mut payload);
+        payload.push_str(&format!("{:02}04", CRC));
+        let crc = crc16(payload.as_bytes());
+        payload.push_str(&format!("{:04X}", crc));
+        Ok(payload)
+    }
+}
+
+// CRC-16/CCITT-FALSE (polynomial 0x1021, initial value 0xFFFF), as required by EMVCo.
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in data {
+        crc ^= u16::from(byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+// IDs whose values are nested templates: merchant account information, additional data, merchant
+// information in an alternate language and unreserved templates at the top level, and payment
+// system specific templates inside the additional data field.
+fn is_template_id(parent: Option<u8>, id: u8) -> bool {
+    match parent {
+        None => matches!(id, 26..=51 | 62 | 64 | 80..=99),
+        Some(ADDITIONAL_DATA) => id >= 50,
+        Some(_) => false,
+    }
+}
+
+// Lengths count characters, so the input is walked by character rather than by byte.
+fn parse_template(input: &str, parent: Option<u8>) -> Result<EmvTemplate, PayloadError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut template = EmvTemplate::new();
+    let mut pos = 0;
+    while pos < chars.len() {
+        let number = |start: usize| -> Result<u8, PayloadError> {
+            chars
+                .get(start..start + 2)
+                .map(|digits| digits.iter().collect::<String>())
+                .and_then(|digits| digits.parse::<u8>().ok())
+                .ok_or_else(|| {
+                    invalid("TLV", format!("expected two digits at character {}", start))
+                })
+        };
+        let id = number(pos)?;
+        let len = usize::from(number(pos + 2)?);
+        let value: String = chars
+            .get(pos + 4..pos + 4 + len)
+            .ok_or_else(|| {
+                invalid(
+                    "TLV",
+                    format!("data object {:02} is shorter than its length {}", id, len),
+                )
+            })?
+            .iter()
+            .collect();
+        if template.fields.contains_key(&id) {
+            return Err(invalid(
+                "TLV",
+                format!("data object {:02} appears more than once", id),
+            ));
+        }
+        template = if is_template_id(parent, id) {
+            template.with_template(id, parse_template(&value, Some(id))?)?
+        } else {
+            template.with_field(id, &value)?
+        };
+        pos += 4 + len;
+    }
+    Ok(template)
+}
+
+fn value_len(value: &EmvValue) -> usize {
+    match value {
+        EmvValue::Text(text) => text.chars().count(),
+        EmvValue::Template(template) => template.encoded_len(),
+    }
+}
+
+fn validate_id(id: u8) -> Result<(), PayloadError> {
+    if id > 99 {
+        return Err(invalid(
+            "ID",
+            format!("must be between 00 and 99, not {}", id),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_settable(id: u8) -> Result<(), PayloadError> {
+    if id == PAYLOAD_FORMAT_INDICATOR || id == CRC {
+        return Err(invalid(
+            "ID",
+            format!("data object {:02} is managed by the builder", id),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_value(value: &str) -> Result<(), PayloadError> {
+    let len = value.chars().count();
+    if len == 0 || len > MAX_VALUE_CHARS {
+        return Err(invalid(
+            "value",
+            format!("must be 1 to 99 characters, not {}", len),
+        ));
+    }
+    if value.chars().any(char::is_control) {
+        return Err(invalid(
+            "value",
+            "cannot contain control characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_digits(field: &'static str, value: &str, len: usize) -> Result<(), PayloadError> {
+    if value.len() != len || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid(field, format!("must be {} digits", len)));
+    }
+    Ok(())
+}
+
+fn validate_max_chars(field: &'static str, value: &str, max: usize) -> Result<(), PayloadError> {
+    let len = value.chars().count();
+    if len == 0 || len > max {
+        return Err(invalid(field, format!("must be 1 to {} characters", max)));
+    }
+    Ok(())
+}
+
+fn validate_amount(amount: &str) -> Result<(), PayloadError> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let valid = amount.len() <= 13
+        && !whole.is_empty()
+        && whole.bytes().all(|b| b.is_ascii_digit())
+        && fraction.bytes().all(|b| b.is_ascii_digit())
+        && !(amount.ends_with('.'))
+        && amount.bytes().any(|b| (b'1'..=b'9').contains(&b));
+    if !valid {
+        return Err(invalid(
+            "amount",
+            "must be a positive number of up to 13 characters, such as 12.50".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_country(country: &str) -> Result<(), PayloadError> {
+    if country.len() != 2 || !country.bytes().all(|b| b.is_ascii_uppercase()) {
+        return Err(invalid(
+            "country code",
+            "must be two uppercase letters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Emv {
+        Emv::new()
+            .with_point_of_initiation(PointOfInitiation::Static)
+            .with_merchant_account(
+                29,
+                EmvTemplate::new()
+                    .with_field(0, "A000000677010111")
+                    .unwrap()
+                    .with_field(1, "0066812345678")
+                    .unwrap(),
+            )
+            .unwrap()
+            .with_merchant_category_code("0000")
+            .unwrap()
+            .with_currency("764")
+            .unwrap()
+            .with_country_code("TH")
+            .unwrap()
+            .with_merchant_name("Somchai")
+            .unwrap()
+            .with_merchant_city("Bangkok")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_crc16_check_value() {
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn test_payload_layout() {
+        let payload = sample().to_payload().unwrap();
+        assert_eq!(
+            &payload[..payload.len() - 4],
+            "000201010211\
+             29370016A000000677010111011300668123456785204000053037645802TH\
+             5907Somchai6007Bangkok6304"
+        );
+        let crc = crc16(&payload.as_bytes()[..payload.len() - 4]);
+        assert!(payload.ends_with(&format!("{:04X}", crc)));
+    }
+
+    #[test]
+    fn test_parse_round_trip_and_crc_verification() {
+        let emv = sample()
+            .with_amount("99.50")
+            .unwrap()
+            .with_additional_data(
+                EmvTemplate::new()
+                    .with_field(5, "REF1")
+                    .unwrap()
+                    .with_template(50, EmvTemplate::new().with_field(0, "x").unwrap())
+                    .unwrap(),
+            )
+            .unwrap();
+        let payload = emv.to_payload().unwrap();
+        let parsed = Emv::parse(&payload).unwrap();
+        assert_eq!(parsed, emv);
+        assert_eq!(
+            parsed.data.template(29).unwrap().text(1),
+            Some("0066812345678")
+        );
+
+        let last = if payload.ends_with('0') { "1" } else { "0" };
+        let corrupted = format!("{}{}", &payload[..payload.len() - 1], last);
+        assert!(Emv::parse(&corrupted).is_err());
+        let tampered = payload.replace("Somchai", "Somchak");
+        assert!(Emv::parse(&tampered).is_err());
+        assert!(Emv::parse("000201").is_err());
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(matches!(
+            Emv::new().validate(),
+            Err(PayloadError::MissingField("merchant account information"))
+        ));
+        let emv = sample();
+        assert!(emv.clone().with_currency("THB").is_err());
+        assert!(emv.clone().with_amount("0.00").is_err());
+        assert!(emv.clone().with_amount("1.").is_err());
+        assert!(emv.clone().with_country_code("th").is_err());
+        assert!(emv
+            .clone()
+            .with_merchant_account(2, EmvTemplate::new())
+            .is_err());
+        assert!(emv.clone().with_field(63, "ABCD").is_err());
+        assert!(emv.clone().with_field(65, &"x".repeat(100)).is_err());
+        assert!(emv.clone().with_field(26, "x").is_err());
+        assert!(emv.clone().with_field(62, "x").is_err());
+        assert!(emv.clone().with_field(80, "x").is_err());
+        assert!(emv.clone().with_template(59, EmvTemplate::new()).is_err());
+        assert!(emv.clone().with_template(63, EmvTemplate::new()).is_err());
+        assert!(emv
+            .clone()
+            .with_template(80, EmvTemplate::new().with_field(0, "x").unwrap())
+            .is_ok());
+        assert!(emv.with_merchant_name(&"x".repeat(26)).is_err());
+    }
+}
@@ -0,0 +1,513 @@
+//! SEPA credit transfers in the EPC069-12 format, also known as GiroCode.
+use super::{invalid, mod97, ParseMode, PayloadError, QrPayload};
+use crate::qrcode::{EncodeTextOptions, QrCodeEcc, Version};
+
+// Limits from EPC069-12, version 3.0.
+const MAX_PAYLOAD_BYTES: usize = 331;
+const MAX_VERSION: Version = Version::new(13);
+const MAX_AMOUNT_CENTS: u64 = 99_999_999_999;
+const MAX_NAME_CHARS: usize = 70;
+const MAX_REFERENCE_CHARS: usize = 35;
+const MAX_TEXT_CHARS: usize = 140;
+const MAX_INFO_CHARS: usize = 70;
+
+/// Version of the EPC QR code format (line 2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EpcVersion {
+    /// `001`: the BIC is mandatory
+    V1,
+    /// `002`: the BIC is optional within the EEA
+    V2,
+}
+
+/// Builder for an EPC069-12 SEPA credit transfer payload.
+///
+/// Produces the 12-line format with UTF-8 as character set, checks the IBAN and creditor reference
+/// checksums, and encodes with the mandated [QrCodeEcc::Medium] up to version 13.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::payload::{Epc, QrPayload};
+/// use qirust::qrcode::{QrCodeEcc, Version};
+///
+/// let transfer = Epc::new("Red Cross of Belgium", "BE72 0000 0000 1616").unwrap()
+///     .with_bic("BPOTBEB1").unwrap()
+///     .with_amount_cents(100).unwrap()
+///     .with_purpose("CHAR").unwrap()
+///     .with_text("Urgency fund").unwrap();
+/// assert_eq!(
+///     transfer.to_payload().unwrap(),
+///     "BCD\n002\n1\nSCT\nBPOTBEB1\nRed Cross of Belgium\nBE72000000001616\nEUR1.00\nCHAR\n\nUrgency fund"
+/// );
+///
+/// let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let qr = transfer.encode(&mut tempbuffer, &mut outbuffer).unwrap();
+/// assert_eq!(qr.error_correction_level(), QrCodeEcc::Medium);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Epc<'a> {
+    /// Format version (defaults to [EpcVersion::V2])
+    pub version: EpcVersion,
+    /// BIC of the beneficiary bank, 8 or 11 characters
+    pub bic: Option<String>,
+    /// Name of the beneficiary, up to 70 characters
+    pub name: &'a str,
+    /// IBAN of the beneficiary, without spaces
+    pub iban: String,
+    /// Amount in euro cents, from 1 to 99 999 999 999
+    pub amount_cents: Option<u64>,
+    /// Four letter ISO 20022 purpose code
+    pub purpose: Option<&'a str>,
+    /// Structured creditor reference, up to 35 characters
+    pub reference: Option<&'a str>,
+    /// Unstructured remittance text, up to 140 characters
+    pub text: Option<&'a str>,
+    /// Beneficiary to originator information, up to 70 characters
+    pub information: Option<&'a str>,
+}
+
+impl<'a> Epc<'a> {
+    /// Creates a transfer to the given beneficiary. Spaces in the IBAN are removed.
+    pub fn new(name: &'a str, iban: &str) -> Result<Self, PayloadError> {
+        let epc = Self {
+            version: EpcVersion::V2,
+            bic: None,
+            name,
+            iban: normalize(iban),
+            amount_cents: None,
+            purpose: None,
+            reference: None,
+            text: None,
+            information: None,
+        };
+        validate_text("name", name, MAX_NAME_CHARS)?;
+        validate_iban(&epc.iban)?;
+        Ok(epc)
+    }
+
+    pub fn with_version(mut self, version: EpcVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn with_bic(mut self, bic: &str) -> Result<Self, PayloadError> {
+        let bic = normalize(bic);
+        validate_bic(&bic)?;
+        self.bic = Some(bic);
+        Ok(self)
+    }
+
+    pub fn with_amount_cents(mut self, cents: u64) -> Result<Self, PayloadError> {
+        validate_amount(cents)?;
+        self.amount_cents = Some(cents);
+        Ok(self)
+    }
+
+    pub fn with_purpose(mut self, purpose: &'a str) -> Result<Self, PayloadError> {
+        validate_purpose(purpose)?;
+        self.purpose = Some(purpose);
+        Ok(self)
+    }
+
+    /// Sets the structured creditor reference; cannot be combined with [Epc::with_text]
+    pub fn with_reference(mut self, reference: &'a str) -> Result<Self, PayloadError> {
+        validate_reference(reference)?;
+        self.reference = Some(reference);
+        Ok(self)
+    }
+
+    /// Sets the unstructured remittance text; cannot be combined with [Epc::with_reference]
+    pub fn with_text(mut self, text: &'a str) -> Result<Self, PayloadError> {
+        validate_text("text", text, MAX_TEXT_CHARS)?;
+        self.text = Some(text);
+        Ok(self)
+    }
+
+    pub fn with_information(mut self, information: &'a str) -> Result<Self, PayloadError> {
+        validate_text("information", information, MAX_INFO_CHARS)?;
+        self.information = Some(information);
+        Ok(self)
+    }
+
+    /// Validates the fields and their combination before use
+    pub fn validate(&self) -> Result<(), PayloadError> {
+        validate_text("name", self.name, MAX_NAME_CHARS)?;
+        validate_iban(&self.iban)?;
+        match &self.bic {
+            Some(bic) => validate_bic(bic)?,
+            None if self.version == EpcVersion::V1 => {
+                return Err(PayloadError::MissingField("BIC"));
+            }
+            None => {}
+        }
+        if let Some(cents) = self.amount_cents {
+            validate_amount(cents)?;
+        }
+        if let Some(purpose) = self.purpose {
+            validate_purpose(purpose)?;
+        }
+        if let Some(reference) = self.reference {
+            validate_reference(reference)?;
+        }
+        if let Some(text) = self.text {
+            validate_text("text", text, MAX_TEXT_CHARS)?;
+        }
+        if let Some(information) = self.information {
+            validate_text("information", information, MAX_INFO_CHARS)?;
+        }
+        if self.reference.is_some() && self.text.is_some() {
+            return Err(invalid(
+                "reference",
+                "a structured reference and remittance text cannot both be set".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl QrPayload for Epc<'_> {
+    fn to_payload(&self) -> Result<String, PayloadError> {
+        self.validate()?;
+
+        let amount = self
+            .amount_cents
+            .map(|cents| format!("EUR{}.{:02}", cents / 100, cents % 100));
+        let lines = [
+            "BCD",
+            match self.version {
+                EpcVersion::V1 => "001",
+                EpcVersion::V2 => "002",
+            },
+            // Character set 1 is UTF-8
+            "1",
+            "SCT",
+            self.bic.as_deref().unwrap_or_default(),
+            self.name,
+            &self.iban,
+            amount.as_deref().unwrap_or_default(),
+            self.purpose.unwrap_or_default(),
+            self.reference.unwrap_or_default(),
+            self.text.unwrap_or_default(),
+            self.information.unwrap_or_default(),
+        ];
+        // Trailing empty lines may be omitted
+        let used = lines.iter().rposition(|line| !line.is_empty()).unwrap_or(0) + 1;
+        let payload = lines[..used].join("\n");
+
+        if payload.len() > MAX_PAYLOAD_BYTES {
+            return Err(invalid(
+                "payload",
+                format!(
+                    "is {} bytes, EPC QR codes hold at most {}",
+                    payload.len(),
+                    MAX_PAYLOAD_BYTES
+                ),
+            ));
+        }
+        Ok(payload)
+    }
+
+    /// EPC069-12 mandates error correction level M and at most version 13.
+    fn encode_options(&self) -> EncodeTextOptions {
+        EncodeTextOptions {
+            ecl: QrCodeEcc::Medium,
+            minversion: Version::MIN,
+            maxversion: MAX_VERSION,
+            mask: None,
+            boostecl: false,
+        }
+    }
+}
+
+/// SEPA credit transfer read from an EPC069-12 payload by [parse](super::parse).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedEpc {
+    pub version: EpcVersion,
+    pub bic: Option<String>,
+    pub name: String,
+    pub iban: String,
+    pub amount_cents: Option<u64>,
+    pub purpose: Option<String>,
+    pub reference: Option<String>,
+    pub text: Option<String>,
+    pub information: Option<String>,
+}
+
+impl ParsedEpc {
+    /// Returns a validated builder with the same fields
+    pub fn to_builder(&self) -> Result<Epc<'_>, PayloadError> {
+        let epc = Epc {
+            version: self.version,
+            bic: self.bic.clone(),
+            name: &self.name,
+            iban: self.iban.clone(),
+            amount_cents: self.amount_cents,
+            purpose: self.purpose.as_deref(),
+            reference: self.reference.as_deref(),
+            text: self.text.as_deref(),
+            information: self.information.as_deref(),
+        };
+        epc.validate()?;
+        Ok(epc)
+    }
+
+    // Parses the payload lines, starting with the `BCD` service tag.
+    pub(super) fn parse(text: &str, mode: ParseMode) -> Result<Self, PayloadError> {
+        let strict = mode == ParseMode::Strict;
+        let lines: Vec<&str> = text
+            .split('\n')
+            .map(|line| match mode {
+                ParseMode::Strict => line.strip_suffix('\r').unwrap_or(line),
+                ParseMode::Lenient => line.trim(),
+            })
+            .collect();
+        if lines.len() < 7 || lines.len() > 12 {
+            return Err(invalid(
+                "payload",
+                "must have between 7 and 12 lines".to_string(),
+            ));
+        }
+
+        let version = match lines[1] {
+            "001" => EpcVersion::V1,
+            "002" => EpcVersion::V2,
+            _ => return Err(invalid("version", "must be 001 or 002".to_string())),
+        };
+        let charset_valid = match mode {
+            ParseMode::Strict => lines[2] == "1",
+            ParseMode::Lenient => matches!(lines[2], "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8"),
+        };
+        if !charset_valid {
+            return Err(invalid("character set", "must be 1 (UTF-8)".to_string()));
+        }
+        if lines[3] != "SCT" {
+            return Err(invalid("identification", "must be SCT".to_string()));
+        }
+
+        let optional = |index: usize| {
+            lines
+                .get(index)
+                .filter(|line| !line.is_empty())
+                .map(|line| line.to_string())
+        };
+        let epc = Self {
+            version,
+            bic: optional(4).map(|bic| normalize(&bic)),
+            name: lines[5].to_string(),
+            iban: normalize(lines[6]),
+            amount_cents: optional(7)
+                .map(|amount| parse_amount(&amount))
+                .transpose()?,
+            purpose: optional(8),
+            reference: optional(9),
+            text: optional(10),
+            information: optional(11),
+        };
+        if strict {
+            epc.to_builder()?.to_payload()?;
+        } else if epc.name.is_empty() || epc.iban.is_empty() {
+            return Err(PayloadError::MissingField("beneficiary"));
+        }
+        Ok(epc)
+    }
+}
+
+// Reads an amount such as `EUR12.5` in cents.
+fn parse_amount(amount: &str) -> Result<u64, PayloadError> {
+    let error = || {
+        invalid(
+            "amount",
+            "must be written as EUR followed by a number".to_string(),
+        )
+    };
+    let amount = amount.strip_prefix("EUR").ok_or_else(error)?;
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let digits = |value: &str| value.bytes().all(|b| b.is_ascii_digit());
+    if whole.is_empty() || fraction.len() > 2 || !digits(whole) || !digits(fraction) {
+        return Err(error());
+    }
+    let whole: u64 = whole.parse().map_err(|_| error())?;
+    let fraction: u64 = format!("{:0<2}", fraction).parse().map_err(|_| error())?;
+    whole
+        .checked_mul(100)
+        .and_then(|cents| cents.checked_add(fraction))
+        .ok_or_else(error)
+}
+
+fn normalize(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
+// Text fields occupy one line each, so line breaks and other control characters are rejected.
+fn validate_text(field: &'static str, value: &str, max_chars: usize) -> Result<(), PayloadError> {
+    if value.trim().is_empty() {
+        return Err(invalid(field, "cannot be empty".to_string()));
+    }
+    if value.chars().count() > max_chars {
+        return Err(invalid(
+            field,
+            format!("cannot be longer than {} characters", max_chars),
+        ));
+    }
+    if value.chars().any(char::is_control) {
+        return Err(invalid(
+            field,
+            "cannot contain line breaks or control characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_iban(iban: &str) -> Result<(), PayloadError> {
+    let bytes = iban.as_bytes();
+    let well_formed = (15..=34).contains(&bytes.len())
+        && bytes[..2].iter().all(u8::is_ascii_uppercase)
+        && bytes[2..4].iter().all(u8::is_ascii_digit)
+        && bytes[4..].iter().all(u8::is_ascii_alphanumeric);
+    if !well_formed {
+        return Err(invalid(
+            "IBAN",
+            "must be a country code, two check digits and up to 30 letters or digits".to_string(),
+        ));
+    }
+    if mod97(iban) != 1 {
+        return Err(invalid("IBAN", "checksum does not match".to_string()));
+    }
+    Ok(())
+}
+
+fn validate_bic(bic: &str) -> Result<(), PayloadError> {
+    let bytes = bic.as_bytes();
+    let valid = matches!(bytes.len(), 8 | 11)
+        && bytes[..6].iter().all(u8::is_ascii_uppercase)
+        && bytes[6..].iter().all(u8::is_ascii_alphanumeric);
+    if !valid {
+        return Err(invalid(
+            "BIC",
+            "must be 8 or 11 characters, starting with six letters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_amount(cents: u64) -> Result<(), PayloadError> {
+    if !(1..=MAX_AMOUNT_CENTS).contains(&cents) {
+        return Err(invalid(
+            "amount",
+            "must be between EUR 0.01 and EUR 999999999.99".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_purpose(purpose: &str) -> Result<(), PayloadError> {
+    if purpose.len() != 4 || !purpose.bytes().all(|b| b.is_ascii_uppercase()) {
+        return Err(invalid(
+            "purpose",
+            "must be a four letter code such as CHAR".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// References starting with `RF` are ISO 11649 creditor references and carry a checksum.
+fn validate_reference(reference: &str) -> Result<(), PayloadError> {
+    validate_text("reference", reference, MAX_REFERENCE_CHARS)?;
+    if !reference.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return Err(invalid(
+            "reference",
+            "can only contain letters and digits".to_string(),
+        ));
+    }
+    if reference.starts_with("RF") && (reference.len() < 5 || mod97(reference) != 1) {
+        return Err(invalid(
+            "reference",
+            "creditor reference checksum does not match".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IBAN: &str = "DE89 3704 0044 0532 0130 00";
+
+    #[test]
+    fn test_payload_lines() {
+        let epc = Epc::new("Max Mustermann", IBAN)
+            .unwrap()
+            .with_version(EpcVersion::V1)
+            .with_bic("cobadeffxxx")
+            .unwrap()
+            .with_amount_cents(1_234_505)
+            .unwrap()
+            .with_reference("RF18539007547034")
+            .unwrap()
+            .with_information("Thanks");
+        let payload = epc.unwrap().to_payload().unwrap();
+        assert_eq!(
+            payload,
+            "BCD\n001\n1\nSCT\nCOBADEFFXXX\nMax Mustermann\nDE89370400440532013000\n\
+             EUR12345.05\n\nRF18539007547034\n\nThanks"
+        );
+        assert_eq!(payload.lines().count(), 12);
+
+        let minimal = Epc::new("Max", IBAN).unwrap().to_payload().unwrap();
+        assert_eq!(minimal, "BCD\n002\n1\nSCT\n\nMax\nDE89370400440532013000");
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(Epc::new("Max", "DE88370400440532013000").is_err());
+        assert!(Epc::new("Max", "DE89").is_err());
+        assert!(Epc::new("", IBAN).is_err());
+        assert!(Epc::new("Max\nMustermann", IBAN).is_err());
+
+        let epc = Epc::new("Max", IBAN).unwrap();
+        assert!(matches!(
+            epc.clone().with_version(EpcVersion::V1).validate(),
+            Err(PayloadError::MissingField("BIC"))
+        ));
+        assert!(epc.clone().with_bic("COBA1EFF").is_err());
+        assert!(epc.clone().with_amount_cents(0).is_err());
+        assert!(epc.clone().with_amount_cents(MAX_AMOUNT_CENTS + 1).is_err());
+        assert!(epc.clone().with_purpose("char").is_err());
+        assert!(epc.clone().with_reference("RF19539007547034").is_err());
+        assert!(epc.clone().with_text(&"x".repeat(141)).is_err());
+
+        let both = epc
+            .with_reference("RF18539007547034")
+            .unwrap()
+            .with_text("Invoice 1")
+            .unwrap();
+        assert!(both.validate().is_err());
+    }
+
+    #[test]
+    fn test_encoding_uses_medium_ecc_and_version_limit() {
+        let name = "N".repeat(MAX_NAME_CHARS);
+        let text = "é".repeat(MAX_TEXT_CHARS);
+        let epc = Epc::new(&name, IBAN)
+            .unwrap()
+            .with_bic("COBADEFFXXX")
+            .unwrap()
+            .with_text(&text)
+            .unwrap();
+        assert!(epc.to_payload().is_err());
+
+        let epc = epc.with_text(&text[..200]).unwrap();
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let qr = epc.encode(&mut tempbuffer, &mut outbuffer).unwrap();
+        assert_eq!(qr.error_correction_level(), QrCodeEcc::Medium);
+        assert!(qr.version() <= MAX_VERSION);
+    }
+}
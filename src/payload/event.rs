@@ -0,0 +1,184 @@
+//! Calendar events in the iCalendar `VEVENT` format.
+use super::{escape_text, fold_line, invalid, PayloadError, QrPayload};
+
+/// Builder for a `BEGIN:VEVENT` calendar event payload.
+///
+/// Times are written as `YYYYMMDD` for all-day events, or as `YYYYMMDDTHHMMSS` with an optional
+/// trailing `Z` for UTC. Text is escaped and long lines are folded as in iCalendar files.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::payload::{CalendarEvent, QrPayload};
+///
+/// let event = CalendarEvent::new("Team lunch", "20261018T120000Z").unwrap()
+///     .with_end("20261018T133000Z").unwrap()
+///     .with_location("Café Central, Zürich");
+/// assert_eq!(
+///     event.to_payload().unwrap(),
+///     "BEGIN:VEVENT\r\nSUMMARY:Team lunch\r\nDTSTART:20261018T120000Z\r\n\
+///      DTEND:20261018T133000Z\r\nLOCATION:Café Central\\, Zürich\r\nEND:VEVENT\r\n"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct CalendarEvent<'a> {
+    pub summary: &'a str,
+    /// Start date or date-time
+    pub start: &'a str,
+    /// End date or date-time, in the same form as the start
+    pub end: Option<&'a str>,
+    pub location: Option<&'a str>,
+    pub description: Option<&'a str>,
+}
+
+impl<'a> CalendarEvent<'a> {
+    pub fn new(summary: &'a str, start: &'a str) -> Result<Self, PayloadError> {
+        let event = Self {
+            summary,
+            start,
+            end: None,
+            location: None,
+            description: None,
+        };
+        event.validate()?;
+        Ok(event)
+    }
+
+    pub fn with_end(mut self, end: &'a str) -> Result<Self, PayloadError> {
+        self.end = Some(end);
+        self.validate()?;
+        Ok(self)
+    }
+
+    pub fn with_location(mut self, location: &'a str) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    pub fn with_description(mut self, description: &'a str) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    /// Returns true if the event spans whole days rather than a time range
+    pub fn is_all_day(&self) -> bool {
+        self.start.len() == 8
+    }
+
+    /// Validates the fields before use
+    pub fn validate(&self) -> Result<(), PayloadError> {
+        if self.summary.trim().is_empty() {
+            return Err(PayloadError::MissingField("summary"));
+        }
+        validate_time("start", self.start)?;
+        if let Some(end) = self.end {
+            validate_time("end", end)?;
+            if end.len() != self.start.len() || end.ends_with('Z') != self.start.ends_with('Z') {
+                return Err(invalid(
+                    "end",
+                    "must use the same form as the start".to_string(),
+                ));
+            }
+            // Equal forms compare chronologically as strings
+            if end < self.start {
+                return Err(invalid("end", "cannot be before the start".to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl QrPayload for CalendarEvent<'_> {
+    fn to_payload(&self) -> Result<String, PayloadError> {
+        self.validate()?;
+
+        let date_param = if self.is_all_day() { ";VALUE=DATE" } else { "" };
+        let mut lines = vec![
+            "BEGIN:VEVENT".to_string(),
+            format!("SUMMARY:{}", escape_text(self.summary)),
+            format!("DTSTART{}:{}", date_param, self.start),
+        ];
+        if let Some(end) = self.end {
+            lines.push(format!("DTEND{}:{}", date_param, end));
+        }
+        if let Some(location) = self.location {
+            lines.push(format!("LOCATION:{}", escape_text(location)));
+        }
+        if let Some(description) = self.description {
+            lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        lines.push("END:VEVENT".to_string());
+
+        let mut payload = String::new();
+        for line in &lines {
+            fold_line(line, &mut payload);
+        }
+        Ok(payload)
+    }
+}
+
+fn validate_time(field: &'static str, value: &str) -> Result<(), PayloadError> {
+    let number = |range: std::ops::Range<usize>| {
+        value
+            .get(range)
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|digits| digits.parse::<u32>().ok())
+    };
+    let date_valid = number(0..4).is_some()
+        && number(4..6).is_some_and(|month| (1..=12).contains(&month))
+        && number(6..8).is_some_and(|day| (1..=31).contains(&day));
+    let valid = date_valid
+        && match value.len() {
+            8 => true,
+            15 | 16 => {
+                value.as_bytes()[8] == b'T'
+                    && number(9..11).is_some_and(|hour| hour < 24)
+                    && number(11..13).is_some_and(|minute| minute < 60)
+                    && number(13..15).is_some_and(|second| second <= 60)
+                    && (value.len() == 15 || value.ends_with('Z'))
+            }
+            _ => false,
+        };
+    if !valid {
+        return Err(invalid(
+            field,
+            "must be formatted as YYYYMMDD or YYYYMMDDTHHMMSS with an optional Z".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_day_event() {
+        let event = CalendarEvent::new("Conference", "20261020")
+            .unwrap()
+            .with_end("20261022")
+            .unwrap()
+            .with_description("Day 1: talks; Day 2: workshops\nBring a laptop");
+        assert!(event.is_all_day());
+        assert_eq!(
+            event.to_payload().unwrap(),
+            "BEGIN:VEVENT\r\nSUMMARY:Conference\r\nDTSTART;VALUE=DATE:20261020\r\n\
+             DTEND;VALUE=DATE:20261022\r\n\
+             DESCRIPTION:Day 1: talks\\; Day 2: workshops\\nBring a laptop\r\nEND:VEVENT\r\n"
+        );
+    }
+
+    #[test]
+    fn test_time_validation() {
+        assert!(CalendarEvent::new("", "20261020").is_err());
+        assert!(CalendarEvent::new("x", "2026-10-20").is_err());
+        assert!(CalendarEvent::new("x", "20261320").is_err());
+        assert!(CalendarEvent::new("x", "20261020T250000").is_err());
+        assert!(CalendarEvent::new("x", "20261020T120000").is_ok());
+
+        let event = CalendarEvent::new("x", "20261020T120000Z").unwrap();
+        assert!(event.clone().with_end("20261020T110000Z").is_err());
+        assert!(event.clone().with_end("20261020T130000").is_err());
+        assert!(event.with_end("20261021").is_err());
+    }
+}
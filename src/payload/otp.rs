@@ -0,0 +1,476 @@
+//! One-time password provisioning in the `otpauth://` key URI format used by authenticator apps.
+use super::parser::query_pairs;
+use super::{invalid, percent_decode, percent_encode, ParseMode, PayloadError, QrPayload};
+use crate::helper::to_svg_string;
+use crate::qrcode::Version;
+use std::fmt;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+// 80 bits, the length most authenticator services issue.
+const MIN_SECRET_BYTES: usize = 10;
+
+/// Kind of one-time password.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OtpKind {
+    /// Time-based codes (RFC 6238) that change every period.
+    Totp,
+    /// Counter-based codes (RFC 4226), starting at the given counter value.
+    Hotp { counter: u64 },
+}
+
+/// Hash algorithm used to compute the codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OtpAlgorithm {
+    #[default]
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl OtpAlgorithm {
+    const ALL: [OtpAlgorithm; 3] = [
+        OtpAlgorithm::Sha1,
+        OtpAlgorithm::Sha256,
+        OtpAlgorithm::Sha512,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            OtpAlgorithm::Sha1 => "SHA1",
+            OtpAlgorithm::Sha256 => "SHA256",
+            OtpAlgorithm::Sha512 => "SHA512",
+        }
+    }
+}
+
+/// Builder for an `otpauth://` TOTP or HOTP provisioning payload.
+///
+/// The secret is kept in memory only: it is left out of the `Debug` output and never written to
+/// disk or to a cache, so it only leaves the builder through [QrPayload::to_payload],
+/// [QrPayload::encode] or [Otp::to_svg_string].
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::payload::{Otp, OtpKind, QrPayload};
+///
+/// let otp = Otp::new(OtpKind::Totp, "alice@example.com", b"12345678901234567890").unwrap()
+///     .with_issuer("Example Co").unwrap();
+/// assert_eq!(
+///     otp.to_payload().unwrap(),
+///     "otpauth://totp/Example%20Co:alice%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ\
+///      &issuer=Example%20Co&algorithm=SHA1&digits=6&period=30"
+/// );
+///
+/// let svg = otp.to_svg_string(4).unwrap();
+/// assert!(svg.contains("<svg"));
+/// ```
+#[derive(Clone)]
+pub struct Otp<'a> {
+    pub kind: OtpKind,
+    /// Account name shown in the authenticator, usually a user name or email address
+    pub account: &'a str,
+    /// Provider or service the account belongs to
+    pub issuer: Option<&'a str>,
+    pub algorithm: OtpAlgorithm,
+    /// Number of digits in each code, from 6 to 8
+    pub digits: u8,
+    /// Seconds each time-based code is valid for
+    pub period: u32,
+    secret: Vec<u8>,
+}
+
+impl<'a> Otp<'a> {
+    /// Creates a builder from the raw shared secret, which must be at least 10 bytes long
+    pub fn new(kind: OtpKind, account: &'a str, secret: &[u8]) -> Result<Self, PayloadError> {
+        let otp = Self {
+            kind,
+            account,
+            issuer: None,
+            algorithm: OtpAlgorithm::default(),
+            digits: 6,
+            period: 30,
+            secret: secret.to_vec(),
+        };
+        otp.validate()?;
+        Ok(otp)
+    }
+
+    /// Creates a builder from a base32 secret as shown to users for manual entry.
+    ///
+    /// Lowercase letters, spaces, hyphens and `=` padding are accepted.
+    pub fn from_base32(
+        kind: OtpKind,
+        account: &'a str,
+        secret: &str,
+    ) -> Result<Self, PayloadError> {
+        let secret = base32_decode(secret)
+            .ok_or_else(|| invalid("secret", "must be a valid base32 string"))?;
+        Self::new(kind, account, &secret)
+    }
+
+    pub fn with_issuer(mut self, issuer: &'a str) -> Result<Self, PayloadError> {
+        self.issuer = Some(issuer);
+        self.validate()?;
+        Ok(self)
+    }
+
+    pub fn with_algorithm(mut self, algorithm: OtpAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn with_digits(mut self, digits: u8) -> Result<Self, PayloadError> {
+        self.digits = digits;
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Sets the period of a TOTP in seconds
+    pub fn with_period(mut self, period: u32) -> Result<Self, PayloadError> {
+        if let OtpKind::Hotp { .. } = self.kind {
+            return Err(invalid("period", "only applies to TOTP"));
+        }
+        self.period = period;
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Returns the secret in base32 without padding, as used in the payload
+    pub fn secret_base32(&self) -> String {
+        base32_encode(&self.secret)
+    }
+
+    /// Validates the fields before use
+    pub fn validate(&self) -> Result<(), PayloadError> {
+        if self.account.trim().is_empty() {
+            return Err(PayloadError::MissingField("account"));
+        }
+        // The label uses a colon to separate the issuer from the account
+        if self.account.contains(':') {
+            return Err(invalid("account", "cannot contain ':'"));
+        }
+        if let Some(issuer) = self.issuer {
+            if issuer.trim().is_empty() || issuer.contains(':') {
+                return Err(invalid(
+                    "issuer",
+                    "must be non-empty and cannot contain ':'",
+                ));
+            }
+        }
+        if self.secret.len() < MIN_SECRET_BYTES {
+            return Err(invalid(
+                "secret",
+                format!("must be at least {} bytes long", MIN_SECRET_BYTES),
+            ));
+        }
+        if !(6..=8).contains(&self.digits) {
+            return Err(invalid("digits", "must be between 6 and 8"));
+        }
+        if self.period == 0 {
+            return Err(invalid("period", "must be at least one second"));
+        }
+        Ok(())
+    }
+
+    /// Encodes the payload and renders it as an SVG string with the given quiet zone in modules.
+    ///
+    /// The QR code is built in memory with [QrPayload::encode] and rendered by
+    /// [to_svg_string](crate::helper::to_svg_string); nothing is written to disk.
+    pub fn to_svg_string(&self, border: i32) -> Result<String, PayloadError> {
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let qr = self.encode(&mut tempbuffer, &mut outbuffer)?;
+        let svg = to_svg_string(&qr, border);
+        // The buffers hold the secret in encoded form
+        tempbuffer.fill(0);
+        outbuffer.fill(0);
+        Ok(svg)
+    }
+}
+
+impl fmt::Debug for Otp<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Otp")
+            .field("kind", &self.kind)
+            .field("account", &self.account)
+            .field("issuer", &self.issuer)
+            .field("algorithm", &self.algorithm)
+            .field("digits", &self.digits)
+            .field("period", &self.period)
+            .field("secret", &"<redacted>")
+            .finish()
+    }
+}
+
+impl Drop for Otp<'_> {
+    fn drop(&mut self) {
+        self.secret.fill(0);
+    }
+}
+
+impl QrPayload for Otp<'_> {
+    fn to_payload(&self) -> Result<String, PayloadError> {
+        self.validate()?;
+
+        let (kind, counter) = match self.kind {
+            OtpKind::Totp => ("totp", None),
+            OtpKind::Hotp { counter } => ("hotp", Some(counter)),
+        };
+        let account = percent_encode(self.account, "");
+        let mut payload = match self.issuer {
+            Some(issuer) => format!(
+                "otpauth://{}/{}:{}?secret={}&issuer={}",
+                kind,
+                percent_encode(issuer, ""),
+                account,
+                self.secret_base32(),
+                percent_encode(issuer, "")
+            ),
+            None => format!(
+                "otpauth://{}/{}?secret={}",
+                kind,
+                account,
+                self.secret_base32()
+            ),
+        };
+        payload.push_str(&format!(
+            "&algorithm={}&digits={}",
+            self.algorithm.as_str(),
+            self.digits
+        ));
+        match counter {
+            Some(counter) => payload.push_str(&format!("&counter={}", counter)),
+            None => payload.push_str(&format!("&period={}", self.period)),
+        }
+        Ok(payload)
+    }
+}
+
+/// One-time password settings read from an `otpauth://` payload by [parse](super::parse).
+///
+/// Like [Otp], the secret is left out of the `Debug` output.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ParsedOtp {
+    pub kind: OtpKind,
+    pub account: String,
+    pub issuer: Option<String>,
+    pub algorithm: OtpAlgorithm,
+    pub digits: u8,
+    pub period: u32,
+    secret: Vec<u8>,
+}
+
+impl ParsedOtp {
+    /// Returns the raw shared secret
+    pub fn secret(&self) -> &[u8] {
+        &self.secret
+    }
+
+    /// Returns a validated builder with the same fields
+    pub fn to_builder(&self) -> Result<Otp<'_>, PayloadError> {
+        let mut otp = Otp::new(self.kind, &self.account, &self.secret)?;
+        otp.issuer = self.issuer.as_deref();
+        otp.algorithm = self.algorithm;
+        otp.digits = self.digits;
+        otp.period = self.period;
+        otp.validate()?;
+        Ok(otp)
+    }
+
+    // Parses the part following `otpauth://`, such as `totp/Issuer:account?secret=...`.
+    pub(super) fn parse(body: &str, mode: ParseMode) -> Result<Self, PayloadError> {
+        let strict = mode == ParseMode::Strict;
+        let (kind, rest) = body
+            .split_once('/')
+            .ok_or_else(|| invalid("type", "must be totp or hotp"))?;
+        let (label, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let is = |name: &str| match mode {
+            ParseMode::Strict => kind == name,
+            ParseMode::Lenient => kind.eq_ignore_ascii_case(name),
+        };
+        let hotp = if is("totp") {
+            false
+        } else if is("hotp") {
+            true
+        } else {
+            return Err(invalid("type", "must be totp or hotp"));
+        };
+
+        let label = percent_decode(label, mode)?;
+        let (label_issuer, account) = match label.split_once(':') {
+            Some((issuer, account)) => (Some(issuer.to_string()), account.trim_start()),
+            None => (None, label.as_str()),
+        };
+        let mut otp = Self {
+            kind: OtpKind::Totp,
+            account: account.to_string(),
+            issuer: label_issuer.clone(),
+            algorithm: OtpAlgorithm::default(),
+            digits: 6,
+            period: 30,
+            secret: Vec::new(),
+        };
+        let mut counter = None;
+        let number_error = |field| invalid(field, "must be a number");
+        for (key, value) in query_pairs(query) {
+            let value = percent_decode(value, mode)?;
+            match key {
+                "secret" => {
+                    if strict
+                        && !value
+                            .bytes()
+                            .all(|b| BASE32_ALPHABET.contains(&b) || b == b'=')
+                    {
+                        return Err(invalid("secret", "must be uppercase base32"));
+                    }
+                    otp.secret = base32_decode(&value)
+                        .ok_or_else(|| invalid("secret", "must be a valid base32 string"))?;
+                }
+                "issuer" => otp.issuer = Some(value),
+                "algorithm" => {
+                    otp.algorithm = OtpAlgorithm::ALL
+                        .into_iter()
+                        .find(|algorithm| match mode {
+                            ParseMode::Strict => algorithm.as_str() == value,
+                            ParseMode::Lenient => algorithm.as_str().eq_ignore_ascii_case(&value),
+                        })
+                        .ok_or_else(|| invalid("algorithm", "must be SHA1, SHA256 or SHA512"))?;
+                }
+                "digits" => otp.digits = value.parse().map_err(|_| number_error("digits"))?,
+                "period" => otp.period = value.parse().map_err(|_| number_error("period"))?,
+                "counter" => counter = Some(value.parse().map_err(|_| number_error("counter"))?),
+                // Extensions such as `image` are ignored
+                _ => {}
+            }
+        }
+        if hotp {
+            let counter = match counter {
+                Some(counter) => counter,
+                None if strict => return Err(PayloadError::MissingField("counter")),
+                None => 0,
+            };
+            otp.kind = OtpKind::Hotp { counter };
+        }
+
+        if strict {
+            if label_issuer.is_some() && otp.issuer != label_issuer {
+                return Err(invalid("issuer", "does not match the label"));
+            }
+            otp.to_builder()?;
+        } else if otp.secret.is_empty() {
+            return Err(PayloadError::MissingField("secret"));
+        }
+        Ok(otp)
+    }
+}
+
+impl fmt::Debug for ParsedOtp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParsedOtp")
+            .field("kind", &self.kind)
+            .field("account", &self.account)
+            .field("issuer", &self.issuer)
+            .field("algorithm", &self.algorithm)
+            .field("digits", &self.digits)
+            .field("period", &self.period)
+            .field("secret", &"<redacted>")
+            .finish()
+    }
+}
+
+impl Drop for ParsedOtp {
+    fn drop(&mut self) {
+        self.secret.fill(0);
+    }
+}
+
+// Encodes bytes as RFC 4648 base32 without padding.
+fn base32_encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for &byte in data {
+        buffer = (buffer << 8) | u16::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32_ALPHABET[usize::from((buffer >> bits) & 0x1f)] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(BASE32_ALPHABET[usize::from((buffer << (5 - bits)) & 0x1f)] as char);
+    }
+    encoded
+}
+
+// Decodes base32, ignoring case, spaces, hyphens and trailing padding.
+fn base32_decode(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=');
+    let mut decoded = Vec::with_capacity(text.len() * 5 / 8);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for c in text.chars().filter(|c| !matches!(c, ' ' | '-')) {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&b| b == c.to_ascii_uppercase() as u8 && c.is_ascii())?;
+        buffer = (buffer << 5) | value as u16;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+        }
+    }
+    // Leftover bits must be zero padding of the last character
+    if buffer & ((1 << bits) - 1) != 0 {
+        return None;
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base32_round_trip() {
+        // RFC 4648 test vectors
+        for (raw, encoded) in [
+            ("", ""),
+            ("f", "MY"),
+            ("fo", "MZXQ"),
+            ("foo", "MZXW6"),
+            ("foob", "MZXW6YQ"),
+            ("fooba", "MZXW6YTB"),
+            ("foobar", "MZXW6YTBOI"),
+        ] {
+            assert_eq!(base32_encode(raw.as_bytes()), encoded);
+            assert_eq!(base32_decode(encoded).unwrap(), raw.as_bytes());
+        }
+        assert_eq!(base32_decode("mzxw 6ytb-oi======").unwrap(), b"foobar");
+        assert!(base32_decode("MZXW1").is_none());
+        assert!(base32_decode("MZ").is_none());
+        assert!(base32_decode("MZ7").is_none());
+    }
+
+    #[test]
+    fn test_hotp_payload_and_validation() {
+        let otp = Otp::from_base32(OtpKind::Hotp { counter: 42 }, "bob", "jbsw y3dp ehpk 3pxp")
+            .unwrap()
+            .with_algorithm(OtpAlgorithm::Sha256)
+            .with_digits(8)
+            .unwrap();
+        assert_eq!(
+            otp.to_payload().unwrap(),
+            "otpauth://hotp/bob?secret=JBSWY3DPEHPK3PXP&algorithm=SHA256&digits=8&counter=42"
+        );
+        assert!(!format!("{:?}", otp).contains("JBSWY3DP"));
+
+        assert!(otp.clone().with_period(60).is_err());
+        assert!(otp.clone().with_digits(9).is_err());
+        assert!(otp.clone().with_issuer("Acme: Corp").is_err());
+        assert!(Otp::new(OtpKind::Totp, "bob", b"too short").is_err());
+        assert!(Otp::new(OtpKind::Totp, "bob:1", b"1234567890").is_err());
+        assert!(Otp::from_base32(OtpKind::Totp, "bob", "not base32!").is_err());
+    }
+}
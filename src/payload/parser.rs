@@ -0,0 +1,425 @@
+//! Classification of decoded QR code text into the payload formats of this module.
+use super::{
+    Emv, ParsedEmail, ParsedEpc, ParsedGeo, ParsedMeCard, ParsedOtp, ParsedSms, ParsedVCard,
+    ParsedWiFi, PayloadError, Url,
+};
+
+/// How strictly [parse_with] checks recognised formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Requires well-formed payloads: exact field names and terminators, escaped special characters,
+    /// no unknown fields, and values that pass the same validation as the builders.
+    Strict,
+    /// Accepts payloads as real-world generators write them: field names in any case, missing
+    /// terminators, unknown fields and unvalidated values.
+    #[default]
+    Lenient,
+}
+
+/// Payload recognised by [parse], holding the same fields as the matching builder.
+///
+/// Structured variants can be turned back into builders with their `to_builder` method, for
+/// example to re-encode a scanned payload.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Payload {
+    /// `WIFI:` network credentials
+    WiFi(ParsedWiFi),
+    /// `BEGIN:VCARD` contact card
+    VCard(ParsedVCard),
+    /// `MECARD:` contact card
+    MeCard(ParsedMeCard),
+    /// EPC069-12 SEPA credit transfer
+    Epc(ParsedEpc),
+    /// EMVCo merchant-presented payment
+    Emv(Emv),
+    /// `otpauth://` one-time password provisioning
+    Otp(ParsedOtp),
+    /// `geo:` location
+    Geo(ParsedGeo),
+    /// `tel:` phone number, without the scheme
+    Phone(String),
+    /// `SMSTO:` or `sms:` text message
+    Sms(ParsedSms),
+    /// `mailto:` link
+    Email(ParsedEmail),
+    /// Any other URL with a `scheme://` prefix, as written in the payload
+    Url(String),
+    /// Text that matches none of the formats above
+    Text(String),
+}
+
+/// Classifies scanned QR code text, reading recognised formats in [ParseMode::Lenient] mode.
+///
+/// Text that looks like a known format but cannot be read is returned as [Payload::Text], so this
+/// never fails.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::payload::{parse, Payload, WiFiSecurity};
+///
+/// match parse("WIFI:T:WPA;S:Guest;P:p@ss\\:word;;") {
+///     Payload::WiFi(wifi) => {
+///         assert_eq!(wifi.ssid, "Guest");
+///         assert_eq!(wifi.security, WiFiSecurity::Wpa);
+///         assert_eq!(wifi.password.as_deref(), Some("p@ss:word"));
+///     }
+///     other => panic!("unexpected payload {:?}", other),
+/// }
+/// assert_eq!(parse("Hello"), Payload::Text("Hello".to_string()));
+/// ```
+pub fn parse(text: &str) -> Payload {
+    parse_with(text, ParseMode::Lenient).unwrap_or_else(|_| Payload::Text(text.to_string()))
+}
+
+/// Classifies scanned QR code text using the given [ParseMode].
+///
+/// Returns an error if the text starts like a known format but cannot be read in that mode. Text
+/// that matches no format is returned as [Payload::Text].
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::payload::{parse_with, ParseMode, Payload};
+///
+/// // Missing `;;` terminator and an unescaped colon in the password
+/// let scanned = "wifi:t:WPA;s:Guest;p:pass:word";
+/// assert!(matches!(parse_with(scanned, ParseMode::Lenient), Ok(Payload::WiFi(_))));
+/// assert!(parse_with(scanned, ParseMode::Strict).is_err());
+/// ```
+pub fn parse_with(text: &str, mode: ParseMode) -> Result<Payload, PayloadError> {
+    let text = match mode {
+        ParseMode::Strict => text,
+        ParseMode::Lenient => text.trim(),
+    };
+
+    if let Some(body) = strip_prefix_ignore_case(text, "WIFI:") {
+        return ParsedWiFi::parse(body, mode).map(Payload::WiFi);
+    }
+    if let Some(body) = strip_prefix_ignore_case(text, "MECARD:") {
+        return ParsedMeCard::parse(body, mode).map(Payload::MeCard);
+    }
+    if strip_prefix_ignore_case(text, "BEGIN:VCARD").is_some() {
+        return ParsedVCard::parse(text, mode).map(Payload::VCard);
+    }
+    if text.starts_with("BCD\n") || text.starts_with("BCD\r\n") {
+        return ParsedEpc::parse(text, mode).map(Payload::Epc);
+    }
+    if text.starts_with("000201") {
+        return Emv::parse(text).map(Payload::Emv);
+    }
+    if let Some(body) = strip_prefix_ignore_case(text, "otpauth://") {
+        return ParsedOtp::parse(body, mode).map(Payload::Otp);
+    }
+    if let Some(body) = strip_prefix_ignore_case(text, "geo:") {
+        return ParsedGeo::parse(body, mode).map(Payload::Geo);
+    }
+    if let Some(number) = strip_prefix_ignore_case(text, "tel:") {
+        if mode == ParseMode::Strict {
+            super::validate_phone(number)?;
+        }
+        return Ok(Payload::Phone(number.to_string()));
+    }
+    if let Some(body) = strip_prefix_ignore_case(text, "SMSTO:") {
+        return ParsedSms::parse_smsto(body, mode).map(Payload::Sms);
+    }
+    if let Some(body) = strip_prefix_ignore_case(text, "sms:") {
+        return ParsedSms::parse_uri(body, mode).map(Payload::Sms);
+    }
+    if let Some(body) = strip_prefix_ignore_case(text, "mailto:") {
+        return ParsedEmail::parse(body, mode).map(Payload::Email);
+    }
+    if is_url(text, mode) {
+        if mode == ParseMode::Strict {
+            Url::new(text)?;
+        }
+        return Ok(Payload::Url(text.to_string()));
+    }
+    Ok(Payload::Text(text.to_string()))
+}
+
+pub(crate) fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    text.get(..prefix.len())
+        .filter(|start| start.eq_ignore_ascii_case(prefix))
+        .map(|_| &text[prefix.len()..])
+}
+
+// Splits a `key=value&key=value` query, skipping empty pairs.
+pub(crate) fn query_pairs(query: &str) -> impl Iterator<Item = (&str, &str)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+}
+
+// Recognises `scheme://` URLs, and in lenient mode also bare `www.` addresses.
+fn is_url(text: &str, mode: ParseMode) -> bool {
+    let has_scheme = text.split_once("://").is_some_and(|(scheme, _)| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"+-.".contains(&b))
+    });
+    let bare = mode == ParseMode::Lenient
+        && strip_prefix_ignore_case(text, "www.").is_some()
+        && !text.contains(char::is_whitespace);
+    has_scheme || bare
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload::{
+        Address, ContactName, EapMethod, Email, EmailType, EmvTemplate, Epc, EpcVersion, Geo,
+        MeCard, Otp, OtpAlgorithm, OtpKind, Phase2Method, Phone, PhoneType, QrPayload, Sms, VCard,
+        VCardVersion, WiFi, WiFiSecurity,
+    };
+
+    // Parses a builder's payload in both modes and checks that rebuilding gives the same payload.
+    fn round_trip(payload: &str) -> Payload {
+        let strict = parse_with(payload, ParseMode::Strict).unwrap();
+        assert_eq!(strict, parse(payload), "modes disagree for {:?}", payload);
+        let rebuilt = match &strict {
+            Payload::WiFi(wifi) => wifi.to_builder().unwrap().to_payload(),
+            Payload::VCard(card) => card.to_builder().unwrap().to_payload(),
+            Payload::MeCard(card) => card.to_builder().unwrap().to_payload(),
+            Payload::Epc(epc) => epc.to_builder().unwrap().to_payload(),
+            Payload::Emv(emv) => emv.to_payload(),
+            Payload::Otp(otp) => otp.to_builder().unwrap().to_payload(),
+            Payload::Geo(geo) => geo.to_builder().unwrap().to_payload(),
+            Payload::Phone(number) => Phone::new(number).unwrap().to_payload(),
+            Payload::Sms(sms) => sms.to_builder().unwrap().to_payload(),
+            Payload::Email(email) => email.to_builder().unwrap().to_payload(),
+            Payload::Url(url) => Ok(url.clone()),
+            Payload::Text(text) => Ok(text.clone()),
+        };
+        assert_eq!(rebuilt.unwrap(), payload);
+        strict
+    }
+
+    #[test]
+    fn test_round_trip_wifi_and_contacts() {
+        let wifi = WiFi::new("Café;Corp")
+            .unwrap()
+            .with_security(WiFiSecurity::Wpa2Enterprise)
+            .with_eap_method(EapMethod::Ttls)
+            .with_phase2_method(Phase2Method::Pap)
+            .with_identity("alice\\admin")
+            .unwrap()
+            .with_password("\"quoted\"")
+            .unwrap()
+            .with_hidden(true);
+        let Payload::WiFi(parsed) = round_trip(&wifi.to_payload().unwrap()) else {
+            panic!("expected Wi-Fi");
+        };
+        assert_eq!(parsed.ssid, "Café;Corp");
+        assert_eq!(parsed.identity.as_deref(), Some("alice\\admin"));
+        let hex = WiFi::new("CAFE")
+            .unwrap()
+            .with_password("DEADBEEF12")
+            .unwrap();
+        round_trip(&hex.to_payload().unwrap());
+
+        let note = "A long note, with\nline breaks. ".repeat(4);
+        for version in [VCardVersion::V3, VCardVersion::V4] {
+            let card = VCard::new(version)
+                .with_name_parts(ContactName {
+                    family: "Doe",
+                    given: "Jane",
+                    additional: "Q.",
+                    prefix: "Dr.",
+                    suffix: "",
+                })
+                .with_organization("Example, Inc.")
+                .with_title("Head of R;D")
+                .with_phone(PhoneType::Cell, "+1 555 0100")
+                .unwrap()
+                .with_email(EmailType::Work, "jane@example.com")
+                .unwrap()
+                .with_address(Address {
+                    street: "1 Main St",
+                    locality: "Springfield",
+                    country: "USA",
+                    ..Address::default()
+                })
+                .with_url("https://example.com")
+                .unwrap()
+                .with_note(&note)
+                .with_photo(&[0xff, 0xd8, 0xff, 0xe0], "image/jpeg")
+                .unwrap();
+            let Payload::VCard(parsed) = round_trip(&card.to_payload().unwrap()) else {
+                panic!("expected vCard");
+            };
+            assert_eq!(parsed.version, version);
+            assert_eq!(parsed.name.prefix, "Dr.");
+            assert_eq!(parsed.phones[0].0, PhoneType::Cell);
+            assert_eq!(
+                parsed.emails,
+                [(EmailType::Work, "jane@example.com".to_string())]
+            );
+            assert_eq!(parsed.photo.unwrap().media_type, "image/jpeg");
+        }
+
+        let card = MeCard::new("Doe", "Jane")
+            .unwrap()
+            .with_nickname("JD")
+            .with_phone("+15550100")
+            .unwrap()
+            .with_email("jane@example.com")
+            .unwrap()
+            .with_url("https://example.com")
+            .unwrap()
+            .with_birthday("19900102")
+            .unwrap()
+            .with_address(Address {
+                street: "1 Main St, Apt 2",
+                locality: "Springfield",
+                ..Address::default()
+            });
+        let Payload::MeCard(parsed) = round_trip(&card.to_payload().unwrap()) else {
+            panic!("expected MeCard");
+        };
+        assert_eq!(parsed.address.unwrap().street, "1 Main St, Apt 2");
+        assert_eq!(parsed.url.as_deref(), Some("https://example.com"));
+    }
+
+    #[test]
+    fn test_round_trip_payments_and_uris() {
+        let epc = Epc::new("Red Cross of Belgium", "BE72000000001616")
+            .unwrap()
+            .with_version(EpcVersion::V1)
+            .with_bic("BPOTBEB1")
+            .unwrap()
+            .with_amount_cents(1_234_505)
+            .unwrap()
+            .with_reference("RF18539007547034")
+            .unwrap();
+        let Payload::Epc(parsed) = round_trip(&epc.to_payload().unwrap()) else {
+            panic!("expected EPC");
+        };
+        assert_eq!(parsed.amount_cents, Some(1_234_505));
+
+        let emv = Emv::new()
+            .with_merchant_account(26, EmvTemplate::new().with_field(0, "com.example").unwrap())
+            .unwrap()
+            .with_merchant_category_code("5812")
+            .unwrap()
+            .with_currency("360")
+            .unwrap()
+            .with_country_code("ID")
+            .unwrap()
+            .with_merchant_name("Warung Kopi")
+            .unwrap()
+            .with_merchant_city("Jakarta")
+            .unwrap();
+        round_trip(&emv.to_payload().unwrap());
+
+        let otp = Otp::new(
+            OtpKind::Hotp { counter: 7 },
+            "alice@example.com",
+            b"0123456789",
+        )
+        .unwrap()
+        .with_issuer("Example Co")
+        .unwrap()
+        .with_algorithm(OtpAlgorithm::Sha512)
+        .with_digits(8)
+        .unwrap();
+        let Payload::Otp(parsed) = round_trip(&otp.to_payload().unwrap()) else {
+            panic!("expected OTP");
+        };
+        assert_eq!(parsed.secret(), b"0123456789");
+        assert_eq!(parsed.issuer.as_deref(), Some("Example Co"));
+
+        let geo = Geo::new(47.3769, -8.5)
+            .unwrap()
+            .with_altitude(408.0)
+            .unwrap()
+            .with_query("Zürich HB, track 3");
+        round_trip(&geo.to_payload().unwrap());
+        round_trip(
+            &Phone::new("+41 44 668 18 00")
+                .unwrap()
+                .to_payload()
+                .unwrap(),
+        );
+        let sms = Sms::new("+15550100").unwrap().with_message("JOIN: a:b");
+        round_trip(&sms.to_payload().unwrap());
+        let email = Email::new("a@example.com")
+            .unwrap()
+            .with_cc("c@example.com")
+            .unwrap()
+            .with_subject("Order #42 & more")
+            .with_body("Line 1\nLine 2");
+        let Payload::Email(parsed) = round_trip(&email.to_payload().unwrap()) else {
+            panic!("expected email");
+        };
+        assert_eq!(parsed.body.as_deref(), Some("Line 1\nLine 2"));
+        let url = Url::new("https://example.com/menü").unwrap();
+        round_trip(&url.to_payload().unwrap());
+    }
+
+    #[test]
+    fn test_lenient_and_strict_differences() {
+        let lenient = |text| parse_with(text, ParseMode::Lenient);
+        let strict = |text| parse_with(text, ParseMode::Strict);
+
+        // Unknown fields, lowercase keys and loose values
+        let wifi = "WIFI:S:Home;T:wpa2;P:hunter22;H:1;X:ignored";
+        let Ok(Payload::WiFi(parsed)) = lenient(wifi) else {
+            panic!("expected Wi-Fi");
+        };
+        assert_eq!(parsed.security, WiFiSecurity::Wpa);
+        assert!(parsed.hidden);
+        assert!(strict(wifi).is_err());
+        assert!(strict("WIFI:T:WPA;S:Home;P:short;;").is_err());
+
+        // LF line endings and a missing END line
+        let card = "BEGIN:VCARD\nVERSION:2.1\nN:Doe;John\nTEL;CELL:+1 555 0100\n";
+        let Ok(Payload::VCard(parsed)) = lenient(card) else {
+            panic!("expected vCard");
+        };
+        assert_eq!(parsed.version, VCardVersion::V3);
+        assert_eq!(
+            parsed.phones,
+            [(PhoneType::Cell, "+1 555 0100".to_string())]
+        );
+        assert!(strict(card).is_err());
+
+        assert!(matches!(
+            lenient("MECARD:n:Doe,John;ADR:1 Main St;"),
+            Ok(Payload::MeCard(_))
+        ));
+        assert!(strict("MECARD:N:Doe,John;ADR:1 Main St;;").is_err());
+
+        let otp = "otpauth://TOTP/Acme:bob?secret=jbswy3dpehpk3pxp&issuer=Other";
+        assert!(matches!(lenient(otp), Ok(Payload::Otp(_))));
+        assert!(strict(otp).is_err());
+
+        assert!(matches!(lenient("geo:91,0"), Ok(Payload::Geo(_))));
+        assert!(strict("geo:91,0").is_err());
+        assert!(strict("geo:1,2;u=35").is_ok());
+
+        assert_eq!(
+            lenient("www.example.com").unwrap(),
+            Payload::Url("www.example.com".to_string())
+        );
+        assert_eq!(
+            strict("www.example.com").unwrap(),
+            Payload::Text("www.example.com".to_string())
+        );
+        assert!(strict("https://exa mple.com").is_err());
+
+        // Malformed known formats fall back to text
+        assert_eq!(parse("WIFI:"), Payload::Text("WIFI:".to_string()));
+        assert_eq!(
+            parse("BCD\n002\n1\nSCT"),
+            Payload::Text("BCD\n002\n1\nSCT".to_string())
+        );
+        assert_eq!(
+            parse(" tel:+1 555 0100 "),
+            Payload::Phone("+1 555 0100".to_string())
+        );
+        assert_eq!(parse("12:30"), Payload::Text("12:30".to_string()));
+    }
+}
@@ -0,0 +1,528 @@
+//! Swiss QR-bill payment part in the SPC format, version 0200.
+use super::{invalid, mod97, PayloadError, QrPayload};
+use crate::qrcode::{EncodeTextOptions, QrCodeEcc, Version};
+
+// Limits from the Swiss Implementation Guidelines for the QR-bill, version 2.3.
+const MAX_PAYLOAD_CHARS: usize = 997;
+const MAX_VERSION: Version = Version::new(25);
+const MAX_AMOUNT_CENTS: u64 = 99_999_999_999;
+const MAX_MESSAGE_CHARS: usize = 140;
+const MAX_SCHEME_CHARS: usize = 100;
+const MAX_ALTERNATIVE_SCHEMES: usize = 2;
+// QR-IBANs use an institution identifier (characters 5 to 9) in this range.
+const QR_IID_RANGE: std::ops::RangeInclusive<u32> = 30000..=31999;
+
+/// Currency of a QR-bill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwissCurrency {
+    Chf,
+    Eur,
+}
+
+/// Payment reference of a QR-bill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwissReference<'a> {
+    /// `QRR`: 27-digit QR reference with a modulo 10 check digit, requires a QR-IBAN
+    Qrr(&'a str),
+    /// `SCOR`: ISO 11649 creditor reference such as `RF18539007547034`, requires a regular IBAN
+    Scor(&'a str),
+    /// `NON`: no reference, requires a regular IBAN
+    Non,
+}
+
+/// Structured address (address type `S`) of the creditor or debtor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SwissAddress<'a> {
+    /// Name or company, up to 70 characters
+    pub name: &'a str,
+    /// Street, up to 70 characters; may be empty
+    pub street: &'a str,
+    /// Building number, up to 16 characters; may be empty
+    pub building_number: &'a str,
+    /// Postal code without country prefix, up to 16 characters
+    pub postal_code: &'a str,
+    /// Town, up to 35 characters
+    pub town: &'a str,
+    /// Two-letter ISO 3166-1 country code
+    pub country: &'a str,
+}
+
+impl SwissAddress<'_> {
+    fn validate(&self, field: &'static str) -> Result<(), PayloadError> {
+        let parts = [
+            (self.name, 70, true),
+            (self.street, 70, false),
+            (self.building_number, 16, false),
+            (self.postal_code, 16, true),
+            (self.town, 35, true),
+        ];
+        for (value, max_chars, required) in parts {
+            if required && value.trim().is_empty() {
+                return Err(PayloadError::MissingField(field));
+            }
+            validate_text(field, value, max_chars)?;
+        }
+        if self.country.len() != 2 || !self.country.bytes().all(|b| b.is_ascii_uppercase()) {
+            return Err(invalid(
+                field,
+                "country must be two uppercase letters".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn lines(&self) -> [&str; 7] {
+        [
+            "S",
+            self.name,
+            self.street,
+            self.building_number,
+            self.postal_code,
+            self.town,
+            self.country,
+        ]
+    }
+}
+
+/// Builder for the payload of a Swiss QR-bill.
+///
+/// Encodes with the mandated [QrCodeEcc::Medium] up to version 25. Render the result at its
+/// prescribed 46 × 46 mm with the Swiss cross using [crate::render::SwissQrRenderer].
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::payload::{QrPayload, SwissAddress, SwissCurrency, SwissQrBill, SwissReference};
+///
+/// let creditor = SwissAddress {
+///     name: "Robert Schneider AG",
+///     street: "Rue du Lac",
+///     building_number: "1268",
+///     postal_code: "2501",
+///     town: "Biel",
+///     country: "CH",
+/// };
+/// let bill = SwissQrBill::new("CH44 3199 9123 0008 8901 2", creditor, SwissCurrency::Chf).unwrap()
+///     .with_amount_cents(194_975).unwrap()
+///     .with_reference(SwissReference::Qrr("210000000003139471430009017")).unwrap()
+///     .with_message("Order of 15 June 2020").unwrap();
+///
+/// let payload = bill.to_payload().unwrap();
+/// assert!(payload.starts_with("SPC\n0200\n1\nCH4431999123000889012\nS\nRobert Schneider AG\n"));
+/// assert!(payload.ends_with("QRR\n210000000003139471430009017\nOrder of 15 June 2020\nEPD"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SwissQrBill<'a> {
+    /// IBAN or QR-IBAN of the creditor, without spaces
+    pub iban: String,
+    pub creditor: SwissAddress<'a>,
+    /// Amount in cents, from 1 to 99 999 999 999; left open for the payer if not set
+    pub amount_cents: Option<u64>,
+    pub currency: SwissCurrency,
+    /// Ultimate debtor; left open for the payer if not set
+    pub debtor: Option<SwissAddress<'a>>,
+    /// Payment reference (defaults to [SwissReference::Non])
+    pub reference: SwissReference<'a>,
+    /// Unstructured message
+    pub message: Option<&'a str>,
+    /// Structured bill information, sharing the 140 character limit with the message
+    pub bill_information: Option<&'a str>,
+    /// Up to two alternative scheme parameters of 100 characters each
+    pub alternative_schemes: Vec<&'a str>,
+}
+
+impl<'a> SwissQrBill<'a> {
+    /// Creates a bill payable to `creditor`. Spaces in the IBAN are removed.
+    pub fn new(
+        iban: &str,
+        creditor: SwissAddress<'a>,
+        currency: SwissCurrency,
+    ) -> Result<Self, PayloadError> {
+        let bill = Self {
+            iban: iban
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>()
+                .to_ascii_uppercase(),
+            creditor,
+            amount_cents: None,
+            currency,
+            debtor: None,
+            reference: SwissReference::Non,
+            message: None,
+            bill_information: None,
+            alternative_schemes: Vec::new(),
+        };
+        validate_iban(&bill.iban)?;
+        creditor.validate("creditor")?;
+        Ok(bill)
+    }
+
+    pub fn with_amount_cents(mut self, cents: u64) -> Result<Self, PayloadError> {
+        validate_amount(cents)?;
+        self.amount_cents = Some(cents);
+        Ok(self)
+    }
+
+    pub fn with_debtor(mut self, debtor: SwissAddress<'a>) -> Result<Self, PayloadError> {
+        debtor.validate("debtor")?;
+        self.debtor = Some(debtor);
+        Ok(self)
+    }
+
+    /// Sets the reference, checking its check digits and that it matches the IBAN type
+    pub fn with_reference(mut self, reference: SwissReference<'a>) -> Result<Self, PayloadError> {
+        self.reference = reference;
+        self.validate_reference()?;
+        Ok(self)
+    }
+
+    pub fn with_message(mut self, message: &'a str) -> Result<Self, PayloadError> {
+        self.message = Some(message);
+        self.validate_messages()?;
+        Ok(self)
+    }
+
+    pub fn with_bill_information(mut self, information: &'a str) -> Result<Self, PayloadError> {
+        self.bill_information = Some(information);
+        self.validate_messages()?;
+        Ok(self)
+    }
+
+    /// Adds an alternative scheme parameter; at most two are allowed
+    pub fn with_alternative_scheme(mut self, scheme: &'a str) -> Result<Self, PayloadError> {
+        self.alternative_schemes.push(scheme);
+        self.validate_schemes()?;
+        Ok(self)
+    }
+
+    /// Returns true if the IBAN is a QR-IBAN, which requires a QR reference
+    pub fn is_qr_iban(&self) -> bool {
+        is_qr_iban(&self.iban)
+    }
+
+    /// Validates the fields and their combination before use
+    pub fn validate(&self) -> Result<(), PayloadError> {
+        validate_iban(&self.iban)?;
+        self.creditor.validate("creditor")?;
+        if let Some(debtor) = &self.debtor {
+            debtor.validate("debtor")?;
+        }
+        if let Some(cents) = self.amount_cents {
+            validate_amount(cents)?;
+        }
+        self.validate_reference()?;
+        self.validate_messages()?;
+        self.validate_schemes()
+    }
+
+    fn validate_reference(&self) -> Result<(), PayloadError> {
+        match (self.reference, self.is_qr_iban()) {
+            (SwissReference::Qrr(reference), true) => validate_qr_reference(reference),
+            (SwissReference::Scor(reference), false) => validate_creditor_reference(reference),
+            (SwissReference::Non, false) => Ok(()),
+            (SwissReference::Qrr(_), false) => Err(invalid(
+                "reference",
+                "QR references require a QR-IBAN".to_string(),
+            )),
+            (_, true) => Err(invalid(
+                "reference",
+                "a QR-IBAN requires a QR reference".to_string(),
+            )),
+        }
+    }
+
+    fn validate_messages(&self) -> Result<(), PayloadError> {
+        let message = self.message.unwrap_or_default();
+        let information = self.bill_information.unwrap_or_default();
+        validate_text("message", message, MAX_MESSAGE_CHARS)?;
+        validate_text("bill information", information, MAX_MESSAGE_CHARS)?;
+        if message.chars().count() + information.chars().count() > MAX_MESSAGE_CHARS {
+            return Err(invalid(
+                "message",
+                "message and bill information cannot exceed 140 characters together".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn validate_schemes(&self) -> Result<(), PayloadError> {
+        if self.alternative_schemes.len() > MAX_ALTERNATIVE_SCHEMES {
+            return Err(invalid(
+                "alternative scheme",
+                "at most two are allowed".to_string(),
+            ));
+        }
+        for scheme in &self.alternative_schemes {
+            if scheme.is_empty() {
+                return Err(invalid("alternative scheme", "cannot be empty".to_string()));
+            }
+            validate_text("alternative scheme", scheme, MAX_SCHEME_CHARS)?;
+        }
+        Ok(())
+    }
+}
+
+impl QrPayload for SwissQrBill<'_> {
+    fn to_payload(&self) -> Result<String, PayloadError> {
+        self.validate()?;
+
+        let amount = self
+            .amount_cents
+            .map(|cents| format!("{}.{:02}", cents / 100, cents % 100))
+            .unwrap_or_default();
+        let (reference_type, reference) = match self.reference {
+            SwissReference::Qrr(reference) => ("QRR", reference),
+            SwissReference::Scor(reference) => ("SCOR", reference),
+            SwissReference::Non => ("NON", ""),
+        };
+
+        // Header: QR type, version and coding type (UTF-8)
+        let mut lines = vec!["SPC", "0200", "1", &self.iban];
+        lines.extend(self.creditor.lines());
+        // The ultimate creditor is reserved for future use and left empty
+        lines.extend([""; 7]);
+        lines.push(&amount);
+        lines.push(match self.currency {
+            SwissCurrency::Chf => "CHF",
+            SwissCurrency::Eur => "EUR",
+        });
+        match &self.debtor {
+            Some(debtor) => lines.extend(debtor.lines()),
+            None => lines.extend([""; 7]),
+        }
+        lines.extend([reference_type, reference]);
+        lines.push(self.message.unwrap_or_default());
+        lines.push("EPD");
+        if self.bill_information.is_some() || !self.alternative_schemes.is_empty() {
+            lines.push(self.bill_information.unwrap_or_default());
+            lines.extend(&self.alternative_schemes);
+        }
+        let payload = lines.join("\n");
+
+        let len = payload.chars().count();
+        if len > MAX_PAYLOAD_CHARS {
+            return Err(invalid(
+                "payload",
+                format!(
+                    "is {} characters, QR-bills hold at most {}",
+                    len, MAX_PAYLOAD_CHARS
+                ),
+            ));
+        }
+        Ok(payload)
+    }
+
+    /// The QR-bill standard mandates error correction level M and at most version 25.
+    fn encode_options(&self) -> EncodeTextOptions {
+        EncodeTextOptions {
+            ecl: QrCodeEcc::Medium,
+            minversion: Version::MIN,
+            maxversion: MAX_VERSION,
+            mask: None,
+            boostecl: false,
+        }
+    }
+}
+
+fn is_qr_iban(iban: &str) -> bool {
+    iban.get(4..9)
+        .and_then(|iid| iid.parse::<u32>().ok())
+        .is_some_and(|iid| QR_IID_RANGE.contains(&iid))
+}
+
+// QR-bill text is limited to the Latin characters of the SPS character set.
+fn is_permitted_char(c: char) -> bool {
+    matches!(c, ' '..='~' | '\u{A0}'..='\u{17F}' | 'Ș' | 'ș' | 'Ț' | 'ț' | '€')
+}
+
+fn validate_text(field: &'static str, value: &str, max_chars: usize) -> Result<(), PayloadError> {
+    if value.chars().count() > max_chars {
+        return Err(invalid(
+            field,
+            format!("cannot be longer than {} characters", max_chars),
+        ));
+    }
+    if let Some(c) = value.chars().find(|&c| !is_permitted_char(c)) {
+        return Err(invalid(
+            field,
+            format!("character {:?} is not permitted in QR-bills", c),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_iban(iban: &str) -> Result<(), PayloadError> {
+    let well_formed = iban.len() == 21
+        && (iban.starts_with("CH") || iban.starts_with("LI"))
+        && iban.bytes().skip(2).all(|b| b.is_ascii_alphanumeric());
+    if !well_formed {
+        return Err(invalid(
+            "IBAN",
+            "must be a 21 character Swiss or Liechtenstein IBAN".to_string(),
+        ));
+    }
+    if mod97(iban) != 1 {
+        return Err(invalid("IBAN", "checksum does not match".to_string()));
+    }
+    Ok(())
+}
+
+fn validate_amount(cents: u64) -> Result<(), PayloadError> {
+    if !(1..=MAX_AMOUNT_CENTS).contains(&cents) {
+        return Err(invalid(
+            "amount",
+            "must be between 0.01 and 999999999.99".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// QR references are 27 digits whose last digit is a recursive modulo 10 check digit.
+fn validate_qr_reference(reference: &str) -> Result<(), PayloadError> {
+    const TABLE: [u32; 10] = [0, 9, 4, 6, 8, 2, 7, 1, 3, 5];
+    if reference.len() != 27 || !reference.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid(
+            "reference",
+            "QR references must be 27 digits".to_string(),
+        ));
+    }
+    let digits: Vec<u32> = reference.bytes().map(|b| u32::from(b - b'0')).collect();
+    let carry = digits[..26]
+        .iter()
+        .fold(0, |carry, &digit| TABLE[((carry + digit) % 10) as usize]);
+    if (10 - carry) % 10 != digits[26] {
+        return Err(invalid(
+            "reference",
+            "QR reference check digit does not match".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_creditor_reference(reference: &str) -> Result<(), PayloadError> {
+    let well_formed = (5..=25).contains(&reference.len())
+        && reference.starts_with("RF")
+        && reference.bytes().all(|b| b.is_ascii_alphanumeric());
+    if !well_formed || mod97(reference) != 1 {
+        return Err(invalid(
+            "reference",
+            "must be an ISO 11649 creditor reference with matching check digits".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QR_IBAN: &str = "CH4431999123000889012";
+    const IBAN: &str = "CH5800791123000889012";
+
+    fn creditor() -> SwissAddress<'static> {
+        SwissAddress {
+            name: "Robert Schneider AG",
+            street: "Rue du Lac",
+            building_number: "1268",
+            postal_code: "2501",
+            town: "Biel",
+            country: "CH",
+        }
+    }
+
+    #[test]
+    fn test_payload_elements() {
+        let bill = SwissQrBill::new(IBAN, creditor(), SwissCurrency::Eur)
+            .unwrap()
+            .with_debtor(SwissAddress {
+                name: "Pia-Maria Rutschmann-Schnyder",
+                street: "Grosse Marktgasse",
+                building_number: "28",
+                postal_code: "9400",
+                town: "Rorschach",
+                country: "CH",
+            })
+            .unwrap()
+            .with_reference(SwissReference::Scor("RF18539007547034"))
+            .unwrap()
+            .with_bill_information("//S1/10/10201409/11/200701")
+            .unwrap()
+            .with_alternative_scheme("eBill/B/41010560425610173")
+            .unwrap();
+        let payload = bill.to_payload().unwrap();
+        let lines: Vec<&str> = payload.split('\n').collect();
+        assert_eq!(lines.len(), 33);
+        assert_eq!(lines[..4], ["SPC", "0200", "1", IBAN]);
+        assert!(lines[11..18].iter().all(|line| line.is_empty()));
+        assert_eq!(lines[18..20], ["", "EUR"]);
+        assert_eq!(lines[20], "S");
+        assert_eq!(lines[27..31], ["SCOR", "RF18539007547034", "", "EPD"]);
+        assert_eq!(
+            lines[31..],
+            ["//S1/10/10201409/11/200701", "eBill/B/41010560425610173"]
+        );
+
+        let minimal = SwissQrBill::new(IBAN, creditor(), SwissCurrency::Chf)
+            .unwrap()
+            .to_payload()
+            .unwrap();
+        assert_eq!(minimal.split('\n').count(), 31);
+        assert!(minimal.ends_with("\nCHF\n\n\n\n\n\n\n\nNON\n\n\nEPD"));
+    }
+
+    #[test]
+    fn test_reference_rules() {
+        let qr_bill = SwissQrBill::new(QR_IBAN, creditor(), SwissCurrency::Chf).unwrap();
+        assert!(qr_bill.is_qr_iban());
+        assert!(qr_bill.validate().is_err());
+        assert!(qr_bill
+            .clone()
+            .with_reference(SwissReference::Qrr("210000000003139471430009018"))
+            .is_err());
+        assert!(qr_bill
+            .with_reference(SwissReference::Qrr("210000000003139471430009017"))
+            .is_ok());
+
+        let bill = SwissQrBill::new(IBAN, creditor(), SwissCurrency::Chf).unwrap();
+        assert!(!bill.is_qr_iban());
+        assert!(bill
+            .clone()
+            .with_reference(SwissReference::Qrr("210000000003139471430009017"))
+            .is_err());
+        assert!(bill
+            .with_reference(SwissReference::Scor("RF19539007547034"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_field_validation() {
+        assert!(
+            SwissQrBill::new("DE89370400440532013000", creditor(), SwissCurrency::Chf).is_err()
+        );
+        assert!(SwissQrBill::new("CH5800791123000889013", creditor(), SwissCurrency::Chf).is_err());
+        let nameless = SwissAddress {
+            name: "",
+            ..creditor()
+        };
+        assert!(SwissQrBill::new(IBAN, nameless, SwissCurrency::Chf).is_err());
+
+        let bill = SwissQrBill::new(IBAN, creditor(), SwissCurrency::Chf).unwrap();
+        assert!(bill.clone().with_amount_cents(0).is_err());
+        assert!(bill.clone().with_message("Zahlung 😀").is_err());
+        let long = "x".repeat(100);
+        assert!(bill
+            .clone()
+            .with_message(&long)
+            .unwrap()
+            .with_bill_information(&long)
+            .is_err());
+        assert!(bill
+            .with_alternative_scheme("a")
+            .unwrap()
+            .with_alternative_scheme("b")
+            .unwrap()
+            .with_alternative_scheme("c")
+            .is_err());
+    }
+}
@@ -0,0 +1,645 @@
+//! Wi-Fi network credentials in the `WIFI:` format understood by Android and iOS cameras.
+use super::{escape, invalid, split_unescaped, unescape, ParseMode, PayloadError, QrPayload};
+
+// Characters that must be escaped with a backslash inside `WIFI:` fields.
+const SPECIAL_CHARS: &[char] = &['\\', ';', ',', ':', '"'];
+const MAX_SSID_BYTES: usize = 32;
+
+/// Authentication type of a Wi-Fi network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WiFiSecurity {
+    /// Open network without a password (`T:nopass`).
+    Open,
+    /// WEP with a 5 or 13 character key, or a 10 or 26 digit hex key (`T:WEP`).
+    Wep,
+    /// WPA or WPA2 Personal with an 8 to 63 character passphrase or a 64 digit hex key (`T:WPA`).
+    Wpa,
+    /// WPA3 Personal using SAE (`T:SAE`).
+    Wpa3,
+    /// WPA2 Enterprise using the EAP fields (`T:WPA2-EAP`).
+    Wpa2Enterprise,
+}
+
+impl WiFiSecurity {
+    const ALL: [WiFiSecurity; 5] = [
+        WiFiSecurity::Open,
+        WiFiSecurity::Wep,
+        WiFiSecurity::Wpa,
+        WiFiSecurity::Wpa3,
+        WiFiSecurity::Wpa2Enterprise,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            WiFiSecurity::Open => "nopass",
+            WiFiSecurity::Wep => "WEP",
+            WiFiSecurity::Wpa => "WPA",
+            WiFiSecurity::Wpa3 => "SAE",
+            WiFiSecurity::Wpa2Enterprise => "WPA2-EAP",
+        }
+    }
+}
+
+/// EAP method of a WPA2 Enterprise network (`E:` field).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EapMethod {
+    Peap,
+    Tls,
+    Ttls,
+    Pwd,
+    Sim,
+    Aka,
+    AkaPrime,
+}
+
+impl EapMethod {
+    const ALL: [EapMethod; 7] = [
+        EapMethod::Peap,
+        EapMethod::Tls,
+        EapMethod::Ttls,
+        EapMethod::Pwd,
+        EapMethod::Sim,
+        EapMethod::Aka,
+        EapMethod::AkaPrime,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            EapMethod::Peap => "PEAP",
+            EapMethod::Tls => "TLS",
+            EapMethod::Ttls => "TTLS",
+            EapMethod::Pwd => "PWD",
+            EapMethod::Sim => "SIM",
+            EapMethod::Aka => "AKA",
+            EapMethod::AkaPrime => "AKA_PRIME",
+        }
+    }
+
+    // Methods that authenticate with an identity and password rather than a certificate or SIM.
+    fn uses_password(self) -> bool {
+        matches!(self, EapMethod::Peap | EapMethod::Ttls | EapMethod::Pwd)
+    }
+}
+
+/// Phase 2 (inner) authentication of a PEAP or TTLS network (`PH2:` field).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase2Method {
+    Pap,
+    Mschap,
+    Mschapv2,
+    Gtc,
+}
+
+impl Phase2Method {
+    const ALL: [Phase2Method; 4] = [
+        Phase2Method::Pap,
+        Phase2Method::Mschap,
+        Phase2Method::Mschapv2,
+        Phase2Method::Gtc,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Phase2Method::Pap => "PAP",
+            Phase2Method::Mschap => "MSCHAP",
+            Phase2Method::Mschapv2 => "MSCHAPV2",
+            Phase2Method::Gtc => "GTC",
+        }
+    }
+}
+
+/// Builder for a `WIFI:` network payload.
+///
+/// Special characters (`\`, `;`, `,`, `:` and `"`) in the SSID, password and identities are escaped,
+/// and an SSID or passphrase that consists only of hex digits is quoted so scanners do not mistake
+/// it for a raw hex key.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::payload::{EapMethod, Phase2Method, QrPayload, WiFi, WiFiSecurity};
+///
+/// let guest = WiFi::new("Guest").unwrap()
+///     .with_security(WiFiSecurity::Wpa)
+///     .with_password("p@ss:word").unwrap()
+///     .with_hidden(true);
+/// assert_eq!(guest.to_payload().unwrap(), "WIFI:T:WPA;S:Guest;P:p@ss\\:word;H:true;;");
+///
+/// let office = WiFi::new("Corp").unwrap()
+///     .with_security(WiFiSecurity::Wpa2Enterprise)
+///     .with_eap_method(EapMethod::Peap)
+///     .with_phase2_method(Phase2Method::Mschapv2)
+///     .with_identity("alice").unwrap()
+///     .with_password("secret").unwrap();
+/// assert_eq!(
+///     office.to_payload().unwrap(),
+///     "WIFI:T:WPA2-EAP;S:Corp;E:PEAP;PH2:MSCHAPV2;I:alice;P:secret;;"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct WiFi<'a> {
+    /// Network name, 1 to 32 bytes
+    pub ssid: &'a str,
+    /// Authentication type (defaults to [WiFiSecurity::Wpa])
+    pub security: WiFiSecurity,
+    /// Password or key, required for every security type except [WiFiSecurity::Open]
+    pub password: Option<&'a str>,
+    /// Whether the network does not broadcast its SSID (defaults to false)
+    pub hidden: bool,
+    /// EAP method, required for [WiFiSecurity::Wpa2Enterprise]
+    pub eap_method: Option<EapMethod>,
+    /// Phase 2 authentication for PEAP and TTLS
+    pub phase2_method: Option<Phase2Method>,
+    /// Enterprise identity (user name)
+    pub identity: Option<&'a str>,
+    /// Enterprise anonymous (outer) identity
+    pub anonymous_identity: Option<&'a str>,
+}
+
+impl<'a> WiFi<'a> {
+    pub fn new(ssid: &'a str) -> Result<Self, PayloadError> {
+        let wifi = Self {
+            ssid,
+            security: WiFiSecurity::Wpa,
+            password: None,
+            hidden: false,
+            eap_method: None,
+            phase2_method: None,
+            identity: None,
+            anonymous_identity: None,
+        };
+        wifi.validate_ssid()?;
+        Ok(wifi)
+    }
+
+    pub fn with_security(mut self, security: WiFiSecurity) -> Self {
+        self.security = security;
+        self
+    }
+
+    pub fn with_password(mut self, password: &'a str) -> Result<Self, PayloadError> {
+        if password.is_empty() {
+            return Err(invalid("password", "cannot be empty"));
+        }
+        self.password = Some(password);
+        Ok(self)
+    }
+
+    pub fn with_hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    pub fn with_eap_method(mut self, method: EapMethod) -> Self {
+        self.eap_method = Some(method);
+        self
+    }
+
+    pub fn with_phase2_method(mut self, method: Phase2Method) -> Self {
+        self.phase2_method = Some(method);
+        self
+    }
+
+    pub fn with_identity(mut self, identity: &'a str) -> Result<Self, PayloadError> {
+        if identity.is_empty() {
+            return Err(invalid("identity", "cannot be empty"));
+        }
+        self.identity = Some(identity);
+        Ok(self)
+    }
+
+    pub fn with_anonymous_identity(mut self, identity: &'a str) -> Result<Self, PayloadError> {
+        if identity.is_empty() {
+            return Err(invalid("anonymous identity", "cannot be empty"));
+        }
+        self.anonymous_identity = Some(identity);
+        Ok(self)
+    }
+
+    /// Validates the combination of fields before use
+    pub fn validate(&self) -> Result<(), PayloadError> {
+        self.validate_ssid()?;
+
+        let enterprise = self.security == WiFiSecurity::Wpa2Enterprise;
+        if !enterprise
+            && (self.eap_method.is_some()
+                || self.phase2_method.is_some()
+                || self.identity.is_some()
+                || self.anonymous_identity.is_some())
+        {
+            return Err(invalid(
+                "security",
+                "EAP fields require WPA2 Enterprise security",
+            ));
+        }
+
+        match self.security {
+            WiFiSecurity::Open => {
+                if self.password.is_some() {
+                    return Err(invalid("password", "open networks cannot have a password"));
+                }
+            }
+            WiFiSecurity::Wep => {
+                let key = self
+                    .password
+                    .ok_or(PayloadError::MissingField("password"))?;
+                let valid = match key.len() {
+                    5 | 13 => key.is_ascii(),
+                    10 | 26 => is_hex(key),
+                    _ => false,
+                };
+                if !valid {
+                    return Err(invalid(
+                        "password",
+                        "WEP keys must be 5 or 13 ASCII characters or 10 or 26 hex digits",
+                    ));
+                }
+            }
+            WiFiSecurity::Wpa => {
+                let key = self
+                    .password
+                    .ok_or(PayloadError::MissingField("password"))?;
+                let passphrase =
+                    (8..=63).contains(&key.len()) && key.bytes().all(|b| (32..=126).contains(&b));
+                let raw_key = key.len() == 64 && is_hex(key);
+                if !(passphrase || raw_key) {
+                    return Err(invalid(
+                        "password",
+                        "WPA passphrases must be 8 to 63 printable ASCII characters or 64 hex digits",
+                    ));
+                }
+            }
+            WiFiSecurity::Wpa3 => {
+                self.password
+                    .ok_or(PayloadError::MissingField("password"))?;
+            }
+            WiFiSecurity::Wpa2Enterprise => {
+                let method = self
+                    .eap_method
+                    .ok_or(PayloadError::MissingField("EAP method"))?;
+                if self.phase2_method.is_some()
+                    && !matches!(method, EapMethod::Peap | EapMethod::Ttls)
+                {
+                    return Err(invalid(
+                        "phase 2 method",
+                        "only PEAP and TTLS use phase 2 authentication",
+                    ));
+                }
+                if method.uses_password() {
+                    self.identity
+                        .ok_or(PayloadError::MissingField("identity"))?;
+                    self.password
+                        .ok_or(PayloadError::MissingField("password"))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_ssid(&self) -> Result<(), PayloadError> {
+        if self.ssid.is_empty() || self.ssid.len() > MAX_SSID_BYTES {
+            return Err(invalid("SSID", "must be between 1 and 32 bytes"));
+        }
+        Ok(())
+    }
+}
+
+impl QrPayload for WiFi<'_> {
+    fn to_payload(&self) -> Result<String, PayloadError> {
+        self.validate()?;
+
+        let mut payload = format!(
+            "WIFI:T:{};S:{};",
+            self.security.as_str(),
+            quote_if_hex(self.ssid)
+        );
+        if let Some(method) = self.eap_method {
+            payload.push_str(&format!("E:{};", method.as_str()));
+        }
+        if let Some(method) = self.phase2_method {
+            payload.push_str(&format!("PH2:{};", method.as_str()));
+        }
+        if let Some(identity) = self.anonymous_identity {
+            payload.push_str(&format!("A:{};", escape(identity, SPECIAL_CHARS)));
+        }
+        if let Some(identity) = self.identity {
+            payload.push_str(&format!("I:{};", escape(identity, SPECIAL_CHARS)));
+        }
+        if let Some(password) = self.password {
+            // Raw WEP and WPA keys are hex by definition and must stay unquoted
+            let raw_key = match self.security {
+                WiFiSecurity::Wep => matches!(password.len(), 10 | 26),
+                WiFiSecurity::Wpa => password.len() == 64,
+                _ => false,
+            };
+            let password = if raw_key {
+                password.to_string()
+            } else {
+                quote_if_hex(password)
+            };
+            payload.push_str(&format!("P:{};", password));
+        }
+        if self.hidden {
+            payload.push_str("H:true;");
+        }
+        payload.push(';');
+        Ok(payload)
+    }
+}
+
+/// Wi-Fi network read from a `WIFI:` payload by [parse](super::parse).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedWiFi {
+    pub ssid: String,
+    pub security: WiFiSecurity,
+    pub password: Option<String>,
+    pub hidden: bool,
+    pub eap_method: Option<EapMethod>,
+    pub phase2_method: Option<Phase2Method>,
+    pub identity: Option<String>,
+    pub anonymous_identity: Option<String>,
+}
+
+impl ParsedWiFi {
+    /// Returns a validated builder with the same fields
+    pub fn to_builder(&self) -> Result<WiFi<'_>, PayloadError> {
+        let wifi = WiFi {
+            ssid: &self.ssid,
+            security: self.security,
+            password: self.password.as_deref(),
+            hidden: self.hidden,
+            eap_method: self.eap_method,
+            phase2_method: self.phase2_method,
+            identity: self.identity.as_deref(),
+            anonymous_identity: self.anonymous_identity.as_deref(),
+        };
+        wifi.validate()?;
+        Ok(wifi)
+    }
+
+    // Parses the fields following `WIFI:`. A missing `T:` field means an open network.
+    pub(super) fn parse(body: &str, mode: ParseMode) -> Result<Self, PayloadError> {
+        let strict = mode == ParseMode::Strict;
+        let mut fields = split_unescaped(body, ';');
+        if strict {
+            if !body.ends_with(";;") || fields.iter().rev().skip(2).any(|f| f.is_empty()) {
+                return Err(invalid("payload", "fields must end with ';;'"));
+            }
+            fields.truncate(fields.len() - 2);
+        }
+
+        let mut wifi = Self {
+            ssid: String::new(),
+            security: WiFiSecurity::Open,
+            password: None,
+            hidden: false,
+            eap_method: None,
+            phase2_method: None,
+            identity: None,
+            anonymous_identity: None,
+        };
+        let mut seen = Vec::new();
+        for field in fields.into_iter().filter(|field| !field.is_empty()) {
+            let Some((key, raw)) = field.split_once(':') else {
+                if strict {
+                    return Err(invalid("payload", "fields must be written as KEY:value"));
+                }
+                continue;
+            };
+            let key = if strict {
+                key.to_string()
+            } else {
+                key.to_ascii_uppercase()
+            };
+            if strict && seen.contains(&key) {
+                return Err(invalid("payload", "fields cannot be repeated"));
+            }
+            match key.as_str() {
+                "T" => wifi.security = parse_security(raw, mode)?,
+                "S" => wifi.ssid = unquote("SSID", raw, mode)?,
+                "P" if !strict && raw.is_empty() => {}
+                "P" => wifi.password = Some(unquote("password", raw, mode)?),
+                "H" => wifi.hidden = parse_hidden(raw, mode)?,
+                "E" => {
+                    wifi.eap_method = Some(parse_named(
+                        "EAP method",
+                        raw,
+                        EapMethod::ALL,
+                        EapMethod::as_str,
+                        mode,
+                    )?)
+                }
+                "PH2" => {
+                    wifi.phase2_method = Some(parse_named(
+                        "phase 2 method",
+                        raw,
+                        Phase2Method::ALL,
+                        Phase2Method::as_str,
+                        mode,
+                    )?)
+                }
+                "I" => wifi.identity = Some(unquote("identity", raw, mode)?),
+                "A" => wifi.anonymous_identity = Some(unquote("anonymous identity", raw, mode)?),
+                _ if strict => return Err(invalid("payload", "contains an unknown field")),
+                _ => {}
+            }
+            seen.push(key);
+        }
+
+        if wifi.ssid.is_empty() {
+            return Err(PayloadError::MissingField("SSID"));
+        }
+        if strict {
+            wifi.to_builder()?;
+        }
+        Ok(wifi)
+    }
+}
+
+fn parse_security(raw: &str, mode: ParseMode) -> Result<WiFiSecurity, PayloadError> {
+    if mode == ParseMode::Lenient {
+        // Spellings used by other generators
+        match raw.to_ascii_uppercase().as_str() {
+            "" | "NONE" | "OPEN" => return Ok(WiFiSecurity::Open),
+            "WPA2" | "WPA/WPA2" | "WPA2-PSK" | "WPA-PSK" => return Ok(WiFiSecurity::Wpa),
+            "WPA3" | "WPA3-SAE" => return Ok(WiFiSecurity::Wpa3),
+            "WPA2-ENTERPRISE" | "WPA-EAP" | "EAP" => return Ok(WiFiSecurity::Wpa2Enterprise),
+            _ => {}
+        }
+    }
+    parse_named(
+        "security",
+        raw,
+        WiFiSecurity::ALL,
+        WiFiSecurity::as_str,
+        mode,
+    )
+}
+
+fn parse_hidden(raw: &str, mode: ParseMode) -> Result<bool, PayloadError> {
+    match raw {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ if mode == ParseMode::Lenient => Ok(raw.eq_ignore_ascii_case("true") || raw == "1"),
+        _ => Err(invalid("hidden", "must be true or false")),
+    }
+}
+
+// Finds the variant written as `raw`, ignoring case in lenient mode.
+fn parse_named<T: Copy, const N: usize>(
+    field: &'static str,
+    raw: &str,
+    variants: [T; N],
+    name: fn(T) -> &'static str,
+    mode: ParseMode,
+) -> Result<T, PayloadError> {
+    variants
+        .into_iter()
+        .find(|&variant| match mode {
+            ParseMode::Strict => name(variant) == raw,
+            ParseMode::Lenient => name(variant).eq_ignore_ascii_case(raw),
+        })
+        .ok_or_else(|| invalid(field, "is not a known value"))
+}
+
+// Reverses `quote_if_hex`: quoted values are taken as-is, others are unescaped.
+fn unquote(field: &'static str, raw: &str, mode: ParseMode) -> Result<String, PayloadError> {
+    let quoted = raw
+        .strip_prefix('"')
+        .and_then(|raw| raw.strip_suffix('"'))
+        .filter(|inner| !inner.contains(['"', '\\']));
+    match quoted {
+        Some(inner) => Ok(inner.to_string()),
+        None => unescape(field, raw, SPECIAL_CHARS, mode),
+    }
+}
+
+fn is_hex(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+// Escapes the value and wraps it in double quotes if it could be mistaken for a hex string.
+fn quote_if_hex(value: &str) -> String {
+    if is_hex(value) {
+        format!("\"{}\"", value)
+    } else {
+        escape(value, SPECIAL_CHARS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qrcode::Version;
+
+    #[test]
+    fn test_escaping_and_quoting() {
+        let wifi = WiFi::new("My;Net,\\\"1\"")
+            .unwrap()
+            .with_password("deadbeef")
+            .unwrap();
+        assert_eq!(
+            wifi.to_payload().unwrap(),
+            "WIFI:T:WPA;S:My\\;Net\\,\\\\\\\"1\\\";P:\"deadbeef\";;"
+        );
+
+        let raw = "a".repeat(64);
+        let wifi = WiFi::new("CAFE").unwrap().with_password(&raw).unwrap();
+        assert_eq!(
+            wifi.to_payload().unwrap(),
+            format!("WIFI:T:WPA;S:\"CAFE\";P:{};;", raw)
+        );
+    }
+
+    #[test]
+    fn test_security_types() {
+        let open = WiFi::new("Coffee")
+            .unwrap()
+            .with_security(WiFiSecurity::Open);
+        assert_eq!(open.to_payload().unwrap(), "WIFI:T:nopass;S:Coffee;;");
+        assert!(open
+            .clone()
+            .with_password("secret")
+            .unwrap()
+            .validate()
+            .is_err());
+
+        let wep = WiFi::new("Old")
+            .unwrap()
+            .with_security(WiFiSecurity::Wep)
+            .with_password("0123456789")
+            .unwrap();
+        assert_eq!(wep.to_payload().unwrap(), "WIFI:T:WEP;S:Old;P:0123456789;;");
+        let wep = wep.with_password("abc").unwrap();
+        assert!(wep.validate().is_err());
+
+        let wpa3 = WiFi::new("Home")
+            .unwrap()
+            .with_security(WiFiSecurity::Wpa3)
+            .with_password("pw")
+            .unwrap()
+            .with_hidden(true);
+        assert_eq!(
+            wpa3.to_payload().unwrap(),
+            "WIFI:T:SAE;S:Home;P:pw;H:true;;"
+        );
+
+        let short = WiFi::new("Home").unwrap().with_password("short").unwrap();
+        assert!(short.validate().is_err());
+        assert!(matches!(
+            WiFi::new("Home").unwrap().validate(),
+            Err(PayloadError::MissingField("password"))
+        ));
+    }
+
+    #[test]
+    fn test_enterprise() {
+        let wifi = WiFi::new("Corp")
+            .unwrap()
+            .with_security(WiFiSecurity::Wpa2Enterprise)
+            .with_eap_method(EapMethod::Ttls)
+            .with_phase2_method(Phase2Method::Pap)
+            .with_anonymous_identity("anon@corp")
+            .unwrap()
+            .with_identity("bob:corp")
+            .unwrap()
+            .with_password("hunter2")
+            .unwrap();
+        assert_eq!(
+            wifi.to_payload().unwrap(),
+            "WIFI:T:WPA2-EAP;S:Corp;E:TTLS;PH2:PAP;A:anon@corp;I:bob\\:corp;P:hunter2;;"
+        );
+
+        let tls = WiFi::new("Corp")
+            .unwrap()
+            .with_security(WiFiSecurity::Wpa2Enterprise)
+            .with_eap_method(EapMethod::Tls);
+        assert!(tls.validate().is_ok());
+        assert!(tls
+            .with_phase2_method(Phase2Method::Gtc)
+            .validate()
+            .is_err());
+
+        let not_enterprise = WiFi::new("Corp")
+            .unwrap()
+            .with_password("password1")
+            .unwrap()
+            .with_eap_method(EapMethod::Peap);
+        assert!(not_enterprise.validate().is_err());
+    }
+
+    #[test]
+    fn test_ssid_validation_and_encoding() {
+        assert!(WiFi::new("").is_err());
+        assert!(WiFi::new(&"x".repeat(33)).is_err());
+
+        let wifi = WiFi::new("Guest")
+            .unwrap()
+            .with_password("password1")
+            .unwrap();
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let qr = wifi.encode(&mut tempbuffer, &mut outbuffer).unwrap();
+        assert!(qr.size() >= 21);
+    }
+}
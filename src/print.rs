@@ -0,0 +1,443 @@
+//! Physical sizing for print.
+//!
+//! Raster renderers size codes in pixels per module. For print, the size is usually given in
+//! millimetres or inches instead, together with the resolution of the printer. [PrintSize] converts
+//! such a request into a whole number of pixels per module, since fractional modules would blur
+//! their edges, and [PrintLayout] reports how far the rendered symbol is from the requested width.
+//! [PrintRenderer] renders at that size, and [PrintImage::write_png] stores the resolution in the
+//! PNG `pHYs` chunk so that print and layout software place the image at its physical size.
+//!
+//! # Example
+//!
+//! ```rust
+//! use qirust::print::{Length, PrintRenderer, PrintSize};
+//! use qirust::qrcode::{EncodeTextOptions, QrCode, QrCodeEcc, Version};
+//! use qirust::render::{QrStyle, Renderer};
+//!
+//! let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+//! let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+//! let qr = QrCode::encode_text(
+//!     "https://example.com",
+//!     &mut tempbuffer,
+//!     &mut outbuffer,
+//!     EncodeTextOptions {
+//!         ecl: QrCodeEcc::Medium,
+//!         minversion: Version::MIN,
+//!         maxversion: Version::MAX,
+//!         mask: None,
+//!         boostecl: true,
+//!     },
+//! ).unwrap();
+//!
+//! // A 25 mm symbol with a 2.5 mm quiet zone, printed at 600 dpi
+//! let size = PrintSize::new(Length::Mm(25.0), 600).unwrap()
+//!     .with_quiet_zone(Length::Mm(2.5)).unwrap()
+//!     .with_max_error(Length::Mm(0.5)).unwrap();
+//! let print = PrintRenderer::new(size).render(&qr, &QrStyle::new()).unwrap();
+//! assert_eq!(print.layout.module_px, 24); // 25 modules of 1.016 mm
+//! assert!(print.layout.symbol_error_mm().abs() < 0.5);
+//!
+//! let mut png = Vec::new();
+//! print.write_png(&mut png).unwrap();
+//! # assert!(png.windows(4).any(|chunk| chunk == b"pHYs"));
+//! ```
+use crate::checksum::crc32;
+use crate::helper::{HelperError, MAX_UPSCALE_FACTOR};
+use crate::qrcode::QrCode;
+use crate::render::{render_raster, QrStyle, Renderer};
+use image::{codecs::png::PngEncoder, RgbaImage};
+use std::io::Write;
+
+const MM_PER_INCH: f64 = 25.4;
+const MAX_PRINT_DPI: u32 = 2400;
+// Signature (8 bytes) plus the IHDR chunk (length, type, 13 data bytes and CRC), which must come
+// first in every PNG file.
+const PNG_HEADER_LEN: usize = 8 + 4 + 4 + 13 + 4;
+
+/// A physical length in millimetres or inches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    /// Millimetres
+    Mm(f64),
+    /// Inches
+    Inch(f64),
+}
+
+impl Length {
+    /// The length in millimetres.
+    pub fn to_mm(self) -> f64 {
+        match self {
+            Length::Mm(mm) => mm,
+            Length::Inch(inches) => inches * MM_PER_INCH,
+        }
+    }
+
+    /// The length in inches.
+    pub fn to_inches(self) -> f64 {
+        match self {
+            Length::Mm(mm) => mm / MM_PER_INCH,
+            Length::Inch(inches) => inches,
+        }
+    }
+
+    /// The length in (fractional) pixels at `dpi`.
+    pub fn to_px(self, dpi: u32) -> f64 {
+        self.to_inches() * f64::from(dpi)
+    }
+
+    // Checks that the length is finite and not negative.
+    fn check(self, name: &str) -> Result<(), HelperError> {
+        let mm = self.to_mm();
+        if !mm.is_finite() || mm < 0.0 {
+            return Err(HelperError::InvalidInput(format!(
+                "{} must be a finite length of at least zero",
+                name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A requested physical size of a code at a printing resolution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrintSize {
+    /// Width of the symbol itself, without the quiet zone
+    pub symbol_width: Length,
+    /// Width of the quiet zone on each side (defaults to none)
+    pub quiet_zone: Length,
+    /// Printing resolution in dots per inch
+    pub dpi: u32,
+    /// Largest accepted difference between the requested and the rendered symbol width, or `None`
+    /// to accept any rounding
+    pub max_error: Option<Length>,
+}
+
+impl PrintSize {
+    pub fn new(symbol_width: Length, dpi: u32) -> Result<Self, HelperError> {
+        let size = Self {
+            symbol_width,
+            quiet_zone: Length::Mm(0.0),
+            dpi,
+            max_error: None,
+        };
+        size.validate()?;
+        Ok(size)
+    }
+
+    pub fn with_quiet_zone(mut self, quiet_zone: Length) -> Result<Self, HelperError> {
+        quiet_zone.check("Quiet zone")?;
+        self.quiet_zone = quiet_zone;
+        Ok(self)
+    }
+
+    pub fn with_max_error(mut self, max_error: Length) -> Result<Self, HelperError> {
+        max_error.check("Maximum error")?;
+        self.max_error = Some(max_error);
+        Ok(self)
+    }
+
+    /// Validates the size before use
+    pub fn validate(&self) -> Result<(), HelperError> {
+        if !(1..=MAX_PRINT_DPI).contains(&self.dpi) {
+            return Err(HelperError::InvalidInput(format!(
+                "dpi must be between 1 and {}",
+                MAX_PRINT_DPI
+            )));
+        }
+        self.symbol_width.check("Symbol width")?;
+        if self.symbol_width.to_mm() == 0.0 {
+            return Err(HelperError::InvalidInput(
+                "Symbol width must be greater than zero".to_string(),
+            ));
+        }
+        self.quiet_zone.check("Quiet zone")?;
+        if let Some(max_error) = self.max_error {
+            max_error.check("Maximum error")?;
+        }
+        Ok(())
+    }
+
+    /// Computes the pixel layout for a symbol of `modules` × `modules` modules, such as
+    /// [QrCode::size].
+    ///
+    /// The module size is the whole number of pixels that brings the symbol closest to the
+    /// requested width. Fails if the resolution is too low to give every module a pixel, if a module
+    /// would need more than 100 pixels, the largest module size of a [QrStyle], or if the rounding
+    /// error exceeds [PrintSize::max_error].
+    pub fn layout(&self, modules: u32) -> Result<PrintLayout, HelperError> {
+        self.validate()?;
+        if modules == 0 {
+            return Err(HelperError::InvalidInput(
+                "Symbol must have at least one module".to_string(),
+            ));
+        }
+        let module_px = (self.symbol_width.to_px(self.dpi) / f64::from(modules)).round();
+        if module_px < 1.0 {
+            return Err(HelperError::InvalidInput(format!(
+                "{} dpi is too low for {} modules in {:.3} mm",
+                self.dpi,
+                modules,
+                self.symbol_width.to_mm()
+            )));
+        }
+        if module_px > f64::from(MAX_UPSCALE_FACTOR) {
+            return Err(HelperError::InvalidInput(format!(
+                "{:.3} mm at {} dpi needs {} pixels for each of {} modules, more than the maximum \
+                 of {}; lower the resolution or the symbol width",
+                self.symbol_width.to_mm(),
+                self.dpi,
+                module_px,
+                modules,
+                MAX_UPSCALE_FACTOR
+            )));
+        }
+        let layout = PrintLayout {
+            modules,
+            module_px: module_px as u32,
+            quiet_zone_px: self.quiet_zone.to_px(self.dpi).round() as u32,
+            dpi: self.dpi,
+            requested_mm: self.symbol_width.to_mm(),
+        };
+
+        if let Some(max_error) = self.max_error {
+            let error = layout.symbol_error_mm();
+            if error.abs() > max_error.to_mm() {
+                return Err(HelperError::InvalidInput(format!(
+                    "{:.3} mm at {} dpi renders {:.3} mm wide, which is off by more than {:.3} mm",
+                    layout.requested_mm,
+                    self.dpi,
+                    layout.symbol_width_mm(),
+                    max_error.to_mm()
+                )));
+            }
+        }
+        Ok(layout)
+    }
+}
+
+/// The pixel layout chosen by [PrintSize::layout].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrintLayout {
+    /// Number of modules across the symbol
+    pub modules: u32,
+    /// Pixels per module
+    pub module_px: u32,
+    /// Pixels of quiet zone on each side
+    pub quiet_zone_px: u32,
+    /// Printing resolution in dots per inch
+    pub dpi: u32,
+    /// Requested symbol width in millimetres
+    pub requested_mm: f64,
+}
+
+impl PrintLayout {
+    /// Width of the symbol in pixels, without the quiet zone.
+    pub fn symbol_px(&self) -> u64 {
+        u64::from(self.modules) * u64::from(self.module_px)
+    }
+
+    /// Width of the image in pixels, including the quiet zone on both sides.
+    pub fn image_px(&self) -> u64 {
+        self.symbol_px() + 2 * u64::from(self.quiet_zone_px)
+    }
+
+    /// Width of the rendered symbol in millimetres.
+    pub fn symbol_width_mm(&self) -> f64 {
+        self.px_to_mm(self.symbol_px())
+    }
+
+    /// Width of the rendered image in millimetres, including the quiet zone.
+    pub fn image_width_mm(&self) -> f64 {
+        self.px_to_mm(self.image_px())
+    }
+
+    /// Rendered minus requested symbol width in millimetres: positive when the symbol is printed
+    /// larger than requested.
+    pub fn symbol_error_mm(&self) -> f64 {
+        self.symbol_width_mm() - self.requested_mm
+    }
+
+    // Converts a pixel count at the layout resolution to millimetres.
+    fn px_to_mm(&self, px: u64) -> f64 {
+        px as f64 * MM_PER_INCH / f64::from(self.dpi)
+    }
+}
+
+/// An image rendered by [PrintRenderer] together with its layout.
+#[derive(Debug, Clone)]
+pub struct PrintImage {
+    /// The rendered image
+    pub image: RgbaImage,
+    /// Module size, quiet zone and resolution the image was rendered with
+    pub layout: PrintLayout,
+}
+
+impl PrintImage {
+    /// Writes the image as a PNG whose `pHYs` chunk records [PrintLayout::dpi].
+    pub fn write_png<W: Write>(&self, writer: W) -> Result<(), HelperError> {
+        write_png_with_dpi(&self.image, self.layout.dpi, writer)
+    }
+}
+
+/// Renders a code at a physical size.
+///
+/// The module size and quiet zone of the style are replaced by those of the [PrintLayout] computed
+/// for the code; colors, logo and frame still apply. A caption adds its banner outside the
+/// computed size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrintRenderer {
+    /// The requested physical size
+    pub size: PrintSize,
+}
+
+impl PrintRenderer {
+    pub fn new(size: PrintSize) -> Self {
+        Self { size }
+    }
+}
+
+impl Renderer for PrintRenderer {
+    type Output = PrintImage;
+
+    fn render(&self, qr: &QrCode, style: &QrStyle) -> Result<PrintImage, HelperError> {
+        style.validate()?;
+        let layout = self.size.layout(qr.size() as u32)?;
+        let style = style.clone().with_module_size(layout.module_px)?;
+        let image = render_raster(qr, &style, layout.quiet_zone_px)?;
+        Ok(PrintImage { image, layout })
+    }
+}
+
+/// Writes `img` as a PNG to `writer` with a `pHYs` chunk recording `dpi`.
+///
+/// PNG stores the resolution in pixels per metre, so it is rounded to the nearest whole number;
+/// common resolutions read back within a hundredth of a dot per inch.
+pub fn write_png_with_dpi<W: Write>(
+    img: &RgbaImage,
+    dpi: u32,
+    mut writer: W,
+) -> Result<(), HelperError> {
+    if !(1..=MAX_PRINT_DPI).contains(&dpi) {
+        return Err(HelperError::InvalidInput(format!(
+            "dpi must be between 1 and {}",
+            MAX_PRINT_DPI
+        )));
+    }
+    let mut png = Vec::new();
+    img.write_with_encoder(PngEncoder::new(&mut png))?;
+
+    let pixels_per_metre = (f64::from(dpi) * 1000.0 / MM_PER_INCH).round() as u32;
+    let mut chunk = Vec::with_capacity(4 + 4 + 9 + 4);
+    chunk.extend_from_slice(&9u32.to_be_bytes());
+    chunk.extend_from_slice(b"pHYs");
+    chunk.extend_from_slice(&pixels_per_metre.to_be_bytes());
+    chunk.extend_from_slice(&pixels_per_metre.to_be_bytes());
+    chunk.push(1); // unit: metre
+    let crc = crc32(&chunk[4..]);
+    chunk.extend_from_slice(&crc.to_be_bytes());
+
+    // pHYs must precede the image data, so it goes right after the header
+    writer.write_all(&png[..PNG_HEADER_LEN])?;
+    writer.write_all(&chunk)?;
+    writer.write_all(&png[PNG_HEADER_LEN..])?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qrcode::{EncodeTextOptions, QrCodeEcc, Version};
+
+    #[test]
+    fn test_layout_rounds_module_size() {
+        // 25 mm at 300 dpi is 295.3 px, or 11.81 px for each of 25 modules
+        let size = PrintSize::new(Length::Mm(25.0), 300)
+            .unwrap()
+            .with_quiet_zone(Length::Mm(2.5))
+            .unwrap();
+        let layout = size.layout(25).unwrap();
+        assert_eq!(layout.module_px, 12);
+        assert_eq!(layout.quiet_zone_px, 30);
+        assert_eq!(layout.image_px(), 360);
+        assert!((layout.symbol_error_mm() - 0.4).abs() < 1e-9);
+        assert!((layout.image_width_mm() - 30.48).abs() < 1e-9);
+
+        assert!(size
+            .with_max_error(Length::Mm(0.1))
+            .unwrap()
+            .layout(25)
+            .is_err());
+        assert_eq!(
+            PrintSize::new(Length::Inch(1.0), 600)
+                .unwrap()
+                .layout(25)
+                .unwrap()
+                .module_px,
+            24
+        );
+        assert!(PrintSize::new(Length::Mm(5.0), 72)
+            .unwrap()
+            .layout(177)
+            .is_err());
+        // 50 mm at 1200 dpi is 2362.2 px, or 112 px for each of 21 modules
+        let err = PrintSize::new(Length::Mm(50.0), 1200)
+            .unwrap()
+            .layout(21)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("50.000 mm at 1200 dpi needs 112 pixels"));
+        assert_eq!(
+            PrintSize::new(Length::Mm(50.0), 600)
+                .unwrap()
+                .layout(21)
+                .unwrap()
+                .module_px,
+            56
+        );
+        assert!(PrintSize::new(Length::Mm(0.0), 300).is_err());
+        assert!(PrintSize::new(Length::Mm(25.0), 0).is_err());
+        assert!(size.with_quiet_zone(Length::Mm(f64::NAN)).is_err());
+    }
+
+    #[test]
+    fn test_print_png_records_dpi() {
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let qr = QrCode::encode_text(
+            "Hello, World!",
+            &mut tempbuffer,
+            &mut outbuffer,
+            EncodeTextOptions {
+                ecl: QrCodeEcc::Low,
+                minversion: Version::MIN,
+                maxversion: Version::MAX,
+                mask: None,
+                boostecl: false,
+            },
+        )
+        .unwrap();
+        let size = PrintSize::new(Length::Mm(25.0), 600)
+            .unwrap()
+            .with_quiet_zone(Length::Mm(2.5))
+            .unwrap();
+        let print = PrintRenderer::new(size)
+            .render(&qr, &QrStyle::new())
+            .unwrap();
+        assert_eq!(print.layout.modules, 21);
+        assert_eq!(u64::from(print.image.width()), print.layout.image_px());
+
+        let mut png = Vec::new();
+        print.write_png(&mut png).unwrap();
+        assert_eq!(&png[PNG_HEADER_LEN + 4..PNG_HEADER_LEN + 8], b"pHYs");
+        // 600 dpi is 23622 pixels per metre
+        assert_eq!(
+            &png[PNG_HEADER_LEN + 8..PNG_HEADER_LEN + 17],
+            &[0, 0, 0x5c, 0x46, 0, 0, 0x5c, 0x46, 1]
+        );
+        // The decoder checks the chunk CRC and reads the pixels unchanged
+        let decoded = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(decoded, print.image);
+    }
+}
@@ -54,6 +54,7 @@ pub struct QrCode<'a> {
     modules: &'a mut [u8],
 }
 
+#[derive(Debug, Clone, Copy)]
 pub struct EncodeTextOptions {
     pub ecl: QrCodeEcc,
     pub minversion: Version,
@@ -354,6 +355,22 @@ impl<'a> QrCode<'a> {
         result
     }
 
+    // Returns a view of a QR Code previously encoded into the front of `buffer`, which must be
+    // exactly the version's buffer length.
+    pub(crate) fn from_buffer(buffer: &'a mut [u8]) -> Self {
+        let (size, modules) = buffer.split_first_mut().unwrap();
+        debug_assert_eq!(
+            modules.len() + 1,
+            Version::new((*size - 17) / 4).buffer_len()
+        );
+        Self { size, modules }
+    }
+
+    // Returns the modules packed bitwise, row by row, as stored after the size in the buffer.
+    pub(crate) fn packed_modules(&self) -> &[u8] {
+        self.modules
+    }
+
     /*---- Public methods ----*/
 
     /// Returns this QR Code's version, in the range [1, 40].
@@ -371,15 +388,18 @@ impl<'a> QrCode<'a> {
         let index = (usize::from(self.get_module_bounded(0, 8)) << 1)
             | usize::from(self.get_module_bounded(1, 8));
         use QrCodeEcc::*;
-        [Medium, Low, High, Quartile][index]
+        // The format bits are stored XORed with 0x5412, which flips the first bit of the level
+        [High, Quartile, Medium, Low][index]
     }
 
     /// Returns this QR Code's mask, in the range [0, 7].
     pub fn mask(&self) -> Mask {
+        // Undo the 0x5412 format mask, which covers these three bits with 0b101
         Mask::new(
-            (u8::from(self.get_module_bounded(2, 8)) << 2)
+            ((u8::from(self.get_module_bounded(2, 8)) << 2)
                 | (u8::from(self.get_module_bounded(3, 8)) << 1)
-                | u8::from(self.get_module_bounded(4, 8)),
+                | u8::from(self.get_module_bounded(4, 8)))
+                ^ 0b101,
         )
     }
 
@@ -843,7 +863,7 @@ impl<'a> QrCode<'a> {
     // Returns the number of 8-bit data (i.e. not error correction) codewords contained in any
     // QR Code of the given version number and error correction level, with remainder bits discarded.
     // This stateless pure function could be implemented as a (40*4)-cell lookup table.
-    fn get_num_data_codewords(ver: Version, ecl: QrCodeEcc) -> usize {
+    pub(crate) fn get_num_data_codewords(ver: Version, ecl: QrCodeEcc) -> usize {
         QrCode::get_num_raw_data_modules(ver) / 8
             - QrCode::table_get(&ECC_CODEWORDS_PER_BLOCK, ver, ecl)
                 * QrCode::table_get(&NUM_ERROR_CORRECTION_BLOCKS, ver, ecl)
@@ -1037,6 +1057,11 @@ static NUM_ERROR_CORRECTION_BLOCKS: [[i8; 41]; 4] = [
 
 /// Error correction level for a QR code.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum QrCodeEcc {
     /// Tolerates ~7% erroneous codewords.
     Low,
@@ -1049,6 +1074,14 @@ pub enum QrCodeEcc {
 }
 
 impl QrCodeEcc {
+    /// All levels, from the lowest to the highest error tolerance.
+    pub const ALL: [QrCodeEcc; 4] = [
+        QrCodeEcc::Low,
+        QrCodeEcc::Medium,
+        QrCodeEcc::Quartile,
+        QrCodeEcc::High,
+    ];
+
     // Returns an unsigned 2-bit integer (in the range 0 to 3).
     fn ordinal(self) -> usize {
         use QrCodeEcc::*;
@@ -1272,7 +1305,7 @@ impl<'a> QrSegment<'a> {
     // Calculates and returns the number of bits needed to encode the given
     // segments at the given version. The result is None if a segment has too many
     // characters to fit its length field, or the total bits exceeds usize::MAX.
-    fn get_total_bits(segs: &[Self], version: Version) -> Option<usize> {
+    pub(crate) fn get_total_bits(segs: &[Self], version: Version) -> Option<usize> {
         let mut result: usize = 0;
         for seg in segs {
             let ccbits: u8 = seg.mode.num_char_count_bits(version);
@@ -1334,7 +1367,7 @@ impl QrSegmentMode {
 
     // Returns the bit width of the character count field for a segment in this mode
     // in a QR Code at the given version number. The result is in the range [0, 16].
-    fn num_char_count_bits(self, ver: Version) -> u8 {
+    pub(crate) fn num_char_count_bits(self, ver: Version) -> u8 {
         use QrSegmentMode::*;
         (match self {
             Numeric => [10, 12, 14],
@@ -1501,4 +1534,34 @@ mod tests {
         assert!(QrSegment::is_alphanumeric("HELLO WORLD"));
         assert!(!QrSegment::is_alphanumeric("Hello World"));
     }
+
+    #[test]
+    fn test_format_information_round_trips() {
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        for ecl in [
+            QrCodeEcc::Low,
+            QrCodeEcc::Medium,
+            QrCodeEcc::Quartile,
+            QrCodeEcc::High,
+        ] {
+            for mask in 0..8 {
+                let qr = QrCode::encode_text(
+                    "Hello",
+                    &mut tempbuffer,
+                    &mut outbuffer,
+                    EncodeTextOptions {
+                        ecl,
+                        minversion: Version::MIN,
+                        maxversion: Version::MAX,
+                        mask: Some(Mask::new(mask)),
+                        boostecl: false,
+                    },
+                )
+                .unwrap();
+                assert_eq!(qr.error_correction_level(), ecl);
+                assert_eq!(qr.mask(), Mask::new(mask));
+            }
+        }
+    }
 }
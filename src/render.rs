@@ -0,0 +1,1099 @@
+//! Unified rendering of QR codes across output formats.
+//!
+//! Every output format implements the [Renderer] trait and is driven by the same [QrStyle], so
+//! switching from an in-memory image to a PNG file, an SVG document or terminal text only changes
+//! the renderer value. Use [RenderFormat] when the format is chosen at runtime.
+//!
+//! The trait, [QrStyle], [SvgRenderer] and [TerminalRenderer] only need the standard library. The
+//! raster renderers, the PNG variant of [RenderFormat] and the logo and caption options of
+//! [QrStyle] need the default `image` feature.
+//!
+//! # Example
+//!
+//! ```rust
+//! use qirust::qrcode::{QrCode, QrCodeEcc, Version, EncodeTextOptions};
+//! use qirust::render::{QrStyle, RenderFormat, Renderer, SvgRenderer};
+//!
+//! let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+//! let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+//! let qr = QrCode::encode_text(
+//!     "https://example.com",
+//!     &mut tempbuffer,
+//!     &mut outbuffer,
+//!     EncodeTextOptions {
+//!         ecl: QrCodeEcc::Medium,
+//!         minversion: Version::MIN,
+//!         maxversion: Version::MAX,
+//!         mask: None,
+//!         boostecl: true,
+//!     },
+//! ).unwrap();
+//!
+//! let style = QrStyle::new()
+//!     .with_fg_color([0, 0, 128])
+//!     .with_quiet_zone(2).unwrap()
+//!     .with_module_size(6).unwrap();
+//!
+//! let svg: String = SvgRenderer.render(&qr, &style).unwrap();
+//! let bytes: Vec<u8> = RenderFormat::Terminal.render(&qr, &style).unwrap();
+//! # assert!(svg.contains("#000080"));
+//! # assert!(!bytes.is_empty());
+//! # #[cfg(feature = "image")] {
+//! let png: Vec<u8> = qirust::render::PngRenderer.render(&qr, &style).unwrap();
+//! # assert!(png.starts_with(b"\x89PNG"));
+//! # }
+//! ```
+#[cfg(feature = "image")]
+use crate::helper::{
+    draw_caption, draw_logo, layout_caption, load_fitted_logo, styled_to_writer, write_caption_svg,
+    write_logo_svg, Caption, FrameStyle, ImageOutputFormat, DEFAULT_INNER_FRAME, LOGO_SIZE_DIVISOR,
+};
+use crate::helper::{
+    layout_canvas, to_terminal_string, HelperError, DEFAULT_BORDER_SIZE, DEFAULT_SCALE,
+    MAX_BORDER_SIZE, MAX_UPSCALE_FACTOR,
+};
+#[cfg(feature = "image")]
+use crate::logo::{LogoCache, LogoFilter, LogoFit, LogoFitting};
+use crate::qrcode::{QrCode, QrCodeEcc};
+#[cfg(feature = "image")]
+use image::{DynamicImage, ImageFormat, Rgba, RgbaImage};
+use std::fmt::Write;
+#[cfg(not(feature = "image"))]
+use std::marker::PhantomData;
+#[cfg(feature = "image")]
+use std::{borrow::Cow, ops::Range};
+
+// Physical dimensions prescribed by the Swiss QR-bill style guide.
+#[cfg(feature = "image")]
+const SWISS_SYMBOL_MM: f64 = 46.0;
+#[cfg(feature = "image")]
+const SWISS_CROSS_MM: f64 = 7.0;
+#[cfg(feature = "image")]
+const SWISS_CROSS_BORDER_MM: f64 = 0.5;
+#[cfg(feature = "image")]
+const DEFAULT_SWISS_DPI: u32 = 300;
+#[cfg(feature = "image")]
+const MAX_SWISS_DPI: u32 = 2400;
+// SVG user units are CSS pixels, at 96 per inch.
+#[cfg(feature = "image")]
+const DEFAULT_LOGO_DPI: u32 = 96;
+#[cfg(feature = "image")]
+const MAX_LOGO_DPI: u32 = 2400;
+
+/// Style options shared by every [Renderer] and by the `generate_styled*` helpers.
+///
+/// This is the single style model of the crate: sizes of the symbol itself are expressed in modules
+/// ([QrStyle::quiet_zone]) and converted to pixels through [QrStyle::module_size], while decorations
+/// that only exist in pixel space (logo frame padding, caption) are expressed in pixels. The optional
+/// logo is overlaid at the center of the code by the raster, PNG and SVG renderers, with a frame in
+/// the background color drawn behind it according to [FrameStyle]. Where the output goes is
+/// configured separately with [crate::helper::OutputConfig].
+///
+/// The deprecated [crate::helper::QrConfig], [crate::helper::FrameQrConfig],
+/// [crate::helper::FrameQrSvgConfig] and [crate::helper::FrameQrSaveConfig] convert into a
+/// `QrStyle` with [From].
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(remote = "Self", default)
+)]
+pub struct QrStyle<'a> {
+    /// Foreground color of dark modules as RGB (defaults to black [0, 0, 0])
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::color"))]
+    pub fg_color: [u8; 3],
+    /// Background color as RGB (defaults to white [255, 255, 255])
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::color"))]
+    pub bg_color: [u8; 3],
+    /// Quiet zone around the code in modules (defaults to 4)
+    pub quiet_zone: u32,
+    /// Size of one module in pixels (defaults to 4)
+    pub module_size: u32,
+    /// Error correction level used when a helper encodes the content (defaults to
+    /// [QrCodeEcc::High]); renderers receive an already encoded [QrCode] and ignore it
+    pub ecc: QrCodeEcc,
+    /// Path to a logo image overlaid at the center (defaults to no logo). SVG logos are inlined as
+    /// vector markup by the SVG renderer; the raster renderers only accept raster formats
+    #[cfg(feature = "image")]
+    pub logo_path: Option<Cow<'a, str>>,
+    /// Frame drawn behind the logo (defaults to [FrameStyle::None])
+    #[cfg(feature = "image")]
+    pub frame_style: FrameStyle,
+    /// Padding in pixels between the logo and its frame (defaults to 3)
+    #[cfg(feature = "image")]
+    pub inner_frame_px: u32,
+    /// How the logo is scaled into the logo area (defaults to [LogoFit::Contain])
+    #[cfg(feature = "image")]
+    pub logo_fit: LogoFit,
+    /// Resampling filter used to scale the logo (defaults to [LogoFilter::Lanczos3])
+    #[cfg(feature = "image")]
+    pub logo_filter: LogoFilter,
+    /// Whether raster logos smaller than the logo area are enlarged to fill it (defaults to true).
+    /// The deprecated configurations convert with `false`, as their functions only shrank logos
+    #[cfg(feature = "image")]
+    pub logo_upscale: bool,
+    /// Space in pixels kept free on each side of the logo area, which is one third of the symbol
+    /// wide (defaults to 0)
+    #[cfg(feature = "image")]
+    pub logo_padding_px: u32,
+    /// Resolution of raster logos embedded in SVG output, in pixels per inch with SVG user units at
+    /// 96 per inch (defaults to 96, one pixel per unit)
+    #[cfg(feature = "image")]
+    pub logo_dpi: u32,
+    /// Cache for the prepared logo (defaults to none, which loads the logo on every render)
+    #[cfg(feature = "image")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub logo_cache: Option<&'a LogoCache>,
+    /// Caption banner above or below the code (defaults to none)
+    #[cfg(feature = "image")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub caption: Option<Caption<'a>>,
+    // Borrows nothing without logos and captions, but keeps the lifetime of the style
+    #[cfg(not(feature = "image"))]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) marker: PhantomData<&'a ()>,
+}
+
+impl Default for QrStyle<'_> {
+    fn default() -> Self {
+        Self {
+            fg_color: [0, 0, 0],
+            bg_color: [255, 255, 255],
+            quiet_zone: DEFAULT_BORDER_SIZE,
+            module_size: DEFAULT_SCALE,
+            ecc: QrCodeEcc::High,
+            #[cfg(feature = "image")]
+            logo_path: None,
+            #[cfg(feature = "image")]
+            frame_style: FrameStyle::None,
+            #[cfg(feature = "image")]
+            inner_frame_px: DEFAULT_INNER_FRAME,
+            #[cfg(feature = "image")]
+            logo_fit: LogoFit::default(),
+            #[cfg(feature = "image")]
+            logo_filter: LogoFilter::default(),
+            #[cfg(feature = "image")]
+            logo_upscale: true,
+            #[cfg(feature = "image")]
+            logo_padding_px: 0,
+            #[cfg(feature = "image")]
+            logo_dpi: DEFAULT_LOGO_DPI,
+            #[cfg(feature = "image")]
+            logo_cache: None,
+            #[cfg(feature = "image")]
+            caption: None,
+            #[cfg(not(feature = "image"))]
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> QrStyle<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_fg_color(mut self, color: [u8; 3]) -> Self {
+        self.fg_color = color;
+        self
+    }
+
+    pub fn with_bg_color(mut self, color: [u8; 3]) -> Self {
+        self.bg_color = color;
+        self
+    }
+
+    pub fn with_quiet_zone(mut self, modules: u32) -> Result<Self, HelperError> {
+        if modules > MAX_BORDER_SIZE {
+            return Err(HelperError::InvalidInput(format!(
+                "Quiet zone cannot exceed {} modules",
+                MAX_BORDER_SIZE
+            )));
+        }
+        self.quiet_zone = modules;
+        Ok(self)
+    }
+
+    pub fn with_module_size(mut self, pixels: u32) -> Result<Self, HelperError> {
+        if pixels == 0 || pixels > MAX_UPSCALE_FACTOR {
+            return Err(HelperError::InvalidInput(format!(
+                "Module size must be between 1 and {}",
+                MAX_UPSCALE_FACTOR
+            )));
+        }
+        self.module_size = pixels;
+        Ok(self)
+    }
+
+    pub fn with_ecc(mut self, ecc: QrCodeEcc) -> Self {
+        self.ecc = ecc;
+        self
+    }
+
+    #[cfg(feature = "image")]
+    pub fn with_logo(mut self, logo_path: impl Into<Cow<'a, str>>) -> Result<Self, HelperError> {
+        let logo_path = logo_path.into();
+        if logo_path.is_empty() {
+            return Err(HelperError::InvalidInput(
+                "Logo path cannot be empty".to_string(),
+            ));
+        }
+        self.logo_path = Some(logo_path);
+        Ok(self)
+    }
+
+    #[cfg(feature = "image")]
+    pub fn with_frame_style(mut self, style: FrameStyle) -> Self {
+        self.frame_style = style;
+        self
+    }
+
+    #[cfg(feature = "image")]
+    pub fn with_inner_frame(mut self, size: u32) -> Self {
+        self.inner_frame_px = size;
+        self
+    }
+
+    #[cfg(feature = "image")]
+    pub fn with_logo_fit(mut self, fit: LogoFit) -> Self {
+        self.logo_fit = fit;
+        self
+    }
+
+    #[cfg(feature = "image")]
+    pub fn with_logo_filter(mut self, filter: LogoFilter) -> Self {
+        self.logo_filter = filter;
+        self
+    }
+
+    #[cfg(feature = "image")]
+    pub fn with_logo_upscale(mut self, upscale: bool) -> Self {
+        self.logo_upscale = upscale;
+        self
+    }
+
+    #[cfg(feature = "image")]
+    pub fn with_logo_padding(mut self, pixels: u32) -> Self {
+        self.logo_padding_px = pixels;
+        self
+    }
+
+    #[cfg(feature = "image")]
+    pub fn with_logo_dpi(mut self, dpi: u32) -> Result<Self, HelperError> {
+        if dpi == 0 || dpi > MAX_LOGO_DPI {
+            return Err(HelperError::InvalidInput(format!(
+                "Logo DPI must be between 1 and {}",
+                MAX_LOGO_DPI
+            )));
+        }
+        self.logo_dpi = dpi;
+        Ok(self)
+    }
+
+    /// Reuses logos prepared for earlier renders from `cache`, see [LogoCache].
+    #[cfg(feature = "image")]
+    pub fn with_logo_cache(mut self, cache: &'a LogoCache) -> Self {
+        self.logo_cache = Some(cache);
+        self
+    }
+
+    #[cfg(feature = "image")]
+    pub fn with_caption(mut self, caption: Caption<'a>) -> Self {
+        self.caption = Some(caption);
+        self
+    }
+
+    /// Validates the style before use
+    pub fn validate(&self) -> Result<(), HelperError> {
+        if self.module_size == 0 || self.module_size > MAX_UPSCALE_FACTOR {
+            return Err(HelperError::InvalidInput(format!(
+                "Module size must be between 1 and {}",
+                MAX_UPSCALE_FACTOR
+            )));
+        }
+        if self.quiet_zone > MAX_BORDER_SIZE {
+            return Err(HelperError::InvalidInput(format!(
+                "Quiet zone cannot exceed {} modules",
+                MAX_BORDER_SIZE
+            )));
+        }
+        #[cfg(feature = "image")]
+        self.validate_decorations()?;
+        Ok(())
+    }
+
+    // Validates the logo and caption options.
+    #[cfg(feature = "image")]
+    fn validate_decorations(&self) -> Result<(), HelperError> {
+        if self.logo_path.as_deref().is_some_and(str::is_empty) {
+            return Err(HelperError::InvalidInput(
+                "Logo path cannot be empty".to_string(),
+            ));
+        }
+        if self.logo_dpi == 0 || self.logo_dpi > MAX_LOGO_DPI {
+            return Err(HelperError::InvalidInput(format!(
+                "Logo DPI must be between 1 and {}",
+                MAX_LOGO_DPI
+            )));
+        }
+        if let Some(caption) = &self.caption {
+            caption.validate()?;
+        }
+        Ok(())
+    }
+
+    // Returns the quiet zone in pixels.
+    fn quiet_zone_px(&self) -> u32 {
+        self.quiet_zone * self.module_size
+    }
+
+    #[cfg(feature = "image")]
+    // Returns how the logo is fitted into a symbol of `qr_px` pixels.
+    fn logo_fitting(&self, qr_px: u32) -> Result<LogoFitting, HelperError> {
+        let side = (qr_px / LOGO_SIZE_DIVISOR)
+            .checked_sub(self.logo_padding_px.saturating_mul(2))
+            .filter(|&side| side > 0)
+            .ok_or_else(|| {
+                HelperError::InvalidInput(format!(
+                    "Logo padding of {} pixels leaves no room for the logo",
+                    self.logo_padding_px
+                ))
+            })?;
+        Ok(LogoFitting {
+            side,
+            fit: self.logo_fit,
+            filter: self.logo_filter,
+            upscale: self.logo_upscale,
+        })
+    }
+}
+
+/// A backend that turns a [QrCode] into a specific output format.
+///
+/// Implementations exist for in-memory images ([RasterRenderer]), PNG bytes ([PngRenderer]), bytes
+/// in other image formats ([ImageRenderer]), SVG documents ([SvgRenderer]) and terminal text
+/// ([TerminalRenderer]). New backends only need to implement [Renderer::render] to work with every
+/// caller written against this trait.
+pub trait Renderer {
+    /// The rendered result, such as an image buffer or a string.
+    type Output;
+
+    /// Renders `qr` using the shared `style` options.
+    fn render(&self, qr: &QrCode, style: &QrStyle) -> Result<Self::Output, HelperError>;
+}
+
+/// Renders an in-memory RGBA image buffer.
+#[cfg(feature = "image")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RasterRenderer;
+
+#[cfg(feature = "image")]
+impl Renderer for RasterRenderer {
+    type Output = RgbaImage;
+
+    fn render(&self, qr: &QrCode, style: &QrStyle) -> Result<RgbaImage, HelperError> {
+        style.validate()?;
+        render_raster(qr, style, style.quiet_zone_px())
+    }
+}
+
+/// Renders PNG-encoded bytes.
+#[cfg(feature = "image")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PngRenderer;
+
+#[cfg(feature = "image")]
+impl Renderer for PngRenderer {
+    type Output = Vec<u8>;
+
+    fn render(&self, qr: &QrCode, style: &QrStyle) -> Result<Vec<u8>, HelperError> {
+        let img = RasterRenderer.render(qr, style)?;
+        let mut buffer = Vec::new();
+        DynamicImage::ImageRgba8(img)
+            .write_to(&mut std::io::Cursor::new(&mut buffer), ImageFormat::Png)?;
+        Ok(buffer)
+    }
+}
+
+/// Renders bytes encoded in a selectable [ImageOutputFormat].
+///
+/// Produces the same bytes as [PngRenderer] for [ImageOutputFormat::Png]. Use
+/// [crate::helper::styled_to_writer] to stream the image into a writer instead.
+#[cfg(feature = "image")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ImageRenderer {
+    /// Format the image is encoded in
+    pub format: ImageOutputFormat,
+}
+
+#[cfg(feature = "image")]
+impl ImageRenderer {
+    pub fn new(format: ImageOutputFormat) -> Self {
+        Self { format }
+    }
+}
+
+#[cfg(feature = "image")]
+impl Renderer for ImageRenderer {
+    type Output = Vec<u8>;
+
+    fn render(&self, qr: &QrCode, style: &QrStyle) -> Result<Vec<u8>, HelperError> {
+        let mut buffer = Vec::new();
+        styled_to_writer(qr, style, self.format, &mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+/// Renders an SVG document whose user units match the pixel sizes of [RasterRenderer].
+///
+/// Dark modules are grouped horizontally into a single path. SVG logos are inlined as nested
+/// `<svg>` elements with prefixed ids, and other logos are embedded as base64-encoded PNGs at
+/// [QrStyle::logo_dpi].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SvgRenderer;
+
+impl Renderer for SvgRenderer {
+    type Output = String;
+
+    fn render(&self, qr: &QrCode, style: &QrStyle) -> Result<String, HelperError> {
+        style.validate()?;
+        render_svg(qr, style, style.quiet_zone_px())
+    }
+}
+
+/// Renders text for terminal output using `█` for dark modules.
+///
+/// Only [QrStyle::quiet_zone] applies; colors, module size and logos cannot be expressed in plain
+/// text and are ignored.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TerminalRenderer;
+
+impl Renderer for TerminalRenderer {
+    type Output = String;
+
+    fn render(&self, qr: &QrCode, style: &QrStyle) -> Result<String, HelperError> {
+        style.validate()?;
+        Ok(to_terminal_string(qr, style.quiet_zone as i32))
+    }
+}
+
+/// An output format selected at runtime, rendering to encoded bytes.
+///
+/// Useful when the format comes from configuration or a request parameter: every variant produces
+/// the bytes of a complete document (PNG file contents, UTF-8 SVG or UTF-8 terminal text).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderFormat {
+    /// PNG image bytes, see [PngRenderer].
+    #[cfg(feature = "image")]
+    Png,
+    /// UTF-8 SVG document, see [SvgRenderer].
+    Svg,
+    /// UTF-8 terminal text, see [TerminalRenderer].
+    Terminal,
+}
+
+impl Renderer for RenderFormat {
+    type Output = Vec<u8>;
+
+    fn render(&self, qr: &QrCode, style: &QrStyle) -> Result<Vec<u8>, HelperError> {
+        match self {
+            #[cfg(feature = "image")]
+            RenderFormat::Png => PngRenderer.render(qr, style),
+            RenderFormat::Svg => SvgRenderer.render(qr, style).map(String::into_bytes),
+            RenderFormat::Terminal => TerminalRenderer.render(qr, style).map(String::into_bytes),
+        }
+    }
+}
+
+/// Renders the QR code of a Swiss QR-bill at its prescribed physical size.
+///
+/// The symbol measures exactly 46 × 46 mm at [SwissQrRenderer::dpi] and carries the 7 × 7 mm
+/// Swiss cross in its center, overlaid with the same code that places logos. Module edges are
+/// distributed across the pixel grid so the symbol size stays exact at any resolution. The
+/// QR-bill prescribes a black symbol on white without a quiet zone of its own (the payment part
+/// keeps a 5 mm margin around it), so the colors, quiet zone, logo and caption of the style are
+/// ignored.
+///
+/// # Example
+///
+/// ```rust
+/// use qirust::payload::{QrPayload, SwissAddress, SwissCurrency, SwissQrBill};
+/// use qirust::qrcode::Version;
+/// use qirust::render::{QrStyle, Renderer, SwissQrRenderer};
+///
+/// let creditor = SwissAddress {
+///     name: "Robert Schneider AG",
+///     postal_code: "2501",
+///     town: "Biel",
+///     country: "CH",
+///     ..Default::default()
+/// };
+/// let bill = SwissQrBill::new("CH5800791123000889012", creditor, SwissCurrency::Chf).unwrap();
+///
+/// let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+/// let qr = bill.encode(&mut tempbuffer, &mut outbuffer).unwrap();
+/// let img = SwissQrRenderer::new(300).render(&qr, &QrStyle::default()).unwrap();
+/// assert_eq!(img.dimensions(), (543, 543)); // 46 mm at 300 dpi
+/// ```
+#[cfg(feature = "image")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwissQrRenderer {
+    /// Output resolution in dots per inch
+    pub dpi: u32,
+}
+
+#[cfg(feature = "image")]
+impl Default for SwissQrRenderer {
+    fn default() -> Self {
+        Self::new(DEFAULT_SWISS_DPI)
+    }
+}
+
+#[cfg(feature = "image")]
+impl SwissQrRenderer {
+    pub fn new(dpi: u32) -> Self {
+        Self { dpi }
+    }
+}
+
+#[cfg(feature = "image")]
+impl Renderer for SwissQrRenderer {
+    type Output = RgbaImage;
+
+    fn render(&self, qr: &QrCode, _style: &QrStyle) -> Result<RgbaImage, HelperError> {
+        if !(1..=MAX_SWISS_DPI).contains(&self.dpi) {
+            return Err(HelperError::InvalidInput(format!(
+                "dpi must be between 1 and {}",
+                MAX_SWISS_DPI
+            )));
+        }
+        let symbol_px = mm_to_px(SWISS_SYMBOL_MM, self.dpi);
+        let modules = qr.size() as u32;
+        if symbol_px < modules {
+            return Err(HelperError::InvalidInput(format!(
+                "{} dpi is too low for a version {} code",
+                self.dpi,
+                qr.version().value()
+            )));
+        }
+
+        // Each pixel takes the color of the module its top-left corner falls into. Pixel rows
+        // within the same module row are identical, so each is filled once and then copied.
+        let mut img = RgbaImage::from_pixel(symbol_px, symbol_px, Rgba([255, 255, 255, 255]));
+        let first_px = |module: i32| {
+            (u64::from(module as u32) * u64::from(symbol_px)).div_ceil(u64::from(modules)) as usize
+        };
+        let row_bytes = symbol_px as usize * 4;
+        let buffer: &mut [u8] = &mut img;
+        for y in 0..qr.size() {
+            let (top, bottom) = (first_px(y), first_px(y + 1));
+            let row = &mut buffer[top * row_bytes..(top + 1) * row_bytes];
+            for (start, len) in dark_runs(qr, y) {
+                fill_pixels(
+                    &mut row[first_px(start) * 4..first_px(start + len) * 4],
+                    [0, 0, 0, 255],
+                );
+            }
+            copy_row(buffer, row_bytes, top, 0..row_bytes, bottom);
+        }
+
+        let cross = swiss_cross(mm_to_px(SWISS_CROSS_MM, self.dpi));
+        draw_logo(
+            &mut img,
+            &cross,
+            0,
+            0,
+            symbol_px,
+            FrameStyle::None,
+            0,
+            Rgba([255, 255, 255, 255]),
+        );
+        Ok(img)
+    }
+}
+
+#[cfg(feature = "image")]
+fn mm_to_px(mm: f64, dpi: u32) -> u32 {
+    (mm * f64::from(dpi) / 25.4).round() as u32
+}
+
+// Draws the Swiss cross: a white cross on a black square, surrounded by a white border. The cross
+// follows the proportions of the Swiss flag, with arms 6/32 wide and 20/32 long.
+#[cfg(feature = "image")]
+fn swiss_cross(size: u32) -> RgbaImage {
+    let side = f64::from(size);
+    let border = side * SWISS_CROSS_BORDER_MM / SWISS_CROSS_MM;
+    let square = side - 2.0 * border;
+    let half_width = square * 3.0 / 32.0;
+    let half_length = square * 10.0 / 32.0;
+    let center = side / 2.0;
+
+    RgbaImage::from_fn(size, size, |x, y| {
+        let (px, py) = (f64::from(x) + 0.5, f64::from(y) + 0.5);
+        let inside_square =
+            (border..side - border).contains(&px) && (border..side - border).contains(&py);
+        let (dx, dy) = ((px - center).abs(), (py - center).abs());
+        let on_cross =
+            (dx <= half_width && dy <= half_length) || (dy <= half_width && dx <= half_length);
+        if inside_square && !on_cross {
+            Rgba([0, 0, 0, 255])
+        } else {
+            Rgba([255, 255, 255, 255])
+        }
+    })
+}
+
+// Renders the styled code onto an RGBA canvas with `margin` pixels of background around the symbol.
+// The caption banner, if any, sits between the symbol and the margin and grows the canvas. The
+// style's quiet zone is ignored so that callers with pixel-based margins can share this path.
+#[cfg(feature = "image")]
+pub(crate) fn render_raster(
+    qr: &QrCode,
+    style: &QrStyle,
+    margin: u32,
+) -> Result<RgbaImage, HelperError> {
+    let qr_px = qr.size() as u32 * style.module_size;
+    let caption_layout = style.caption.as_ref().map(layout_caption).transpose()?;
+    let banner = style
+        .caption
+        .as_ref()
+        .zip(caption_layout.as_ref())
+        .map(|(caption, layout)| layout.banner(caption));
+    let canvas = layout_canvas(qr_px, margin, banner)?;
+
+    let [br, bg, bb] = style.bg_color;
+    let [fr, fg, fb] = style.fg_color;
+
+    // Every pixel row of a module row is identical, so each module row is filled into a template
+    // row once and appended `module_size` times; the canvas is written in a single pass
+    let scale = style.module_size as usize;
+    let background_row = [br, bg, bb, 255].repeat(canvas.width as usize);
+    let symbol = canvas.qr_x as usize * 4..(canvas.qr_x + qr_px) as usize * 4;
+    let mut pixels = Vec::with_capacity(background_row.len() * canvas.height as usize);
+    for _ in 0..canvas.qr_y {
+        pixels.extend_from_slice(&background_row);
+    }
+    let mut row = background_row.clone();
+    for y in 0..qr.size() {
+        row[symbol.clone()].copy_from_slice(&background_row[symbol.clone()]);
+        for (start, len) in dark_runs(qr, y) {
+            let from = symbol.start + start as usize * scale * 4;
+            fill_pixels(
+                &mut row[from..from + len as usize * scale * 4],
+                [fr, fg, fb, 255],
+            );
+        }
+        for _ in 0..scale {
+            pixels.extend_from_slice(&row);
+        }
+    }
+    for _ in canvas.qr_y + qr_px..canvas.height {
+        pixels.extend_from_slice(&background_row);
+    }
+    let mut img = RgbaImage::from_raw(canvas.width, canvas.height, pixels)
+        .expect("canvas rows match its dimensions");
+
+    if let Some(logo_path) = style.logo_path.as_deref() {
+        let logo = load_fitted_logo(logo_path, style.logo_fitting(qr_px)?, style.logo_cache)?;
+        draw_logo(
+            &mut img,
+            &logo,
+            canvas.qr_x,
+            canvas.qr_y,
+            qr_px,
+            style.frame_style,
+            style.inner_frame_px,
+            Rgba([br, bg, bb, 255]),
+        );
+    }
+
+    if let (Some(caption), Some(layout)) = (&style.caption, &caption_layout) {
+        draw_caption(&mut img, caption, layout, canvas.banner_x, canvas.banner_y);
+    }
+    Ok(img)
+}
+
+// Renders the styled code as an SVG document with `margin` user units of background around the
+// symbol, mirroring the layout of `render_raster`.
+pub(crate) fn render_svg(qr: &QrCode, style: &QrStyle, margin: u32) -> Result<String, HelperError> {
+    let qr_size = qr.size();
+    let scale = style.module_size;
+    let qr_px = qr_size as u32 * scale;
+    #[cfg(feature = "image")]
+    let caption_layout = style.caption.as_ref().map(layout_caption).transpose()?;
+    #[cfg(feature = "image")]
+    let banner = style
+        .caption
+        .as_ref()
+        .zip(caption_layout.as_ref())
+        .map(|(caption, layout)| layout.banner(caption));
+    #[cfg(not(feature = "image"))]
+    let banner = None;
+    let canvas = layout_canvas(qr_px, margin, banner)?;
+    let [br, bg, bb] = style.bg_color;
+    let [fr, fg, fb] = style.fg_color;
+
+    let mut result = String::with_capacity(500 + (qr_size * qr_size) as usize * 16);
+    writeln!(
+        result,
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE svg PUBLIC \"-//W3C//DTD SVG 1.1//EN\" \"http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd\">\n<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" viewBox=\"0 0 {} {}\" stroke=\"none\">\n<rect width=\"100%\" height=\"100%\" fill=\"#{:02x}{:02x}{:02x}\"/>",
+        canvas.width, canvas.height, br, bg, bb
+    )?;
+
+    #[cfg(feature = "image")]
+    if let (Some(caption), Some(layout)) = (&style.caption, &caption_layout) {
+        write_caption_svg(
+            &mut result,
+            caption,
+            layout,
+            canvas.banner_x,
+            canvas.banner_y,
+        )?;
+    }
+
+    // Render QR modules with horizontal grouping
+    result.push_str("<path d=\"");
+    for y in 0..qr_size {
+        for (start, len) in dark_runs(qr, y) {
+            let width = len as u32 * scale;
+            write!(
+                result,
+                "M{} {}h{}v{}h-{}z",
+                canvas.qr_x + start as u32 * scale,
+                canvas.qr_y + y as u32 * scale,
+                width,
+                scale,
+                width
+            )?;
+        }
+    }
+    writeln!(result, "\" fill=\"#{:02x}{:02x}{:02x}\"/>", fr, fg, fb)?;
+
+    #[cfg(feature = "image")]
+    if let Some(logo_path) = style.logo_path.as_deref() {
+        write_logo_svg(
+            &mut result,
+            logo_path,
+            canvas.qr_x,
+            canvas.qr_y,
+            qr_px,
+            style.logo_fitting(qr_px)?,
+            style.logo_dpi,
+            style.frame_style,
+            style.inner_frame_px,
+            style.bg_color,
+            style.logo_cache,
+        )?;
+    }
+    result.push_str("</svg>\n");
+    Ok(result)
+}
+
+// Returns the horizontal runs of dark modules in row `y`, as the first column and length of each.
+pub(crate) fn dark_runs<'q>(qr: &'q QrCode, y: i32) -> impl Iterator<Item = (i32, i32)> + 'q {
+    let size = qr.size();
+    let mut x = 0;
+    std::iter::from_fn(move || {
+        while x < size && !qr.get_module(x, y) {
+            x += 1;
+        }
+        let start = x;
+        while x < size && qr.get_module(x, y) {
+            x += 1;
+        }
+        (x > start).then_some((start, x - start))
+    })
+}
+
+// Sets every RGBA pixel of a row slice to `color`.
+#[cfg(feature = "image")]
+fn fill_pixels(row: &mut [u8], color: [u8; 4]) {
+    for pixel in row.chunks_exact_mut(4) {
+        pixel.copy_from_slice(&color);
+    }
+}
+
+// Copies the byte range `span` of pixel row `top` into the same range of every row below it, up to
+// but excluding row `end`.
+#[cfg(feature = "image")]
+fn copy_row(buffer: &mut [u8], row_bytes: usize, top: usize, span: Range<usize>, end: usize) {
+    let source = top * row_bytes + span.start..top * row_bytes + span.end;
+    for row in top + 1..end {
+        buffer.copy_within(source.clone(), row * row_bytes + span.start);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qrcode::{EncodeTextOptions, QrCodeEcc, Version};
+    #[cfg(feature = "image")]
+    use crate::test_support::TempDir;
+
+    fn encode<'a>(text: &str, tempbuffer: &mut [u8], outbuffer: &'a mut [u8]) -> QrCode<'a> {
+        QrCode::encode_text(
+            text,
+            tempbuffer,
+            outbuffer,
+            EncodeTextOptions {
+                ecl: QrCodeEcc::Low,
+                minversion: Version::MIN,
+                maxversion: Version::MAX,
+                mask: None,
+                boostecl: false,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn test_raster_renderer_applies_style() {
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let qr = encode("Hello, World!", &mut tempbuffer, &mut outbuffer);
+        let style = QrStyle::new()
+            .with_fg_color([200, 0, 0])
+            .with_bg_color([0, 0, 200])
+            .with_quiet_zone(2)
+            .unwrap()
+            .with_module_size(3)
+            .unwrap();
+
+        let img = RasterRenderer.render(&qr, &style).unwrap();
+        assert_eq!(img.width(), (qr.size() as u32 + 4) * 3);
+        assert_eq!(img.get_pixel(0, 0), &Rgba([0, 0, 200, 255]));
+        // The top-left finder pattern starts right after the quiet zone
+        assert_eq!(img.get_pixel(6, 6), &Rgba([200, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_renderers_share_style() {
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let qr = encode("Hello, World!", &mut tempbuffer, &mut outbuffer);
+        let style = QrStyle::new().with_quiet_zone(1).unwrap();
+
+        let svg = SvgRenderer.render(&qr, &style).unwrap();
+        let dimension = (qr.size() + 2) * 4;
+        assert!(svg.contains(&format!("viewBox=\"0 0 {0} {0}\"", dimension)));
+        assert!(svg.trim_end().ends_with("</svg>"));
+
+        let text = TerminalRenderer.render(&qr, &style).unwrap();
+        assert_eq!(text.lines().count(), (qr.size() + 2) as usize);
+
+        #[cfg(feature = "image")]
+        assert!(RenderFormat::Png
+            .render(&qr, &style)
+            .unwrap()
+            .starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert_eq!(
+            RenderFormat::Svg.render(&qr, &style).unwrap(),
+            svg.into_bytes()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn test_logo_cache_shared_by_renderers() {
+        let directory = TempDir::new("render-cache");
+        let logo_path = directory.write_logo("logo.png", 200, 200, [0, 128, 0, 255]);
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let qr = encode("Hello, World!", &mut tempbuffer, &mut outbuffer);
+
+        let cache = LogoCache::default();
+        let uncached = QrStyle::new()
+            .with_logo(logo_path.to_str().unwrap())
+            .unwrap();
+        let cached = uncached.clone().with_logo_cache(&cache);
+        for _ in 0..2 {
+            assert_eq!(
+                RasterRenderer.render(&qr, &cached).unwrap(),
+                RasterRenderer.render(&qr, &uncached).unwrap()
+            );
+            assert_eq!(
+                SvgRenderer.render(&qr, &cached).unwrap(),
+                SvgRenderer.render(&qr, &uncached).unwrap()
+            );
+        }
+        // One raster and one SVG entry, reused by the second round
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn test_logo_fit_matches_between_png_and_svg() {
+        let directory = TempDir::new("render-fit");
+        let logo_path = directory.write_logo("wordmark.png", 400, 100, [0, 128, 0, 255]);
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let qr = encode("Hello, World!", &mut tempbuffer, &mut outbuffer);
+        // A 21 module symbol of 84 pixels at (16, 16), with a 28 pixel logo area
+        let style = QrStyle::new()
+            .with_logo(logo_path.to_str().unwrap())
+            .unwrap()
+            .with_frame_style(FrameStyle::Square)
+            .with_inner_frame(1)
+            .with_logo_padding(2);
+
+        // The 4:1 logo is fitted into 24 pixels, so it is 24x6 centered at (46, 55)
+        let img = RasterRenderer.render(&qr, &style).unwrap();
+        let logo_pixels: Vec<_> = img
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| **pixel == Rgba([0, 128, 0, 255]))
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert_eq!(logo_pixels.len(), 24 * 6);
+        assert_eq!(logo_pixels[0], (46, 55));
+        let svg = SvgRenderer.render(&qr, &style).unwrap();
+        assert!(svg.contains("<rect x=\"45\" y=\"54\" width=\"26\" height=\"8\""));
+        assert!(svg.contains("<image x=\"46\" y=\"55\" width=\"24\" height=\"6\""));
+
+        // Cover fills the whole area
+        let style = style.with_logo_fit(LogoFit::Cover);
+        let svg = SvgRenderer.render(&qr, &style).unwrap();
+        assert!(svg.contains("<image x=\"46\" y=\"46\" width=\"24\" height=\"24\""));
+        // Padding that leaves no room for the logo is rejected
+        assert!(RasterRenderer
+            .render(&qr, &style.with_logo_padding(14))
+            .is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn test_svg_logos_are_inlined() {
+        let directory = TempDir::new("render-svg-logo");
+        let svg_path = directory.path().join("vector.svg");
+        std::fs::write(
+            &svg_path,
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 40 10"><rect id="r" width="40" height="10"/><use href="#r"/></svg>"##,
+        )
+        .unwrap();
+        let png_path = directory.write_logo("raster.png", 400, 100, [0, 128, 0, 255]);
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let qr = encode("Hello, World!", &mut tempbuffer, &mut outbuffer);
+
+        // Vector logos are placed where raster logos of the same shape are
+        let style = QrStyle::new()
+            .with_logo(svg_path.to_str().unwrap())
+            .unwrap();
+        let svg = SvgRenderer.render(&qr, &style).unwrap();
+        assert!(
+            svg.contains("<svg x=\"44\" y=\"54\" width=\"28\" height=\"7\" viewBox=\"0 0 40 10\"")
+        );
+        assert!(svg.contains("<rect id=\"qirust-logo-"));
+        assert!(!svg.contains("data:image/png"));
+        assert!(matches!(
+            RasterRenderer.render(&qr, &style),
+            Err(HelperError::LogoDecode { .. })
+        ));
+        let style = style.with_logo(png_path.to_str().unwrap()).unwrap();
+        let svg = SvgRenderer.render(&qr, &style).unwrap();
+        assert!(svg.contains("<image x=\"44\" y=\"54\" width=\"28\" height=\"7\""));
+
+        // A higher DPI embeds more pixels at the same size
+        let cache = LogoCache::default();
+        let embedded_len = |dpi| {
+            let style = style
+                .clone()
+                .with_logo_dpi(dpi)
+                .unwrap()
+                .with_logo_cache(&cache);
+            let svg = SvgRenderer.render(&qr, &style).unwrap();
+            assert!(svg.contains("<image x=\"44\" y=\"54\" width=\"28\" height=\"7\""));
+            svg.len()
+        };
+        assert!(embedded_len(300) > embedded_len(96));
+        assert!(style.with_logo_dpi(0).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn test_swiss_renderer_sizes_symbol_and_cross() {
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let qr = encode("SPC\n0200\n1", &mut tempbuffer, &mut outbuffer);
+        let style = QrStyle::new().with_fg_color([200, 0, 0]);
+
+        let img = SwissQrRenderer::new(600).render(&qr, &style).unwrap();
+        assert_eq!(img.dimensions(), (1087, 1087));
+        // Colors are fixed by the standard; the top-left finder pattern is black
+        assert_eq!(img.get_pixel(0, 0), &Rgba([0, 0, 0, 255]));
+
+        // 7 mm cross (165 px): white border, black square, white cross arms
+        let center = 1087 / 2;
+        let cross = 165;
+        let left = center - cross / 2;
+        assert_eq!(img.get_pixel(left + 2, center), &Rgba([255, 255, 255, 255]));
+        assert_eq!(img.get_pixel(left + 20, left + 20), &Rgba([0, 0, 0, 255]));
+        assert_eq!(img.get_pixel(center, center), &Rgba([255, 255, 255, 255]));
+        assert_eq!(
+            img.get_pixel(center, left + 45),
+            &Rgba([255, 255, 255, 255])
+        );
+
+        assert!(SwissQrRenderer::new(0).render(&qr, &style).is_err());
+        assert!(SwissQrRenderer::new(10).render(&qr, &style).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn test_row_spans_match_per_pixel_rendering() {
+        let mut outbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let mut tempbuffer = vec![0u8; Version::MAX.buffer_len()];
+        let qr = encode(
+            &"https://example.com/".repeat(8),
+            &mut tempbuffer,
+            &mut outbuffer,
+        );
+        let size = qr.size() as u32;
+        for (scale, quiet_zone) in [(1, 0), (3, 2), (7, 4)] {
+            let style = QrStyle::new()
+                .with_fg_color([10, 20, 30])
+                .with_bg_color([240, 230, 220])
+                .with_module_size(scale)
+                .unwrap()
+                .with_quiet_zone(quiet_zone)
+                .unwrap();
+            let img = RasterRenderer.render(&qr, &style).unwrap();
+            let expected = RgbaImage::from_fn(img.width(), img.height(), |x, y| {
+                let module = |px: u32| (px / scale) as i32 - quiet_zone as i32;
+                let inside = x / scale >= quiet_zone && x / scale < quiet_zone + size;
+                if inside && qr.get_module(module(x), module(y)) {
+                    Rgba([10, 20, 30, 255])
+                } else {
+                    Rgba([240, 230, 220, 255])
+                }
+            });
+            assert!(img == expected, "scale {}", scale);
+        }
+
+        // Module edges of the Swiss renderer fall between whole pixels
+        let img = SwissQrRenderer::new(300)
+            .render(&qr, &QrStyle::new())
+            .unwrap();
+        let symbol_px = img.width();
+        let module_at = |px: u32| (u64::from(px) * u64::from(size) / u64::from(symbol_px)) as i32;
+        let cross = mm_to_px(SWISS_CROSS_MM, 300);
+        let cross_area = (symbol_px - cross) / 2..(symbol_px + cross) / 2;
+        for (x, y, pixel) in img.enumerate_pixels() {
+            if cross_area.contains(&x) && cross_area.contains(&y) {
+                continue;
+            }
+            let dark = qr.get_module(module_at(x), module_at(y));
+            assert_eq!(pixel[0] == 0, dark, "pixel ({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn test_style_validation() {
+        assert!(QrStyle::new().with_module_size(0).is_err());
+        assert!(QrStyle::new().with_quiet_zone(MAX_BORDER_SIZE + 1).is_err());
+        #[cfg(feature = "image")]
+        assert!(QrStyle::new().with_logo("").is_err());
+        assert!(QrStyle::new().validate().is_ok());
+    }
+}
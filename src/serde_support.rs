@@ -0,0 +1,234 @@
+//! Serialization support behind the `serde` feature.
+//!
+//! Styles and configurations serialize as plain maps with every field optional on input; missing
+//! fields take their [Default] values. Colors are written as `"#rrggbb"` strings and accept either a
+//! hex string in any format understood by [hex_to_rgb] or an `[r, g, b]` array. Enums use lowercase
+//! names (`"rounded"`, `"high"`), and [Version] and [Mask] are plain numbers.
+//!
+//! Deserializing a configuration runs its `validate()` method, so an invalid preset is rejected
+//! when it is loaded rather than when it is first rendered. [Caption] fields are skipped because
+//! the font data has to be supplied at runtime.
+//!
+//! The deprecated configuration structs borrow their string fields from the input and can only be
+//! deserialized from formats that support borrowing, such as `serde_json::from_str` with unescaped
+//! strings. [QrStyle] and [OutputConfig] own their strings when needed and work with any format.
+//!
+//! [Caption]: crate::helper::Caption
+//! [OutputConfig]: crate::helper::OutputConfig
+//! [QrStyle]: crate::render::QrStyle
+#![allow(deprecated)]
+
+use crate::helper::hex_to_rgb;
+#[cfg(feature = "image")]
+use crate::helper::{FrameQrConfig, FrameQrSaveConfig, FrameQrSvgConfig, OutputConfig, QrConfig};
+use crate::qrcode::{Mask, Version};
+use crate::render::QrStyle;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+// Serializes an RGB color as a "#rrggbb" string and deserializes it from a hex string or an array.
+pub(crate) mod color {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        color: &[u8; 3],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let [r, g, b] = *color;
+        serializer.serialize_str(&format!("#{:02x}{:02x}{:02x}", r, g, b))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[u8; 3], D::Error> {
+        struct ColorVisitor;
+
+        impl<'de> de::Visitor<'de> for ColorVisitor {
+            type Value = [u8; 3];
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a hex color string or an [r, g, b] array")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<[u8; 3], E> {
+                hex_to_rgb(value).map_err(E::custom)
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<[u8; 3], A::Error> {
+                let mut color = [0u8; 3];
+                for (i, channel) in color.iter_mut().enumerate() {
+                    *channel = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+                }
+                if seq.next_element::<u8>()?.is_some() {
+                    return Err(de::Error::invalid_length(4, &self));
+                }
+                Ok(color)
+            }
+        }
+
+        deserializer.deserialize_any(ColorVisitor)
+    }
+}
+
+impl Serialize for Version {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.value())
+    }
+}
+
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = u8::deserialize(deserializer)?;
+        if !(Version::MIN.value()..=Version::MAX.value()).contains(&value) {
+            return Err(de::Error::custom(format!(
+                "version {} is out of range [1, 40]",
+                value
+            )));
+        }
+        Ok(Version::new(value))
+    }
+}
+
+impl Serialize for Mask {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.value())
+    }
+}
+
+impl<'de> Deserialize<'de> for Mask {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = u8::deserialize(deserializer)?;
+        if value > 7 {
+            return Err(de::Error::custom(format!(
+                "mask {} is out of range [0, 7]",
+                value
+            )));
+        }
+        Ok(Mask::new(value))
+    }
+}
+
+// Implements `Serialize` and `Deserialize` for a type whose derived implementations were generated
+// as inherent functions with `#[serde(remote = "Self")]`, running `validate()` after deserializing.
+macro_rules! impl_validated_serde {
+    ($($ty:ident $(<$lt:lifetime>)?),* $(,)?) => {$(
+        impl$(<$lt>)? Serialize for $ty$(<$lt>)? {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                $ty::serialize(self, serializer)
+            }
+        }
+
+        impl<'de $(: $lt, $lt)?> Deserialize<'de> for $ty$(<$lt>)? {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = $ty::deserialize(deserializer)?;
+                value.validate().map_err(de::Error::custom)?;
+                Ok(value)
+            }
+        }
+    )*};
+}
+
+impl_validated_serde!(QrStyle<'a>);
+
+#[cfg(feature = "image")]
+impl_validated_serde!(
+    OutputConfig<'a>,
+    QrConfig,
+    FrameQrConfig<'a>,
+    FrameQrSvgConfig<'a>,
+    FrameQrSaveConfig<'a>,
+);
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "image")]
+    use crate::helper::{FrameQrConfig, FrameStyle, ImageOutputFormat, OutputConfig};
+    #[cfg(feature = "image")]
+    use crate::qrcode::QrCodeEcc;
+    use crate::qrcode::{Mask, Version};
+    use crate::render::QrStyle;
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn test_style_round_trip() {
+        let style = QrStyle::new()
+            .with_fg_color([255, 165, 0])
+            .with_module_size(6)
+            .unwrap()
+            .with_ecc(QrCodeEcc::Quartile)
+            .with_logo("logo.png")
+            .unwrap()
+            .with_frame_style(FrameStyle::Rounded);
+
+        let json = serde_json::to_string(&style).unwrap();
+        assert!(json.contains("\"fg_color\":\"#ffa500\""));
+        assert!(json.contains("\"ecc\":\"quartile\""));
+        assert!(json.contains("\"frame_style\":\"rounded\""));
+        assert!(!json.contains("caption"));
+
+        let parsed: QrStyle = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.fg_color, [255, 165, 0]);
+        assert_eq!(parsed.module_size, 6);
+        assert_eq!(parsed.ecc, QrCodeEcc::Quartile);
+        assert_eq!(parsed.logo_path.as_deref(), Some("logo.png"));
+        assert_eq!(parsed.frame_style, FrameStyle::Rounded);
+    }
+
+    #[test]
+    fn test_deserialize_accepts_hex_and_arrays_and_validates() {
+        let style: QrStyle =
+            serde_json::from_str(r#"{"fg_color": "FF0000", "bg_color": [0, 0, 255]}"#).unwrap();
+        assert_eq!(style.fg_color, [255, 0, 0]);
+        assert_eq!(style.bg_color, [0, 0, 255]);
+        assert_eq!(style.quiet_zone, QrStyle::default().quiet_zone);
+
+        assert!(serde_json::from_str::<QrStyle>(r##"{"fg_color": "#GG0000"}"##).is_err());
+        assert!(serde_json::from_str::<QrStyle>(r#"{"bg_color": [1, 2]}"#).is_err());
+        let err = serde_json::from_str::<QrStyle>(r#"{"module_size": 0}"#).unwrap_err();
+        assert!(err.to_string().contains("Module size"));
+
+        #[cfg(feature = "image")]
+        {
+            // Escaped strings cannot be borrowed, so owned strings must work too
+            let output: OutputConfig =
+                serde_json::from_str(r#"{"directory_path": "out\/qr", "file_name": "a"}"#).unwrap();
+            assert_eq!(output.directory_path, "out/qr");
+            assert_eq!(output.format, ImageOutputFormat::Png);
+            let output: OutputConfig =
+                serde_json::from_str(r#"{"format": {"jpeg": {"quality": 80}}}"#).unwrap();
+            assert_eq!(output.format, ImageOutputFormat::Jpeg { quality: 80 });
+            assert!(serde_json::from_str::<OutputConfig>(
+                r#"{"format": {"jpeg": {"quality": 0}}}"#
+            )
+            .is_err());
+            assert!(serde_json::from_str::<OutputConfig>(r#"{"format": "webp"}"#).is_ok());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    #[allow(deprecated)]
+    fn test_legacy_config_deserialize() {
+        let config: FrameQrConfig = serde_json::from_str(
+            r##"{"logo_path": "logo.png", "ecc": "medium", "qr_color": "#00ff00", "file_name": "x"}"##,
+        )
+        .unwrap();
+        assert_eq!(config.logo_path, "logo.png");
+        assert_eq!(config.ecc, QrCodeEcc::Medium);
+        assert_eq!(config.qr_color, [0, 255, 0]);
+        assert_eq!(config.file_name, Some("x"));
+
+        assert!(serde_json::from_str::<FrameQrConfig>("{}").is_err());
+    }
+
+    #[test]
+    fn test_version_and_mask() {
+        assert_eq!(serde_json::to_string(&Version::new(7)).unwrap(), "7");
+        assert_eq!(serde_json::from_str::<Version>("40").unwrap(), Version::MAX);
+        assert!(serde_json::from_str::<Version>("41").is_err());
+        assert_eq!(serde_json::from_str::<Mask>("7").unwrap(), Mask::new(7));
+        assert!(serde_json::from_str::<Mask>("8").is_err());
+    }
+}
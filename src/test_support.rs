@@ -0,0 +1,42 @@
+//! Fixtures shared by the unit tests.
+use image::{Rgba, RgbaImage};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Temporary directory named after a test, removed with its contents when dropped.
+///
+/// Each test passes its own name so that tests running in parallel never share a directory.
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    pub(crate) fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("qirust-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Writes a logo of a single color into the directory and returns its path.
+    pub(crate) fn write_logo(
+        &self,
+        name: &str,
+        width: u32,
+        height: u32,
+        color: [u8; 4],
+    ) -> PathBuf {
+        let path = self.0.join(name);
+        RgbaImage::from_pixel(width, height, Rgba(color))
+            .save(&path)
+            .unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.0).ok();
+    }
+}
@@ -0,0 +1,91 @@
+#!/usr/bin/env python3
+"""Writes blocks.ttf, a minimal TrueType font for caption tests.
+
+Every printable ASCII character except space is drawn as the same solid rectangle, which is enough
+to lay out and rasterize text without shipping a real font. "." and "i" are drawn as a narrow bar,
+for captions much narrower than they are tall. Run from this directory to regenerate.
+"""
+import struct
+
+UNITS_PER_EM = 1000
+ASCENDER, DESCENDER = 800, -200
+BLOCK = (50, 0, 550, 700)  # xMin, yMin, xMax, yMax
+NARROW = (50, 0, 150, 700)
+ADVANCES = [(500, 0), (500, 0), (600, BLOCK[0]), (200, NARROW[0])]  # .notdef, space, block, narrow
+NARROW_CHARS = ".i"
+
+
+def checksum(data):
+    data += b"\0" * (-len(data) % 4)
+    return sum(struct.unpack(">%dI" % (len(data) // 4), data)) & 0xFFFFFFFF
+
+
+def rectangle_glyph(bounds):
+    x_min, y_min, x_max, y_max = bounds
+    points = [(x_min, y_min), (x_min, y_max), (x_max, y_max), (x_max, y_min)]
+    data = struct.pack(">hhhhh", 1, x_min, y_min, x_max, y_max)
+    data += struct.pack(">HH", len(points) - 1, 0)  # end point of the contour, no instructions
+    data += bytes([0x01] * len(points))  # on-curve points with 16-bit coordinates
+    previous = (0, 0)
+    xs, ys = b"", b""
+    for x, y in points:
+        xs += struct.pack(">h", x - previous[0])
+        ys += struct.pack(">h", y - previous[1])
+        previous = (x, y)
+    data += xs + ys
+    return data + b"\0" * (-len(data) % 4)
+
+
+def cmap():
+    glyphs = [1] + [3 if chr(c) in NARROW_CHARS else 2 for c in range(0x21, 0x7E + 1)]
+    # Two segments: printable ASCII through the glyph array, then the required 0xFFFF terminator.
+    # The length is patched in below.
+    subtable = struct.pack(">7H", 4, 0, 0, 4, 4, 1, 0)
+    subtable += struct.pack(">HHH", 0x7E, 0xFFFF, 0)  # end codes, reserved pad
+    subtable += struct.pack(">HH", 0x20, 0xFFFF)  # start codes
+    subtable += struct.pack(">hh", 0, 1)  # id deltas
+    subtable += struct.pack(">HH", 4, 0)  # id range offsets
+    subtable += struct.pack(">%dH" % len(glyphs), *glyphs)
+    subtable = subtable[:2] + struct.pack(">H", len(subtable)) + subtable[4:]
+    return struct.pack(">HHHHI", 0, 1, 3, 1, 12) + subtable
+
+
+def main():
+    block, narrow = rectangle_glyph(BLOCK), rectangle_glyph(NARROW)
+    tables = {
+        b"cmap": cmap(),
+        b"glyf": block + narrow,
+        b"head": struct.pack(
+            ">IIIIHHqqhhhhHHhhh",
+            0x00010000, 0x00010000, 0, 0x5F0F3CF5, 0x000B, UNITS_PER_EM, 0, 0,
+            *BLOCK, 0, 8, 2, 0, 0,
+        ),
+        b"hhea": struct.pack(
+            ">IhhhHhhhhhh5hH",
+            0x00010000, ASCENDER, DESCENDER, 0, 600, 0, 0, BLOCK[2], 1, 0, 0,
+            0, 0, 0, 0, 0, len(ADVANCES),
+        ),
+        b"hmtx": b"".join(struct.pack(">Hh", *metric) for metric in ADVANCES),
+        b"loca": struct.pack(
+            ">5H", 0, 0, 0, len(block) // 2, (len(block) + len(narrow)) // 2
+        ),
+        b"maxp": struct.pack(">IHHHHHHHHHHHHHH", 0x00010000, 4, 4, 1, 0, 0, 2, *[0] * 8),
+    }
+
+    offset = 12 + 16 * len(tables)
+    directory = struct.pack(">IHHHH", 0x00010000, len(tables), 64, 2, 16 * len(tables) - 64)
+    body = b""
+    for tag in sorted(tables):
+        data = tables[tag]
+        directory += struct.pack(">4sIII", tag, checksum(data), offset + len(body), len(data))
+        body += data + b"\0" * (-len(data) % 4)
+
+    font = bytearray(directory + body)
+    head = offset + body.index(tables[b"head"])
+    font[head + 8:head + 12] = struct.pack(">I", (0xB1B0AFBA - checksum(bytes(font))) & 0xFFFFFFFF)
+    with open("blocks.ttf", "wb") as out:
+        out.write(font)
+
+
+if __name__ == "__main__":
+    main()